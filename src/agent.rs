@@ -0,0 +1,644 @@
+use crate::events::AppEvent;
+use ssh_encoding::Encode;
+use ssh_key::{PrivateKey, PublicKey};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// 保存 ssh-agent 套接字路径的环境变量
+pub const AUTH_SOCK_ENV: &str = "SSH_AUTH_SOCK";
+/// 添加密钥时默认的有效期（分钟）
+pub const DEFAULT_LIFETIME_MINS: u64 = 60;
+
+/// ssh-agent 协议的消息类型，参见 draft-miller-ssh-agent
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH_AGENT_SUCCESS: u8 = 6;
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+const SSH_AGENTC_ADD_IDENTITY: u8 = 17;
+const SSH_AGENTC_ADD_ID_CONSTRAINED: u8 = 25;
+const SSH_AGENT_CONSTRAIN_LIFETIME: u8 = 1;
+/// 单条响应的最大长度，防止异常的长度字段导致分配过多内存
+const MAX_MESSAGE_LEN: usize = 256 * 1024;
+/// 读写套接字的超时
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+/// 界面中刷新已加载密钥的间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum AgentError {
+    #[error("未检测到 ssh-agent（{AUTH_SOCK_ENV} 未设置）")]
+    NotRunning,
+    #[cfg(not(unix))]
+    #[error("当前系统不支持连接 ssh-agent")]
+    Unsupported,
+    #[error("无法连接 ssh-agent {}: {source}", .socket.display())]
+    Connect {
+        socket: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("与 ssh-agent 通信失败: {0}")]
+    Io(#[from] io::Error),
+    #[error("ssh-agent 返回了无法识别的响应")]
+    Protocol,
+    #[error("ssh-agent 拒绝了请求")]
+    Refused,
+    #[error("无法读取密钥 {path}: {message}")]
+    Key { path: String, message: String },
+    #[error("密钥口令错误")]
+    WrongPassphrase,
+}
+
+/// ssh-agent 中已加载的密钥
+#[derive(Debug, Clone)]
+pub struct Identity {
+    /// SSH 线路格式的公钥
+    pub key_blob: Vec<u8>,
+    pub comment: String,
+}
+
+impl Identity {
+    /// 获取公钥的 SHA256 指纹
+    ///
+    /// # 返回
+    /// 返回形如 `SHA256:...` 的指纹，公钥格式无法识别时为 None
+    pub fn fingerprint(&self) -> Option<String> {
+        PublicKey::from_bytes(&self.key_blob)
+            .ok()
+            .map(|key| key.fingerprint(Default::default()).to_string())
+    }
+}
+
+/// 通过 `SSH_AUTH_SOCK` 与 ssh-agent 通信的客户端，每个请求使用单独的连接
+#[derive(Debug, Clone)]
+pub struct AgentClient {
+    socket: PathBuf,
+}
+
+impl AgentClient {
+    /// 创建连接到指定套接字的客户端
+    ///
+    /// # 参数
+    /// - `socket`: ssh-agent 的套接字路径
+    ///
+    /// # 返回
+    /// 返回客户端，创建时不会连接
+    pub fn new(socket: PathBuf) -> Self {
+        Self { socket }
+    }
+
+    /// 使用 `SSH_AUTH_SOCK` 创建客户端
+    ///
+    /// # 返回
+    /// 返回 Result，成功为客户端，环境变量未设置时为 Err
+    pub fn from_env() -> Result<Self, AgentError> {
+        std::env::var_os(AUTH_SOCK_ENV)
+            .filter(|socket| !socket.is_empty())
+            .map(|socket| Self::new(PathBuf::from(socket)))
+            .ok_or(AgentError::NotRunning)
+    }
+
+    /// 列出 ssh-agent 中已加载的密钥
+    ///
+    /// # 返回
+    /// 返回 Result，成功为已加载的密钥，通信失败时为 Err
+    pub fn identities(&self) -> Result<Vec<Identity>, AgentError> {
+        let response = self.request(&[SSH_AGENTC_REQUEST_IDENTITIES])?;
+        let mut reader = response.as_slice();
+        if read_u8(&mut reader)? != SSH_AGENT_IDENTITIES_ANSWER {
+            return Err(AgentError::Protocol);
+        }
+        let count = read_u32(&mut reader)?;
+        let mut identities = Vec::new();
+        for _ in 0..count {
+            let key_blob = read_string(&mut reader)?.to_vec();
+            let comment = String::from_utf8_lossy(read_string(&mut reader)?).to_string();
+            identities.push(Identity { key_blob, comment });
+        }
+        Ok(identities)
+    }
+
+    /// 将私钥添加到 ssh-agent
+    ///
+    /// # 参数
+    /// - `key`: 已解密的私钥
+    /// - `lifetime`: 有效期，到期后 ssh-agent 自动删除密钥，None 表示一直保留
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，ssh-agent 拒绝或通信失败时为 Err
+    pub fn add_identity(
+        &self,
+        key: &PrivateKey,
+        lifetime: Option<Duration>,
+    ) -> Result<(), AgentError> {
+        let mut message = zeroize::Zeroizing::new(Vec::new());
+        message.push(if lifetime.is_some() {
+            SSH_AGENTC_ADD_ID_CONSTRAINED
+        } else {
+            SSH_AGENTC_ADD_IDENTITY
+        });
+        // 私钥部分的编码与 OpenSSH 私钥文件中的相同：算法名、密钥数据、注释
+        key.key_data()
+            .encode(&mut *message)
+            .and_then(|_| key.comment().encode(&mut *message))
+            .map_err(|_| AgentError::Protocol)?;
+        if let Some(lifetime) = lifetime {
+            message.push(SSH_AGENT_CONSTRAIN_LIFETIME);
+            let secs = u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX);
+            message.extend_from_slice(&secs.to_be_bytes());
+        }
+
+        match self.request(&message)?.first() {
+            Some(&SSH_AGENT_SUCCESS) => Ok(()),
+            Some(&SSH_AGENT_FAILURE) => Err(AgentError::Refused),
+            _ => Err(AgentError::Protocol),
+        }
+    }
+
+    /// 发送一条请求并读取响应
+    ///
+    /// # 参数
+    /// - `payload`: 消息类型和内容，不包括长度字段
+    ///
+    /// # 返回
+    /// 返回 Result，成功为响应的消息类型和内容，通信失败时为 Err
+    #[cfg(unix)]
+    fn request(&self, payload: &[u8]) -> Result<Vec<u8>, AgentError> {
+        use std::os::unix::net::UnixStream;
+
+        let mut stream =
+            UnixStream::connect(&self.socket).map_err(|source| AgentError::Connect {
+                socket: self.socket.clone(),
+                source,
+            })?;
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        let len = u32::try_from(payload.len()).map_err(|_| AgentError::Protocol)?;
+        stream.write_all(&len.to_be_bytes())?;
+        stream.write_all(payload)?;
+        stream.flush()?;
+
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len == 0 || len > MAX_MESSAGE_LEN {
+            return Err(AgentError::Protocol);
+        }
+        let mut response = vec![0u8; len];
+        stream.read_exact(&mut response)?;
+        Ok(response)
+    }
+
+    #[cfg(not(unix))]
+    fn request(&self, _payload: &[u8]) -> Result<Vec<u8>, AgentError> {
+        Err(AgentError::Unsupported)
+    }
+}
+
+/// 读取 OpenSSH 格式的私钥文件，加密的私钥不会被解密
+///
+/// # 参数
+/// - `path`: 私钥路径，支持 `~`
+///
+/// # 返回
+/// 返回 Result，成功为私钥，文件无法读取或格式无法识别时为 Err
+pub fn load_key(path: &str) -> Result<PrivateKey, AgentError> {
+    let key_error = |message: String| AgentError::Key {
+        path: path.to_string(),
+        message,
+    };
+    let content =
+        fs::read(shellexpand::tilde(path).as_ref()).map_err(|e| key_error(e.to_string()))?;
+    PrivateKey::from_openssh(content)
+        .map_err(|_| key_error("仅支持 OpenSSH 格式的私钥".to_string()))
+}
+
+/// 获取私钥对应的公钥
+///
+/// OpenSSH 格式的私钥文件中公钥未加密，无需口令；
+/// 其他格式的私钥读取同名的 `.pub` 文件。
+///
+/// # 参数
+/// - `path`: 私钥路径，支持 `~`
+///
+/// # 返回
+/// 返回 Result，成功为 SSH 线路格式的公钥，无法读取时为 Err
+pub fn public_key_blob(path: &str) -> Result<Vec<u8>, AgentError> {
+    let public_key = match load_key(path) {
+        Ok(key) => key.public_key().clone(),
+        Err(e) => {
+            let pub_path = format!("{}.pub", shellexpand::tilde(path));
+            PublicKey::read_openssh_file(pub_path.as_ref()).map_err(|_| e)?
+        }
+    };
+    public_key.to_bytes().map_err(|_| AgentError::Protocol)
+}
+
+/// 解密私钥
+///
+/// # 参数
+/// - `key`: 私钥
+/// - `passphrase`: 口令，私钥未加密时忽略
+///
+/// # 返回
+/// 返回 Result，成功为解密后的私钥，口令错误时为 Err
+pub fn decrypt_key(key: &PrivateKey, passphrase: &str) -> Result<PrivateKey, AgentError> {
+    if key.is_encrypted() {
+        key.decrypt(passphrase)
+            .map_err(|_| AgentError::WrongPassphrase)
+    } else {
+        Ok(key.clone())
+    }
+}
+
+/// 主机密钥在 ssh-agent 中的状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyStatus {
+    /// 没有可用的 ssh-agent
+    NoAgent,
+    /// 已加载
+    Loaded,
+    /// 未加载
+    NotLoaded,
+    /// 无法确定，附带错误信息
+    Unknown(String),
+}
+
+/// 一次查询 ssh-agent 的结果，由后台线程通过 `AppEvent::AgentRefreshed` 送回主循环
+#[derive(Debug, Clone)]
+pub struct AgentSnapshot {
+    /// 开始查询时管理器的版本
+    generation: u64,
+    loaded: Result<Vec<Vec<u8>>, String>,
+    key_blobs: HashMap<String, Result<Vec<u8>, String>>,
+}
+
+impl AgentSnapshot {
+    /// 查询 ssh-agent 中已加载的密钥，并读取配置中各密钥的公钥
+    ///
+    /// # 参数
+    /// - `client`: ssh-agent 客户端
+    /// - `generation`: 管理器的版本
+    /// - `keys`: 配置中使用的密钥路径
+    ///
+    /// # 返回
+    /// 返回查询结果
+    fn query(client: &AgentClient, generation: u64, keys: &[String]) -> Self {
+        Self {
+            generation,
+            loaded: client
+                .identities()
+                .map(|identities| identities.into_iter().map(|i| i.key_blob).collect())
+                .map_err(|e| e.to_string()),
+            key_blobs: keys
+                .iter()
+                .map(|key| (key.clone(), public_key_blob(key).map_err(|e| e.to_string())))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AgentManager {
+    client: Option<AgentClient>,
+    /// 已加载的公钥，上次查询失败时为错误信息
+    loaded: Option<Result<Vec<Vec<u8>>, String>>,
+    /// 按配置中的密钥路径保存的公钥，无法读取时为错误信息
+    key_blobs: HashMap<String, Result<Vec<u8>, String>>,
+    /// 上次查询的时间
+    last_refresh: Option<Instant>,
+    /// 本次运行中选择不添加到 ssh-agent 的密钥
+    skipped: HashSet<String>,
+    /// 添加密钥时递增，丢弃添加之前开始的查询结果
+    generation: u64,
+}
+
+impl AgentManager {
+    /// 创建新的 ssh-agent 管理器
+    ///
+    /// # 返回
+    /// 返回管理器，`SSH_AUTH_SOCK` 未设置时不可用
+    pub fn new() -> Self {
+        Self {
+            client: AgentClient::from_env().ok(),
+            ..Self::default()
+        }
+    }
+
+    /// 检查是否需要重新查询已加载的密钥
+    ///
+    /// # 返回
+    /// 返回 true 表示从未查询过或距上次查询已超过刷新间隔
+    pub fn is_due(&self) -> bool {
+        self.client.is_some()
+            && self
+                .last_refresh
+                .map_or(true, |at| at.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    /// 在后台线程中查询 ssh-agent 中已加载的密钥，并读取配置中各密钥的公钥
+    ///
+    /// 查询完成时推送 `AppEvent::AgentRefreshed`，ssh-agent 响应缓慢时不会阻塞界面。
+    ///
+    /// # 参数
+    /// - `keys`: 配置中使用的密钥路径
+    /// - `sender`: 主循环的事件通道
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，无法启动后台线程时为 Err
+    pub fn start_refresh(&mut self, keys: Vec<String>, sender: Sender<AppEvent>) -> io::Result<()> {
+        let Some(client) = self.client.clone() else {
+            return Ok(());
+        };
+        self.last_refresh = Some(Instant::now());
+        let generation = self.generation;
+        std::thread::Builder::new()
+            .name("mssh-agent".to_string())
+            .spawn(move || {
+                let snapshot = AgentSnapshot::query(&client, generation, &keys);
+                // 主循环已退出时忽略发送失败
+                let _ = sender.send(AppEvent::AgentRefreshed(snapshot));
+            })?;
+        Ok(())
+    }
+
+    /// 保存后台查询的结果
+    ///
+    /// # 参数
+    /// - `snapshot`: 查询结果，查询期间添加过密钥时丢弃
+    pub fn apply_refresh(&mut self, snapshot: AgentSnapshot) {
+        if snapshot.generation == self.generation {
+            self.loaded = Some(snapshot.loaded);
+            self.key_blobs = snapshot.key_blobs;
+        }
+    }
+
+    /// 获取密钥在 ssh-agent 中的状态
+    ///
+    /// # 参数
+    /// - `key`: 配置中的密钥路径
+    ///
+    /// # 返回
+    /// 返回密钥状态，尚未查询过该密钥时为 Unknown
+    pub fn key_status(&self, key: &str) -> KeyStatus {
+        if self.client.is_none() {
+            return KeyStatus::NoAgent;
+        }
+        let loaded = match &self.loaded {
+            Some(Ok(loaded)) => loaded,
+            Some(Err(e)) => return KeyStatus::Unknown(e.clone()),
+            None => return KeyStatus::Unknown("正在查询".to_string()),
+        };
+        match self.key_blobs.get(key) {
+            Some(Ok(blob)) if loaded.contains(blob) => KeyStatus::Loaded,
+            Some(Ok(_)) => KeyStatus::NotLoaded,
+            Some(Err(e)) => KeyStatus::Unknown(e.clone()),
+            None => KeyStatus::Unknown("正在查询".to_string()),
+        }
+    }
+
+    /// 检查连接前是否需要提示添加密钥
+    ///
+    /// # 参数
+    /// - `key`: 配置中的密钥路径
+    ///
+    /// # 返回
+    /// 返回 true 表示密钥未加载，且本次运行中没有选择跳过
+    pub fn should_offer(&self, key: &str) -> bool {
+        !self.skipped.contains(key) && self.key_status(key) == KeyStatus::NotLoaded
+    }
+
+    /// 本次运行中不再提示添加该密钥
+    ///
+    /// # 参数
+    /// - `key`: 配置中的密钥路径
+    pub fn skip(&mut self, key: &str) {
+        self.skipped.insert(key.to_string());
+    }
+
+    /// 检查私钥是否需要口令
+    ///
+    /// # 参数
+    /// - `key`: 配置中的密钥路径
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 true 表示私钥已加密，无法读取时为 Err
+    pub fn needs_passphrase(&self, key: &str) -> Result<bool, AgentError> {
+        Ok(load_key(key)?.is_encrypted())
+    }
+
+    /// 解密私钥并添加到 ssh-agent
+    ///
+    /// # 参数
+    /// - `key`: 配置中的密钥路径
+    /// - `passphrase`: 私钥口令，未加密的私钥忽略
+    /// - `lifetime`: 有效期
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，读取、解密或添加失败时为 Err
+    pub fn add_key(
+        &mut self,
+        key: &str,
+        passphrase: &str,
+        lifetime: Duration,
+    ) -> Result<(), AgentError> {
+        let client = self.client.as_ref().ok_or(AgentError::NotRunning)?;
+        let private_key = decrypt_key(&load_key(key)?, passphrase)?;
+        client.add_identity(&private_key, Some(lifetime))?;
+        // 立即更新状态，不等待下次刷新
+        self.generation += 1;
+        if let Ok(blob) = private_key.public_key().to_bytes() {
+            if let Some(Ok(loaded)) = &mut self.loaded {
+                loaded.push(blob.clone());
+            }
+            self.key_blobs.insert(key.to_string(), Ok(blob));
+        }
+        Ok(())
+    }
+}
+
+/// 读取一个字节
+fn read_u8(reader: &mut &[u8]) -> Result<u8, AgentError> {
+    let (&byte, rest) = reader.split_first().ok_or(AgentError::Protocol)?;
+    *reader = rest;
+    Ok(byte)
+}
+
+/// 读取大端序的 uint32
+fn read_u32(reader: &mut &[u8]) -> Result<u32, AgentError> {
+    if reader.len() < 4 {
+        return Err(AgentError::Protocol);
+    }
+    let (bytes, rest) = reader.split_at(4);
+    *reader = rest;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// 读取带长度前缀的字符串
+fn read_string<'a>(reader: &mut &'a [u8]) -> Result<&'a [u8], AgentError> {
+    let len = read_u32(reader)? as usize;
+    if reader.len() < len {
+        return Err(AgentError::Protocol);
+    }
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+    Ok(bytes)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+    use std::thread::{self, JoinHandle};
+
+    /// 测试用的未加密私钥，注释为 `mssh-test`
+    fn fixture_key() -> String {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/agent/id_ed25519")
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn put_string(buf: &mut Vec<u8>, bytes: &[u8]) {
+        buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        buf.extend_from_slice(bytes);
+    }
+
+    /// 启动按顺序回复 `responses` 的 ssh-agent，每个连接处理一条请求
+    ///
+    /// 线程结束时返回收到的请求，不包括长度字段；套接字在返回的 TempDir 销毁时删除。
+    fn stub_agent(
+        name: &str,
+        responses: Vec<Vec<u8>>,
+    ) -> (TempDir, PathBuf, JoinHandle<Vec<Vec<u8>>>) {
+        let dir = TempDir::new(name);
+        let socket = dir.join("agent.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut len = [0u8; 4];
+                stream.read_exact(&mut len).unwrap();
+                let mut request = vec![0u8; u32::from_be_bytes(len) as usize];
+                stream.read_exact(&mut request).unwrap();
+                requests.push(request);
+                stream
+                    .write_all(&(response.len() as u32).to_be_bytes())
+                    .unwrap();
+                stream.write_all(&response).unwrap();
+            }
+            requests
+        });
+        (dir, socket, handle)
+    }
+
+    #[test]
+    fn lists_identities() {
+        let blob = public_key_blob(&fixture_key()).unwrap();
+        let mut answer = vec![SSH_AGENT_IDENTITIES_ANSWER];
+        answer.extend_from_slice(&2u32.to_be_bytes());
+        put_string(&mut answer, &blob);
+        put_string(&mut answer, b"mssh-test");
+        put_string(&mut answer, b"not a key");
+        put_string(&mut answer, b"other");
+        let (_dir, socket, agent) = stub_agent("list", vec![answer]);
+
+        let identities = AgentClient::new(socket).identities().unwrap();
+        assert_eq!(agent.join().unwrap(), [vec![SSH_AGENTC_REQUEST_IDENTITIES]]);
+        assert_eq!(identities.len(), 2);
+        assert_eq!(identities[0].key_blob, blob);
+        assert_eq!(identities[0].comment, "mssh-test");
+        assert!(identities[0]
+            .fingerprint()
+            .is_some_and(|f| f.starts_with("SHA256:")));
+        assert_eq!(identities[1].comment, "other");
+        assert_eq!(identities[1].fingerprint(), None);
+    }
+
+    #[test]
+    fn rejects_malformed_identities_answer() {
+        let mut truncated = vec![SSH_AGENT_IDENTITIES_ANSWER];
+        truncated.extend_from_slice(&1u32.to_be_bytes());
+        let (_dir, socket, agent) =
+            stub_agent("malformed", vec![vec![SSH_AGENT_FAILURE], truncated]);
+
+        let client = AgentClient::new(socket);
+        assert!(matches!(client.identities(), Err(AgentError::Protocol)));
+        assert!(matches!(client.identities(), Err(AgentError::Protocol)));
+        agent.join().unwrap();
+    }
+
+    #[test]
+    fn adds_identity_with_lifetime() {
+        let key = load_key(&fixture_key()).unwrap();
+        let (_dir, socket, agent) = stub_agent("add", vec![vec![SSH_AGENT_SUCCESS]]);
+
+        AgentClient::new(socket)
+            .add_identity(&key, Some(Duration::from_secs(3600)))
+            .unwrap();
+
+        let requests = agent.join().unwrap();
+        let mut reader = requests[0].as_slice();
+        assert_eq!(read_u8(&mut reader).unwrap(), SSH_AGENTC_ADD_ID_CONSTRAINED);
+        assert_eq!(read_string(&mut reader).unwrap(), b"ssh-ed25519");
+        let public = read_string(&mut reader).unwrap();
+        assert_eq!(public, key.public_key().key_data().ed25519().unwrap().0);
+        // OpenSSH 的 ed25519 私钥字段为私钥与公钥的拼接
+        assert_eq!(read_string(&mut reader).unwrap().len(), 64);
+        assert_eq!(read_string(&mut reader).unwrap(), b"mssh-test");
+        assert_eq!(read_u8(&mut reader).unwrap(), SSH_AGENT_CONSTRAIN_LIFETIME);
+        assert_eq!(read_u32(&mut reader).unwrap(), 3600);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn refused_identity_is_an_error() {
+        let key = load_key(&fixture_key()).unwrap();
+        let (_dir, socket, agent) = stub_agent("refuse", vec![vec![SSH_AGENT_FAILURE]]);
+
+        let result = AgentClient::new(socket).add_identity(&key, None);
+        assert!(matches!(result, Err(AgentError::Refused)));
+        assert_eq!(agent.join().unwrap()[0][0], SSH_AGENTC_ADD_IDENTITY);
+    }
+
+    #[test]
+    fn manager_reports_loaded_keys() {
+        let key = fixture_key();
+        let mut answer = vec![SSH_AGENT_IDENTITIES_ANSWER];
+        answer.extend_from_slice(&1u32.to_be_bytes());
+        put_string(&mut answer, &public_key_blob(&key).unwrap());
+        put_string(&mut answer, b"mssh-test");
+        let (_dir, socket, agent) = stub_agent("manager", vec![answer]);
+
+        let mut manager = AgentManager {
+            client: Some(AgentClient::new(socket)),
+            ..AgentManager::default()
+        };
+        let (sender, events) = std::sync::mpsc::channel();
+        manager
+            .start_refresh(vec![key.clone(), "/nonexistent/id_rsa".to_string()], sender)
+            .unwrap();
+        assert!(!manager.is_due());
+        let AppEvent::AgentRefreshed(snapshot) = events.recv().unwrap() else {
+            panic!("unexpected event");
+        };
+        manager.apply_refresh(snapshot);
+        agent.join().unwrap();
+
+        assert_eq!(manager.key_status(&key), KeyStatus::Loaded);
+        assert!(!manager.should_offer(&key));
+        assert!(matches!(
+            manager.key_status("/nonexistent/id_rsa"),
+            KeyStatus::Unknown(_)
+        ));
+    }
+}
@@ -1,11 +1,88 @@
-use crate::config::{ConfigManager, SSHConfig};
+use crate::agent::{AgentManager, DEFAULT_LIFETIME_MINS};
+use crate::config::{normalize_group, ConfigError, ConfigManager, SSHConfig, SortMode};
+use crate::events::AppEvent;
 use crate::form_manager::FormManager;
+use crate::group_manager::{parent_group, ListRow};
+use crate::health_manager::{HealthManager, ProbeTarget};
+use crate::history_manager::HistoryManager;
+use crate::key_manager::{
+    complete_path, create_key_dir, default_key_dir, deploy_script, display_path, fix_permissions,
+    insecure_mode, keygen_command, new_key_path, KeyDialog, PathCompletion,
+};
 use crate::message_manager::MessageManager;
 use crate::navigation_manager::{AppMode, NavigationManager};
+use crate::search_manager::SearchMatch;
+use crate::secret::SecretRef;
 use crate::ssh::SSHManager;
+use crate::ssh_config::{self, default_config_path, default_export_path, OpenSshConfig};
+use crate::sync_manager::{SyncManager, SyncResolution, SyncStatus};
+use crate::tunnel_manager::{unix_now, TunnelManager};
+use crate::vault::{self, Vault, MASTER_PASSWORD_ENV};
+use crossterm::{
+    cursor::Show,
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::cmp::Reverse;
+use std::io::Write;
+use std::process::Command;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+use zeroize::Zeroizing;
 
 pub use crate::message_manager::Message;
 
+/// “最近连接”中最多显示的主机数
+const RECENT_LIMIT: usize = 5;
+
+/// 配置文件的外部修改与尚未保存的修改冲突
+#[derive(Debug, Clone)]
+pub struct ConfigConflict {
+    /// 从文件重新加载的配置
+    pub manager: ConfigManager,
+    /// 被打断的模式，保留修改时回到该模式
+    pub previous_mode: AppMode,
+}
+
+/// 输入密码库主密码的提示
+#[derive(Debug, Clone)]
+pub struct VaultPrompt {
+    pub input: Zeroizing<String>,
+    /// 创建密码库时第一次输入的主密码，等待再次输入确认
+    pub first: Option<Zeroizing<String>>,
+    /// 被打断的模式，解锁或跳过后回到该模式
+    pub previous_mode: AppMode,
+}
+
+/// 连接前将密钥添加到 ssh-agent 的提示
+#[derive(Debug, Clone)]
+pub struct AgentPrompt {
+    /// 主机配置中的密钥路径
+    pub key: String,
+    /// 有效期（分钟）
+    pub minutes: String,
+    pub passphrase: Zeroizing<String>,
+    /// 私钥已加密，需要输入口令
+    pub needs_passphrase: bool,
+    /// 正在输入口令，否则正在输入有效期
+    pub editing_passphrase: bool,
+    /// 口令来自密码库，无需输入
+    pub stored_passphrase: bool,
+    /// 被打断的模式，添加或取消后回到该模式
+    pub previous_mode: AppMode,
+}
+
+/// 保存主机前修复私钥权限的提示
+#[derive(Debug, Clone)]
+pub struct KeyPermissionPrompt {
+    /// 主机配置中的密钥路径
+    pub key: String,
+    /// 私钥文件当前的权限
+    pub mode: u32,
+    /// 被打断的模式，保存或取消后回到该模式
+    pub previous_mode: AppMode,
+}
+
 #[derive(Debug, Clone)]
 pub struct App {
     pub config_manager: ConfigManager,
@@ -13,6 +90,23 @@ pub struct App {
     pub navigation: NavigationManager,
     pub form_manager: FormManager,
     pub message_manager: MessageManager,
+    pub tunnel_manager: TunnelManager,
+    pub history_manager: HistoryManager,
+    pub health_manager: HealthManager,
+    pub agent_manager: AgentManager,
+    /// 后台任务向主循环推送事件的通道
+    pub event_sender: Sender<AppEvent>,
+    /// 等待用户处理的配置冲突
+    pub config_conflict: Option<ConfigConflict>,
+    /// 正在输入的密码库主密码
+    pub vault_prompt: Option<VaultPrompt>,
+    /// 连接前添加密钥到 ssh-agent 的提示
+    pub agent_prompt: Option<AgentPrompt>,
+    /// 保存前修复私钥权限的提示
+    pub key_permission_prompt: Option<KeyPermissionPrompt>,
+    /// 主机删除或密码被替换后，等待密码库解锁时移除的条目
+    pub released_secrets: Vec<String>,
+    pub config_error: Option<String>, // 配置文件加载失败的错误信息
 }
 
 impl App {
@@ -20,20 +114,67 @@ impl App {
     ///
     /// # 参数
     /// - `config_path`: 配置文件路径，可选
+    /// - `event_sender`: 主循环的事件通道
     ///
     /// # 返回
     /// 返回 Result，成功为 App 实例，失败为 Err
-    pub fn new(config_path: Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_manager = ConfigManager::new(config_path)?;
-        let ssh_manager = SSHManager::new(config_manager.global_config.clone());
+    pub fn new(
+        config_path: Option<String>,
+        event_sender: Sender<AppEvent>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let config_path = ConfigManager::resolve_path(config_path);
+        let (config_manager, config_error) = match ConfigManager::load(config_path.clone()) {
+            Ok(manager) => (manager, None),
+            Err(e @ (ConfigError::Read { .. } | ConfigError::Parse { .. })) => {
+                // 配置文件损坏时进入只读模式，保证不会覆盖原文件
+                (ConfigManager::read_only(config_path), Some(e.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mut vault = Vault::new(config_manager.config_path());
+        if let Ok(password) = std::env::var(MASTER_PASSWORD_ENV) {
+            // 主密码错误时仍可在界面中重新输入
+            let _ = vault.unlock(&Zeroizing::new(password));
+        }
+        let ssh_manager = SSHManager::new(
+            config_manager.global_config.clone(),
+            vault,
+            config_manager.config_path().to_path_buf(),
+        );
+        let history_manager = HistoryManager::load(config_manager.config_path());
 
-        Ok(Self {
+        let mut navigation = NavigationManager::new();
+        if config_error.is_some() {
+            navigation.set_mode(AppMode::ConfigError);
+        }
+
+        let mut app = Self {
             config_manager,
             ssh_manager,
-            navigation: NavigationManager::new(),
+            navigation,
             form_manager: FormManager::new(),
             message_manager: MessageManager::new(),
-        })
+            tunnel_manager: TunnelManager::new(),
+            history_manager,
+            health_manager: HealthManager::new(),
+            agent_manager: AgentManager::new(),
+            event_sender,
+            config_conflict: None,
+            vault_prompt: None,
+            agent_prompt: None,
+            key_permission_prompt: None,
+            released_secrets: Vec::new(),
+            config_error,
+        };
+        // 有明文密码需要迁移或代理密码保存在密码库中时，启动后先输入主密码
+        if app.config_error.is_none()
+            && !app.ssh_manager.vault.is_unlocked()
+            && (app.config_manager.uses_vault()
+                || app.config_manager.plaintext_password_count() > 0)
+        {
+            app.show_vault_prompt();
+        }
+        Ok(app)
     }
 
     /// 获取当前应用模式
@@ -95,7 +236,7 @@ impl App {
     /// 导航到下一个项目
     pub fn next(&mut self) {
         if self.navigation.is_list_mode() {
-            let len = self.config_manager.configs.len();
+            let len = self.list_rows().len();
             self.navigation.next_item(len);
         }
     }
@@ -103,11 +244,319 @@ impl App {
     /// 导航到上一个项目
     pub fn previous(&mut self) {
         if self.navigation.is_list_mode() {
-            let len = self.config_manager.configs.len();
+            let len = self.list_rows().len();
             self.navigation.previous_item(len);
         }
     }
 
+    /// 获取匹配搜索内容的主机
+    ///
+    /// 没有搜索内容时按原顺序返回所有主机，否则返回按匹配得分排序的搜索结果。
+    ///
+    /// # 返回
+    /// 返回搜索结果列表
+    pub fn filtered_configs(&self) -> Vec<SearchMatch> {
+        self.navigation
+            .search_manager
+            .filter(&self.config_manager.configs)
+    }
+
+    /// 获取列表中显示的行
+    ///
+    /// 没有搜索内容时按分组显示树形列表，否则不分组显示搜索结果。
+    /// `selected_index` 是该列表中的位置，而不是配置列表中的下标。
+    ///
+    /// # 返回
+    /// 返回按显示顺序排列的行
+    pub fn list_rows(&self) -> Vec<ListRow> {
+        if self.navigation.search_manager.is_filtering() {
+            self.filtered_configs()
+                .into_iter()
+                .map(|matched| ListRow::Host {
+                    matched,
+                    depth: 0,
+                    recent: false,
+                })
+                .collect()
+        } else {
+            self.navigation.group_manager.build_rows(
+                &self.config_manager.configs,
+                &self.display_order(),
+                &self.recent_hosts(),
+            )
+        }
+    }
+
+    /// 获取最近连接过且仍然存在的主机
+    ///
+    /// # 返回
+    /// 返回主机下标，最近的在前
+    pub fn recent_hosts(&self) -> Vec<usize> {
+        self.history_manager
+            .recent()
+            .into_iter()
+            .filter_map(|entry| self.config_manager.find_index(&entry.alias))
+            .take(RECENT_LIMIT)
+            .collect()
+    }
+
+    /// 获取按当前排序方式排列的主机下标
+    ///
+    /// 排序只影响显示顺序，主机编号仍然是在配置文件中的位置；
+    /// 取值相同的主机保持配置文件中的顺序。
+    ///
+    /// # 返回
+    /// 返回排序后的主机下标
+    pub fn display_order(&self) -> Vec<usize> {
+        let configs = &self.config_manager.configs;
+        let history = &self.history_manager;
+        let mut order: Vec<usize> = (0..configs.len()).collect();
+        match self.config_manager.global_config.sort_mode {
+            SortMode::Manual => {}
+            SortMode::Alias => order.sort_by_cached_key(|&i| configs[i].alias.to_lowercase()),
+            SortMode::Address => order.sort_by_cached_key(|&i| configs[i].address.clone()),
+            // 从未连接过的主机排在最后
+            SortMode::LastUsed => order.sort_by_cached_key(|&i| {
+                let last = history.last_used(&configs[i].alias);
+                (last.is_none(), Reverse(last))
+            }),
+            SortMode::MostUsed => {
+                order.sort_by_cached_key(|&i| Reverse(history.use_count(&configs[i].alias)))
+            }
+        }
+        order
+    }
+
+    /// 将选中的主机与同一分组中相邻的主机交换位置
+    ///
+    /// 只在手动排序时可用，调整后的顺序会写入配置文件。
+    ///
+    /// # 参数
+    /// - `up`: true 为上移，false 为下移
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，无法移动时为 Err
+    pub fn move_selected(&mut self, up: bool) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.config_manager.global_config.sort_mode.is_manual() {
+            return Err("请先按 Ctrl+W 切换到手动排序".into());
+        }
+        if self.navigation.search_manager.is_filtering() {
+            return Err("搜索时不能调整顺序".into());
+        }
+        let index = match self.selected_row() {
+            Some(ListRow::Host { recent: true, .. }) => {
+                return Err("不能在最近连接中调整顺序".into())
+            }
+            row => row
+                .and_then(|row| row.host_index())
+                .ok_or("只能调整主机的顺序")?,
+        };
+        let configs = &self.config_manager.configs;
+        let group = &configs[index].group;
+        let neighbor = if up {
+            (0..index).rev().find(|&i| configs[i].group == *group)
+        } else {
+            (index + 1..configs.len()).find(|&i| configs[i].group == *group)
+        };
+        let Some(neighbor) = neighbor else {
+            return Ok(());
+        };
+
+        let alias = configs[index].alias.clone();
+        self.config_manager.swap_configs(index, neighbor)?;
+        self.select_host(&alias);
+        Ok(())
+    }
+
+    /// 切换到下一种排序方式并保存
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，保存失败时为 Err
+    pub fn cycle_sort_mode(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let alias = self.get_selected_config().map(|c| c.alias.clone());
+        let global_config = &mut self.config_manager.global_config;
+        let previous = global_config.sort_mode;
+        global_config.sort_mode = previous.next();
+        if let Err(e) = self.config_manager.save_configs() {
+            self.config_manager.global_config.sort_mode = previous;
+            return Err(e.into());
+        }
+        if let Some(alias) = alias {
+            self.select_host(&alias);
+        }
+        Ok(format!(
+            "排序方式: {}",
+            self.config_manager.global_config.sort_mode.label()
+        ))
+    }
+
+    /// 获取选中的行
+    ///
+    /// # 返回
+    /// 返回选中的行，列表为空时为 None
+    pub fn selected_row(&self) -> Option<ListRow> {
+        let rows = self.list_rows();
+        if rows.is_empty() {
+            return None;
+        }
+        let index = self.navigation.get_valid_selected_index(rows.len());
+        rows.into_iter().nth(index)
+    }
+
+    /// 获取选中的分组
+    ///
+    /// # 返回
+    /// 选中分组行时返回该分组，选中主机时返回主机所在的分组，否则为 None
+    pub fn selected_group(&self) -> Option<String> {
+        match self.selected_row()? {
+            ListRow::Group { path, .. } => Some(path),
+            ListRow::Recent { .. } => None,
+            ListRow::Host { matched, .. } => {
+                self.config_manager.configs[matched.index].group.clone()
+            }
+        }
+    }
+
+    /// 在列表中选中指定主机，必要时展开其所在的分组
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    pub fn select_host(&mut self, alias: &str) {
+        if let Some(group) = self
+            .config_manager
+            .configs
+            .iter()
+            .find(|c| c.alias == alias)
+            .and_then(|c| c.group.clone())
+        {
+            self.navigation.group_manager.expand(&group);
+        }
+        self.navigation.selected_index = self
+            .list_rows()
+            .iter()
+            .position(|row| match row {
+                ListRow::Host {
+                    matched,
+                    recent: false,
+                    ..
+                } => self.config_manager.configs[matched.index].alias == alias,
+                _ => false,
+            })
+            .unwrap_or(0);
+    }
+
+    /// 在列表中选中指定分组
+    ///
+    /// # 参数
+    /// - `path`: 分组路径
+    fn select_group(&mut self, path: &str) {
+        if let Some(index) = self
+            .list_rows()
+            .iter()
+            .position(|row| row.group_path() == Some(path))
+        {
+            self.navigation.selected_index = index;
+        }
+    }
+
+    /// 展开或折叠选中的分组
+    pub fn toggle_selected_group(&mut self) {
+        match self.selected_row() {
+            Some(ListRow::Group { path, .. }) => self.navigation.group_manager.toggle(&path),
+            Some(ListRow::Recent { collapsed, .. }) => {
+                self.navigation.group_manager.recent_collapsed = !collapsed
+            }
+            _ => {}
+        }
+    }
+
+    /// 折叠选中的分组；选中主机或已折叠的分组时跳到上级分组
+    pub fn collapse_selected(&mut self) {
+        let parent = match self.selected_row() {
+            Some(ListRow::Group {
+                path,
+                collapsed: false,
+                ..
+            }) => {
+                self.navigation.group_manager.set_collapsed(&path, true);
+                return;
+            }
+            Some(ListRow::Group { path, .. }) => parent_group(&path).map(str::to_string),
+            Some(ListRow::Recent {
+                collapsed: false, ..
+            }) => {
+                self.navigation.group_manager.recent_collapsed = true;
+                return;
+            }
+            // “最近连接”总是列表的第一行
+            Some(ListRow::Host { recent: true, .. }) => {
+                self.navigation.selected_index = 0;
+                return;
+            }
+            Some(ListRow::Host { matched, depth, .. }) if depth > 0 => {
+                self.config_manager.configs[matched.index].group.clone()
+            }
+            _ => None,
+        };
+        if let Some(parent) = parent {
+            self.select_group(&parent);
+        }
+    }
+
+    /// 展开选中的分组
+    pub fn expand_selected(&mut self) {
+        match self.selected_row() {
+            Some(ListRow::Group {
+                path,
+                collapsed: true,
+                ..
+            }) => self.navigation.group_manager.set_collapsed(&path, false),
+            Some(ListRow::Recent {
+                collapsed: true, ..
+            }) => self.navigation.group_manager.recent_collapsed = false,
+            _ => {}
+        }
+    }
+
+    /// 进入搜索模式，保留已有的搜索内容以便继续修改
+    pub fn start_search(&mut self) {
+        self.navigation.set_mode(AppMode::Search);
+    }
+
+    /// 输入搜索字符，选中得分最高的结果
+    ///
+    /// # 参数
+    /// - `c`: 输入的字符
+    pub fn search_input(&mut self, c: char) {
+        self.navigation.search_manager.push(c);
+        self.navigation.selected_index = 0;
+    }
+
+    /// 删除最后一个搜索字符
+    pub fn search_backspace(&mut self) {
+        self.navigation.search_manager.pop();
+        self.navigation.selected_index = 0;
+    }
+
+    /// 结束输入，保留过滤结果回到列表
+    pub fn finish_search(&mut self) {
+        if !self.navigation.search_manager.is_filtering() {
+            self.clear_search();
+        }
+        self.navigation.set_mode(AppMode::List);
+    }
+
+    /// 清除搜索内容，并在完整列表中保持选中当前主机
+    pub fn clear_search(&mut self) {
+        let selected = self.get_selected_config().map(|c| c.alias.clone());
+        self.navigation.search_manager.clear();
+        match selected {
+            Some(alias) => self.select_host(&alias),
+            None => self.navigation.selected_index = 0,
+        }
+        self.navigation.set_mode(AppMode::List);
+    }
+
     /// 切换焦点
     pub fn toggle_focus(&mut self) {
         self.navigation.toggle_focus(2); // 2个焦点区域：列表和详情
@@ -153,10 +602,15 @@ impl App {
     /// # 返回
     /// 返回 Result，成功为 Ok(())，失败为 Err
     pub fn save_config(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let config = self.form_manager.validate_and_create_config()?;
+        let mut config = self.form_manager.validate_and_create_config()?;
+        let alias = config.alias.clone();
+        if let Some(proxy) = &mut config.proxy {
+            self.seal_password(&mut proxy.password, &vault::proxy_secret_id(Some(&alias)))?;
+        }
 
+        let editing_host = self.form_manager.get_editing_host().cloned();
         let result = if self.form_manager.is_editing() {
-            if let Some(editing_host) = self.form_manager.get_editing_host() {
+            if let Some(editing_host) = &editing_host {
                 self.config_manager.update_config(editing_host, config)
             } else {
                 return Err("正在编辑的主机不存在".into());
@@ -165,13 +619,84 @@ impl App {
             self.config_manager.add_config(config)
         };
 
+        let previous = editing_host
+            .as_deref()
+            .and_then(|host| self.config_manager.find_index(host))
+            .and_then(|index| self.config_manager.configs[index].proxy.as_ref())
+            .and_then(|proxy| proxy.password.clone());
         result?;
+        self.release_secret(previous.as_deref());
+        if let Some(editing_host) = editing_host {
+            // 历史记录写入失败不影响保存配置
+            let _ = self.history_manager.rename(&editing_host, &alias);
+        }
         self.navigation.return_to_list();
         self.form_manager.clear();
+        self.select_host(&alias);
 
         Ok(())
     }
 
+    /// 表单中的私钥权限过宽时，提示在保存前修复
+    ///
+    /// # 返回
+    /// 返回 true 表示已显示提示，保存在提示中确认后进行
+    pub fn offer_key_permission_fix(&mut self) -> bool {
+        // 校验失败时交给 save_config 显示错误
+        let Some(key) = self
+            .form_manager
+            .validate_and_create_config()
+            .ok()
+            .and_then(|config| config.key)
+        else {
+            return false;
+        };
+        let Some(mode) = insecure_mode(&key) else {
+            return false;
+        };
+        self.key_permission_prompt = Some(KeyPermissionPrompt {
+            key,
+            mode,
+            previous_mode: self.mode().clone(),
+        });
+        self.navigation.set_mode(AppMode::KeyPermission);
+        true
+    }
+
+    /// 按提示中的选择修复私钥权限后保存配置
+    ///
+    /// # 参数
+    /// - `fix`: 是否将私钥的权限改为 `0600`
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，修改权限或保存失败时为 Err
+    pub fn resolve_key_permission(
+        &mut self,
+        fix: bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let Some(prompt) = self.key_permission_prompt.clone() else {
+            return Ok(String::new());
+        };
+        self.cancel_key_permission_prompt();
+        if fix {
+            fix_permissions(&prompt.key)
+                .map_err(|e| format!("无法修改 {} 的权限: {e}", prompt.key))?;
+        }
+        self.save_config()?;
+        Ok(if fix {
+            format!("已将 {} 的权限改为 0600，配置保存成功", prompt.key)
+        } else {
+            "配置保存成功".to_string()
+        })
+    }
+
+    /// 关闭修复私钥权限的提示，回到被打断的模式
+    pub fn cancel_key_permission_prompt(&mut self) {
+        if let Some(prompt) = self.key_permission_prompt.take() {
+            self.navigation.set_mode(prompt.previous_mode);
+        }
+    }
+
     /// 删除配置
     ///
     /// # 返回
@@ -179,7 +704,12 @@ impl App {
     pub fn delete_config(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(config) = self.get_selected_config() {
             let alias = config.alias.clone();
+            let password = config.proxy.as_ref().and_then(|p| p.password.clone());
             self.config_manager.remove_config(&alias)?; // 删除失败自动返回 Err
+            self.release_secret(password.as_deref());
+            if self.tunnel_manager.get(&alias).is_some() {
+                self.tunnel_manager.stop(&alias)?;
+            }
         }
         self.navigation.return_to_list();
         Ok(())
@@ -190,41 +720,488 @@ impl App {
     /// # 返回
     /// 返回 Result，成功为 Ok(())，失败为 Err
     pub fn save_proxy_config(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let proxy_config = self.form_manager.create_proxy_config()?;
+        let mut proxy_config = self.form_manager.create_proxy_config()?;
+        self.seal_password(&mut proxy_config.password, &vault::proxy_secret_id(None))?;
         self.config_manager.global_config.proxy = proxy_config;
         self.config_manager.save_configs()?;
         self.navigation.return_to_list();
         Ok(())
     }
+
+    /// 将表单中输入的明文代理密码移入密码库
+    ///
+    /// 密码库未解锁时打开主密码提示，解锁后回到表单重新保存。
+    ///
+    /// # 参数
+    /// - `password`: 表单中的代理密码，移入后替换为引用
+    /// - `id`: 保存到的条目名称
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，密码库未解锁或写入失败时为 Err
+    fn seal_password(
+        &mut self,
+        password: &mut Option<String>,
+        id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if password.as_deref().is_some_and(vault::is_plaintext)
+            && !self.ssh_manager.vault.is_unlocked()
+        {
+            self.show_vault_prompt();
+            return Err("代理密码需要保存到密码库，请先输入主密码".into());
+        }
+        self.ssh_manager.vault.seal(password, id)?;
+        Ok(())
+    }
+
+    /// 主机删除或代理密码被替换后，移除不再使用的密码库条目
+    ///
+    /// 密码库未解锁时先记下条目，解锁后再移除。
+    ///
+    /// # 参数
+    /// - `password`: 主机原来的代理密码
+    fn release_secret(&mut self, password: Option<&str>) {
+        if let Some(id) = vault::host_secret_id(password) {
+            self.released_secrets.push(id.to_string());
+        }
+        self.remove_released_secrets();
+    }
+
+    /// 密码库已解锁时，移除等待移除且没有主机再使用的条目
+    fn remove_released_secrets(&mut self) {
+        if !self.ssh_manager.vault.is_unlocked() {
+            return;
+        }
+        for id in std::mem::take(&mut self.released_secrets) {
+            if !self.config_manager.references_secret(&id) {
+                // 条目已不存在或写入失败时只留下无用的条目，不影响删除主机
+                let _ = self.ssh_manager.vault.remove(&id);
+            }
+        }
+    }
+
+    /// 连接前检查代理密码是否可用，密码库未解锁时打开主密码提示
+    ///
+    /// # 参数
+    /// - `configs`: 要连接的配置
+    ///
+    /// # 返回
+    /// 返回 Result，无需解锁或已解锁时为 Ok(())，否则为 Err
+    fn ensure_vault(&mut self, configs: &[&SSHConfig]) -> Result<(), Box<dyn std::error::Error>> {
+        self.ssh_manager.global_config = self.config_manager.global_config.clone();
+        if configs.iter().any(|c| self.ssh_manager.needs_vault(c)) {
+            self.show_vault_prompt();
+            return Err("代理密码保存在密码库中，请先输入主密码".into());
+        }
+        Ok(())
+    }
+
+    /// 显示密码库主密码提示
+    pub fn show_vault_prompt(&mut self) {
+        if self.vault_prompt.is_some() {
+            return;
+        }
+        self.vault_prompt = Some(VaultPrompt {
+            input: Zeroizing::new(String::new()),
+            first: None,
+            previous_mode: self.mode().clone(),
+        });
+        self.navigation.set_mode(AppMode::VaultUnlock);
+    }
+
+    /// 解锁密码库，密码库不存在时创建
+    ///
+    /// 创建时需要输入两次主密码。解锁后将配置中的明文代理密码移入密码库。
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，主密码错误或两次输入不一致时为 Err
+    pub fn submit_vault_password(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let Some(prompt) = &mut self.vault_prompt else {
+            return Ok(String::new());
+        };
+        let input = std::mem::take(&mut prompt.input);
+        if input.is_empty() {
+            return Err("主密码不能为空".into());
+        }
+
+        let vault = &mut self.ssh_manager.vault;
+        if vault.exists() {
+            vault.unlock(&input)?;
+        } else {
+            match prompt.first.take() {
+                None => {
+                    prompt.first = Some(input);
+                    return Ok("请再次输入主密码".to_string());
+                }
+                Some(first) if first != input => {
+                    return Err("两次输入的主密码不一致，请重新设置".into());
+                }
+                Some(_) => vault.create(&input)?,
+            }
+        }
+
+        self.cancel_vault_prompt();
+        self.remove_released_secrets();
+        if self.config_manager.is_read_only() || self.config_manager.plaintext_password_count() == 0
+        {
+            return Ok("密码库已解锁".to_string());
+        }
+        let count = self
+            .config_manager
+            .migrate_passwords(&mut self.ssh_manager.vault)?;
+        Ok(format!("密码库已解锁，已将 {count} 个明文密码移入密码库"))
+    }
+
+    /// 关闭主密码提示，回到被打断的模式
+    pub fn cancel_vault_prompt(&mut self) {
+        if let Some(prompt) = self.vault_prompt.take() {
+            self.navigation.set_mode(prompt.previous_mode);
+        }
+    }
+
+    /// 选中主机的密钥尚未加载到 ssh-agent 时，提示在连接前添加
+    ///
+    /// # 返回
+    /// 返回 true 表示已显示提示，连接在提示中确认后进行
+    pub fn offer_agent(&mut self) -> bool {
+        let Some(key) = self
+            .get_selected_config()
+            .and_then(|config| config.key.clone())
+        else {
+            return false;
+        };
+        if !self.agent_manager.should_offer(&key) {
+            return false;
+        }
+        // 无法读取的私钥交给 ssh 自己处理
+        let Ok(needs_passphrase) = self.agent_manager.needs_passphrase(&key) else {
+            return false;
+        };
+        let minutes = self
+            .config_manager
+            .global_config
+            .agent_lifetime_mins
+            .unwrap_or(DEFAULT_LIFETIME_MINS);
+        // 密码库已解锁且保存了口令时直接使用
+        let stored = needs_passphrase
+            .then(|| self.ssh_manager.vault.get(&vault::key_secret_id(&key)).ok())
+            .flatten()
+            .map(|passphrase| Zeroizing::new(passphrase.to_string()));
+        let needs_passphrase = needs_passphrase && stored.is_none();
+        self.agent_prompt = Some(AgentPrompt {
+            key,
+            minutes: minutes.to_string(),
+            stored_passphrase: stored.is_some(),
+            passphrase: stored.unwrap_or_default(),
+            needs_passphrase,
+            editing_passphrase: needs_passphrase,
+            previous_mode: self.mode().clone(),
+        });
+        self.navigation.set_mode(AppMode::AgentAdd);
+        true
+    }
+
+    /// 将密钥添加到 ssh-agent 后连接选中的主机
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，有效期无效、口令错误或连接失败时为 Err
+    pub fn submit_agent_prompt(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let Some(prompt) = &mut self.agent_prompt else {
+            return Ok(String::new());
+        };
+        let minutes = match prompt.minutes.parse::<u64>() {
+            Ok(minutes) if minutes > 0 => minutes,
+            _ => return Err("有效期必须是大于 0 的分钟数".into()),
+        };
+        let passphrase = std::mem::take(&mut prompt.passphrase);
+        if let Err(e) =
+            self.agent_manager
+                .add_key(&prompt.key, &passphrase, Duration::from_secs(minutes * 60))
+        {
+            if !prompt.stored_passphrase {
+                return Err(e.into());
+            }
+            // 密码库中的口令已失效，改为手动输入
+            prompt.stored_passphrase = false;
+            prompt.needs_passphrase = true;
+            prompt.editing_passphrase = true;
+            return Err(format!("密码库中保存的口令无效，请输入口令: {e}").into());
+        }
+
+        self.cancel_agent_prompt();
+        self.connect_selected()?;
+        Ok(format!("连接成功，密钥在 ssh-agent 中保留 {minutes} 分钟"))
+    }
+
+    /// 不添加密钥直接连接，本次运行中不再提示该密钥
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，连接失败时为 Err
+    pub fn skip_agent_prompt(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(prompt) = &self.agent_prompt {
+            self.agent_manager.skip(&prompt.key);
+        }
+        self.cancel_agent_prompt();
+        self.connect_selected()
+    }
+
+    /// 关闭添加密钥的提示，回到被打断的模式
+    pub fn cancel_agent_prompt(&mut self) {
+        if let Some(prompt) = self.agent_prompt.take() {
+            self.navigation.set_mode(prompt.previous_mode);
+        }
+    }
+
     /// 连接选中的配置
     ///
     /// # 返回
     /// 返回 Result，成功为 Ok(())，失败为 Err
     pub fn connect_selected(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let valid_index = self
-            .navigation
-            .get_valid_selected_index(self.config_manager.configs.len());
-        if valid_index < self.config_manager.configs.len() {
-            let config = self.config_manager.configs[valid_index].clone();
-            self.ssh_manager.global_config = self.config_manager.global_config.clone();
-            self.ssh_manager.connect(&config)?;
+        if let Some(config) = self.get_selected_config().cloned() {
+            let jump_chain = self.config_manager.resolve_jump_chain(&config)?;
+            self.ensure_vault(&[&config])?;
+            let started_at = unix_now();
+            let status = self.ssh_manager.connect(&config, &jump_chain);
+            // 历史记录写入失败不影响连接
+            let exit_code = status.as_ref().ok().and_then(|s| s.code());
+            let _ = self
+                .history_manager
+                .record(&config.alias, started_at, exit_code);
+            // 刚连接的主机移到“最近连接”的第一位，选中项跟随移动
+            if let Some(ListRow::Host { recent: true, .. }) = self.selected_row() {
+                self.navigation.selected_index = 1;
+            }
+            let status = status?;
+            if !status.success() {
+                return Err(format!("SSH 连接失败，退出码: {:?}", status.code()).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// 为指定配置启动后台隧道
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，失败为 Err
+    pub fn start_tunnel(
+        &mut self,
+        config: &SSHConfig,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if config.enabled_forwards().next().is_none() {
+            return Err(format!("{} 没有启用的端口转发", config.alias).into());
+        }
+        let jump_chain = self.config_manager.resolve_jump_chain(config)?;
+        self.ensure_vault(&[config])?;
+        let cmd = self.ssh_manager.tunnel_command(config, &jump_chain)?;
+        let tunnel = self.tunnel_manager.start(config, cmd)?;
+        Ok(format!("隧道已启动: {} (PID {})", tunnel.alias, tunnel.pid))
+    }
+
+    /// 为选中的配置启动隧道（仅隧道主机按 Enter 时使用）
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，失败为 Err
+    pub fn start_selected_tunnel(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let config = self
+            .get_selected_config()
+            .cloned()
+            .ok_or("没有选中的配置")?;
+        self.start_tunnel(&config)
+    }
+
+    /// 启动或停止选中配置的隧道
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，失败为 Err
+    pub fn toggle_selected_tunnel(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let config = self
+            .get_selected_config()
+            .cloned()
+            .ok_or("没有选中的配置")?;
+        if self.tunnel_manager.is_running(&config.alias) {
+            self.tunnel_manager.stop(&config.alias)?;
+            Ok(format!("隧道已停止: {}", config.alias))
+        } else {
+            self.start_tunnel(&config)
+        }
+    }
+
+    /// 连接选中分组（包括子分组）中的所有主机
+    ///
+    /// 仅隧道主机启动后台隧道；其他主机在 tmux 中时各自打开一个新窗口，
+    /// 否则在当前终端中依次连接，上一个会话结束后连接下一个。
+    ///
+    /// # 返回
+    /// 返回 Result，成功为结果提示信息，有主机连接失败时为 Err
+    pub fn connect_group(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let group = self.selected_group().ok_or("选中的主机不属于任何分组")?;
+        let (tunnels, hosts): (Vec<SSHConfig>, Vec<SSHConfig>) = self
+            .config_manager
+            .configs
+            .iter()
+            .filter(|c| c.in_group(&group))
+            .cloned()
+            .partition(|c| c.tunnel_only);
+        if tunnels.is_empty() && hosts.is_empty() {
+            return Err(format!("分组 {group} 中没有主机").into());
+        }
+        self.ensure_vault(&tunnels.iter().chain(&hosts).collect::<Vec<_>>())?;
+
+        let mut failed = Vec::new();
+        for config in &tunnels {
+            if !self.tunnel_manager.is_running(&config.alias) {
+                if let Err(e) = self.start_tunnel(config) {
+                    failed.push(format!("{}: {e}", config.alias));
+                }
+            }
+        }
+
+        self.ssh_manager.global_config = self.config_manager.global_config.clone();
+        if std::env::var_os("TMUX").is_some() {
+            self.open_tmux_windows(&hosts, &mut failed);
+        } else if !hosts.is_empty() {
+            self.connect_in_sequence(&hosts, &mut failed)?;
+        }
+
+        let total = tunnels.len() + hosts.len();
+        if failed.is_empty() {
+            Ok(format!("已连接分组 {group} 中的 {total} 个主机"))
+        } else {
+            Err(format!(
+                "分组 {group} 中 {}/{total} 个主机连接失败: {}",
+                failed.len(),
+                failed.join("; ")
+            )
+            .into())
+        }
+    }
+
+    /// 为每个主机打开一个 tmux 窗口，窗口中运行 `mssh connect`
+    ///
+    /// # 参数
+    /// - `hosts`: 要连接的主机
+    /// - `failed`: 记录失败的主机
+    fn open_tmux_windows(&self, hosts: &[SSHConfig], failed: &mut Vec<String>) {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe,
+            Err(e) => {
+                failed.push(format!("无法获取 mssh 路径: {e}"));
+                return;
+            }
+        };
+        // 新窗口的工作目录不一定是当前目录，使用绝对路径
+        let config_path = self.config_manager.config_path();
+        let config_path = config_path
+            .canonicalize()
+            .unwrap_or_else(|_| config_path.to_path_buf());
+        for config in hosts {
+            let status = Command::new("tmux")
+                .args(["new-window", "-n", &config.alias])
+                .arg(&exe)
+                .arg("-c")
+                .arg(&config_path)
+                .args(["connect", &config.alias])
+                .status();
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => {
+                    failed.push(format!("{}: tmux 退出码 {:?}", config.alias, status.code()))
+                }
+                Err(e) => failed.push(format!("{}: 无法启动 tmux: {e}", config.alias)),
+            }
+        }
+    }
+
+    /// 在当前终端中依次连接主机
+    ///
+    /// # 参数
+    /// - `hosts`: 要连接的主机
+    /// - `failed`: 记录失败的主机
+    ///
+    /// # 返回
+    /// 返回 Result，终端状态切换失败时为 Err
+    fn connect_in_sequence(
+        &mut self,
+        hosts: &[SSHConfig],
+        failed: &mut Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        disable_raw_mode()?;
+        execute!(std::io::stdout(), LeaveAlternateScreen, Show)?;
+
+        for (i, config) in hosts.iter().enumerate() {
+            println!(
+                "\x1b[36m[{}/{}] 正在连接到 {}\x1b[0m",
+                i + 1,
+                hosts.len(),
+                config.alias
+            );
+            let started_at = unix_now();
+            let result = self
+                .config_manager
+                .resolve_jump_chain(config)
+                .map_err(Into::into)
+                .and_then(|chain| self.ssh_manager.run(config, &chain));
+            let exit_code = result.as_ref().ok().and_then(|s| s.code());
+            let _ = self
+                .history_manager
+                .record(&config.alias, started_at, exit_code);
+            match result {
+                Ok(status) if status.success() => {}
+                Ok(status) => failed.push(format!("{}: 退出码 {:?}", config.alias, status.code())),
+                Err(e) => failed.push(format!("{}: {e}", config.alias)),
+            }
         }
+
+        execute!(std::io::stdout(), EnterAlternateScreen)?;
+        enable_raw_mode()?;
         Ok(())
     }
 
+    /// 显示移动到分组的输入框，预填主机当前所在的分组
+    pub fn show_move_to_group(&mut self) {
+        if let Some(config) = self.get_selected_config() {
+            self.navigation.group_manager.input = config.group.clone().unwrap_or_default();
+            self.navigation.set_mode(AppMode::MoveToGroup);
+        }
+    }
+
+    /// 将输入切换为下一个已有分组
+    pub fn cycle_group_input(&mut self) {
+        let groups = self.config_manager.groups();
+        self.navigation.group_manager.cycle_input(&groups);
+    }
+
+    /// 将选中的主机移动到输入的分组，输入为空时移出分组
+    ///
+    /// # 返回
+    /// 返回 Result，成功为结果提示信息，失败为 Err
+    pub fn confirm_move_to_group(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut config = self
+            .get_selected_config()
+            .cloned()
+            .ok_or("没有选中的配置")?;
+        let alias = config.alias.clone();
+        config.group = normalize_group(&self.navigation.group_manager.input);
+        let message = match &config.group {
+            Some(group) => format!("已将 {alias} 移动到分组 {group}"),
+            None => format!("已将 {alias} 移出分组"),
+        };
+
+        self.config_manager.update_config(&alias, config)?;
+        self.navigation.return_to_list();
+        self.select_host(&alias);
+        Ok(message)
+    }
+
     /// 获取选中的配置
     ///
     /// # 返回
     /// 返回选中的配置引用，如果没有则为 None
     pub fn get_selected_config(&self) -> Option<&SSHConfig> {
-        if self.config_manager.configs.is_empty() {
-            return None;
-        }
-
-        let valid_index = self
-            .navigation
-            .get_valid_selected_index(self.config_manager.configs.len());
-        self.config_manager.configs.get(valid_index)
+        let index = self.selected_row()?.host_index()?;
+        self.config_manager.configs.get(index)
     }
 
     /// 移动到下一个表单字段
@@ -237,6 +1214,22 @@ impl App {
         self.form_manager.previous_field();
     }
 
+    /// 补全密钥字段中的文件路径，没有可补全的内容时移动到下一个字段
+    pub fn complete_key_path(&mut self) {
+        let form_data = &mut self.form_manager.form_data;
+        match complete_path(&form_data.get_current_value()) {
+            PathCompletion::Completed(path) => {
+                form_data.set_current_value(path);
+                form_data.move_cursor_to_end();
+            }
+            PathCompletion::Ambiguous(names) => {
+                self.message_manager
+                    .set_success_message(format!("可选: {}", names.join("  ")));
+            }
+            PathCompletion::Unchanged => self.next_field(),
+        }
+    }
+
     /// 光标左移
     pub fn move_cursor_left(&mut self) {
         self.form_manager.move_cursor_left();
@@ -290,26 +1283,79 @@ impl App {
         self.form_manager.toggle_proxy_option();
     }
 
+    /// 切换编辑中的转发类型
+    pub fn cycle_forward_kind(&mut self) {
+        self.form_manager.cycle_forward_kind();
+    }
+
+    /// 将编辑中的端口转发添加到列表
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    pub fn add_draft_forward(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(self.form_manager.add_draft_forward()?)
+    }
+
+    /// 将选中的端口转发移回编辑字段
+    pub fn edit_selected_forward(&mut self) {
+        self.form_manager.edit_selected_forward();
+    }
+
+    /// 删除选中的端口转发
+    pub fn remove_selected_forward(&mut self) {
+        self.form_manager.remove_selected_forward();
+    }
+
+    /// 在端口转发列表中移动选择
+    ///
+    /// # 参数
+    /// - `forward`: true 为向后移动，false 为向前移动
+    pub fn select_forward(&mut self, forward: bool) {
+        self.form_manager.select_forward(forward);
+    }
+
+    /// 获取可以加入跳板机链的主机
+    ///
+    /// # 返回
+    /// 返回除正在编辑的主机和已选择的跳板机之外的主机别名
+    pub fn jump_candidates(&self) -> Vec<String> {
+        let selected = self.form_manager.form_data.jump_hosts();
+        let editing = self.form_manager.get_editing_host();
+        self.config_manager
+            .configs
+            .iter()
+            .map(|c| &c.alias)
+            .filter(|alias| Some(*alias) != editing && !selected.contains(alias))
+            .cloned()
+            .collect()
+    }
+
+    /// 切换待添加的跳板机
+    ///
+    /// # 参数
+    /// - `forward`: true 为向后切换，false 为向前切换
+    pub fn cycle_jump_candidate(&mut self, forward: bool) {
+        let candidates = self.jump_candidates();
+        self.form_manager.cycle_jump_candidate(&candidates, forward);
+    }
+
+    /// 将待添加的跳板机追加到跳板机链
+    pub fn push_jump_candidate(&mut self) {
+        self.form_manager.push_jump_candidate();
+    }
+
+    /// 移除跳板机链中的最后一个跳板机
+    pub fn pop_jump_host(&mut self) {
+        self.form_manager.pop_jump_host();
+    }
+
     /// 显示导入选择界面
     ///
     /// # 返回
     /// 返回 Result，成功为 Ok(())，失败为 Err
     pub fn show_import_selection(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let ssh_config_path = dirs::home_dir()
-            .ok_or("无法获取用户主目录")?
-            .join(".ssh")
-            .join("config");
-        let content = std::fs::read_to_string(ssh_config_path)?;
-        let mut candidates = Vec::new();
-
-        for line in content.lines() {
-            let line = line.trim();
-            if line.starts_with("Host ") {
-                if let Some(host) = self.config_manager.parse_ssh_host_config(&content, line) {
-                    candidates.push(host);
-                }
-            }
-        }
+        let ssh_config_path = default_config_path().ok_or("无法获取用户主目录")?;
+        let mut candidates = OpenSshConfig::load(&ssh_config_path)?.to_ssh_configs();
 
         candidates.retain(|host| {
             !self
@@ -323,6 +1369,268 @@ impl App {
         Ok(())
     }
 
+    /// 导出所有配置到 mssh 管理的 OpenSSH 配置片段
+    ///
+    /// # 返回
+    /// 返回 Result，成功为结果提示信息，失败为 Err
+    pub fn export_ssh_config(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let export_path = default_export_path().ok_or("无法获取用户主目录")?;
+        let count = crate::ssh_config::export_to_file(
+            &export_path,
+            &self.config_manager.configs,
+            &self.config_manager.global_config,
+            self.config_manager.config_path(),
+        )?;
+
+        let included = default_config_path()
+            .and_then(|path| OpenSshConfig::load(&path).ok())
+            .is_some_and(|ssh_config| ssh_config.includes(&export_path));
+        let mut message = format!("已导出 {count} 个主机到 {}", export_path.display());
+        if !included {
+            message.push_str("，请在 ~/.ssh/config 开头添加 Include config.d/*.conf");
+        }
+        Ok(message)
+    }
+
+    /// 显示与 ~/.ssh/config 的同步差异界面
+    ///
+    /// # 返回
+    /// 返回 Result，成功为差异数量，失败为 Err
+    pub fn show_sync_diff(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let ssh_config_path = default_config_path().ok_or("无法获取用户主目录")?;
+        let ssh_config = if ssh_config_path.exists() {
+            OpenSshConfig::load(&ssh_config_path)?
+        } else {
+            OpenSshConfig::default()
+        };
+
+        let entries = SyncManager::compute(
+            &self.config_manager.configs,
+            &self.config_manager.global_config,
+            self.config_manager.config_path(),
+            &ssh_config,
+        );
+        let count = entries.len();
+        if count > 0 {
+            self.navigation.start_sync(entries);
+        }
+        Ok(count)
+    }
+
+    /// 按每个主机选择的处理方式应用同步
+    ///
+    /// 以 ~/.ssh/config 为准的主机写回 mssh 配置；以 mssh 为准的主机通过
+    /// 重新导出 mssh 管理的配置片段同步到 OpenSSH，不会修改用户手写的配置。
+    ///
+    /// # 返回
+    /// 返回 Result，成功为结果提示信息，失败为 Err
+    pub fn confirm_sync(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let entries = self.navigation.sync_manager.entries.clone();
+        let mut configs = self.config_manager.configs.clone();
+        let existing_count = configs.len();
+        let mut updated = 0;
+        let mut export = false;
+
+        for entry in entries {
+            match (&entry.status, &entry.resolution) {
+                (_, SyncResolution::Skip) => {}
+                (SyncStatus::Added, SyncResolution::UseSsh) => {
+                    if let Some(ssh_config) = entry.ssh_config {
+                        configs.push(ssh_config);
+                        updated += 1;
+                    }
+                }
+                (SyncStatus::Changed, SyncResolution::UseSsh) => {
+                    let existing = configs.iter_mut().find(|c| c.alias == entry.alias);
+                    if let (Some(existing), Some(ssh_config)) = (existing, entry.ssh_config) {
+                        // OpenSSH 一侧使用 ProxyJump 时改为跳板机，没有代理时一并移除；
+                        // 任意 ProxyCommand 无法转换为 mssh 的代理配置，此时保留原有代理
+                        if ssh_config.proxy.is_some() {
+                            existing.proxy = ssh_config.proxy;
+                            existing.use_global_proxy = false;
+                        } else if entry.ssh_proxy_command.is_none() {
+                            existing.proxy = None;
+                            existing.use_global_proxy = false;
+                        }
+                        existing.address = ssh_config.address;
+                        existing.port = ssh_config.port;
+                        existing.user = ssh_config.user;
+                        existing.key = ssh_config.key;
+                        existing.port_forwards = ssh_config.port_forwards;
+                        updated += 1;
+                    }
+                }
+                (SyncStatus::Removed, SyncResolution::UseSsh) => {
+                    configs.retain(|c| c.alias != entry.alias);
+                    updated += 1;
+                }
+                (_, SyncResolution::UseMssh) => export = true,
+            }
+        }
+
+        if updated > 0 {
+            // 新增主机的 ProxyJump 目标对应到 mssh 的别名，并确认所有跳板机链仍然有效
+            let candidates: Vec<SSHConfig> = configs[existing_count..].to_vec();
+            ssh_config::add_jump_hosts(&mut configs, existing_count, &candidates);
+            let previous = std::mem::replace(&mut self.config_manager.configs, configs);
+            if let Err(e) = self.config_manager.check_jump_chains() {
+                self.config_manager.configs = previous;
+                return Err(e.into());
+            }
+            if let Err(e) = self.config_manager.save_configs() {
+                self.config_manager.configs = previous;
+                return Err(e.into());
+            }
+        }
+
+        let mut message = format!("同步完成: 更新 mssh 中 {updated} 个主机");
+        if export {
+            message = format!("{message}，{}", self.export_ssh_config()?);
+        }
+
+        self.navigation.return_to_list();
+        Ok(message)
+    }
+
+    /// 取消同步
+    pub fn cancel_sync(&mut self) {
+        self.navigation.return_to_list();
+        self.message_manager.clear_message();
+    }
+
+    /// 扫描 ~/.ssh 并显示密钥管理界面
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，目录无法读取时为 Err
+    pub fn show_keys(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.rescan_keys()?;
+        self.navigation.set_mode(AppMode::Keys);
+        Ok(())
+    }
+
+    /// 重新扫描 ~/.ssh 中的密钥
+    ///
+    /// # 返回
+    /// 返回 Result，成功为找到的密钥数量，目录无法读取时为 Err
+    pub fn rescan_keys(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let dir = default_key_dir().ok_or("无法获取用户主目录")?;
+        self.navigation
+            .key_manager
+            .scan(&dir, &self.config_manager.configs)
+            .map_err(|e| format!("无法读取 {}: {e}", display_path(&dir)))?;
+        Ok(self.navigation.key_manager.keys.len())
+    }
+
+    /// 打开生成密钥弹窗
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，无法获取主目录时为 Err
+    pub fn show_generate_key(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let dir = default_key_dir().ok_or("无法获取用户主目录")?;
+        self.navigation.key_manager.start_generate(&dir);
+        Ok(())
+    }
+
+    /// 调用 ssh-keygen 生成 ed25519 密钥，生成后选中新密钥
+    ///
+    /// 生成期间退出 TUI，口令由 ssh-keygen 在终端中询问。
+    ///
+    /// # 返回
+    /// 返回 Result，成功为结果提示信息，文件名无效或 ssh-keygen 失败时为 Err
+    pub fn confirm_generate_key(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let Some(KeyDialog::Generate { name, comment, .. }) =
+            self.navigation.key_manager.dialog.clone()
+        else {
+            return Ok(String::new());
+        };
+        let dir = default_key_dir().ok_or("无法获取用户主目录")?;
+        let path = new_key_path(&dir, &name)?;
+        create_key_dir(&dir).map_err(|e| format!("无法创建 {}: {e}", display_path(&dir)))?;
+
+        self.navigation.key_manager.close_dialog();
+        let title = format!("正在生成密钥: {}", display_path(&path));
+        let status = run_in_terminal(&title, keygen_command(&path, &comment))
+            .map_err(|e| format!("无法运行 ssh-keygen: {e}"))?;
+        self.rescan_keys()?;
+        if !status.success() {
+            return Err(format!("ssh-keygen 退出码: {:?}", status.code()).into());
+        }
+        self.navigation.key_manager.select(&path);
+        Ok(format!("已生成密钥 {}", display_path(&path)))
+    }
+
+    /// 打开部署公钥弹窗，默认选中列表中选中的主机
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，没有密钥或主机时为 Err
+    pub fn show_deploy_key(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.navigation.key_manager.selected_key().is_none() {
+            return Err("没有可部署的密钥".into());
+        }
+        if self.config_manager.configs.is_empty() {
+            return Err("没有可部署的主机".into());
+        }
+        let selected = self
+            .get_selected_config()
+            .and_then(|selected| {
+                self.config_manager
+                    .configs
+                    .iter()
+                    .position(|c| c.alias == selected.alias)
+            })
+            .unwrap_or(0);
+        self.navigation.key_manager.dialog = Some(KeyDialog::Deploy { selected });
+        Ok(())
+    }
+
+    /// 将选中的公钥追加到主机的 authorized_keys，与 ssh-copy-id 相同
+    ///
+    /// 部署期间退出 TUI，主机尚未配置密钥认证时可以在终端中输入密码。
+    ///
+    /// # 返回
+    /// 返回 Result，成功为结果提示信息，失败为 Err
+    pub fn confirm_deploy_key(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let Some(KeyDialog::Deploy { selected }) = self.navigation.key_manager.dialog else {
+            return Ok(String::new());
+        };
+        let key = self
+            .navigation
+            .key_manager
+            .selected_key()
+            .cloned()
+            .ok_or("没有选中的密钥")?;
+        let config = self
+            .config_manager
+            .configs
+            .get(selected)
+            .cloned()
+            .ok_or("没有选中的主机")?;
+        let jump_chain = self.config_manager.resolve_jump_chain(&config)?;
+        self.ensure_vault(&[&config])?;
+        let cmd = self.ssh_manager.remote_command(
+            &config,
+            &jump_chain,
+            &deploy_script(&key.public_key),
+        )?;
+
+        self.navigation.key_manager.close_dialog();
+        let title = format!("正在将 {} 部署到 {}", key.name(), config.alias);
+        let status = run_in_terminal(&title, cmd)?;
+        if !status.success() {
+            return Err(format!("部署失败，ssh 退出码: {:?}", status.code()).into());
+        }
+        Ok(format!("已将 {} 部署到 {}", key.name(), config.alias))
+    }
+
+    /// 关闭密钥管理界面的弹窗，没有弹窗时回到列表
+    pub fn cancel_keys(&mut self) {
+        if self.navigation.key_manager.dialog.is_some() {
+            self.navigation.key_manager.close_dialog();
+        } else {
+            self.navigation.return_to_list();
+        }
+    }
+
     /// 导入界面下一个项目
     pub fn import_next(&mut self) {
         self.navigation.import_next();
@@ -349,19 +1657,15 @@ impl App {
     /// 返回 Result，成功为 Ok(())，失败为 Err
     pub fn confirm_import(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let selected_configs = self.navigation.get_selected_imports();
-        let mut imported_count = 0;
-
-        for config in selected_configs {
-            self.config_manager.add_config(config)?; // 出错自动返回 Err
-            imported_count += 1;
-        }
-
-        self.navigation.return_to_list();
-
-        if imported_count == 0 {
+        if selected_configs.is_empty() {
+            self.navigation.return_to_list();
             return Err("未选择任何可导入的配置".into());
         }
 
+        // 整批校验失败时停留在导入界面，可以调整选择后重试
+        self.config_manager
+            .import_configs(selected_configs, &self.navigation.import_manager.candidates)?;
+        self.navigation.return_to_list();
         Ok(())
     }
 
@@ -371,62 +1675,159 @@ impl App {
         self.message_manager.clear_message();
     }
 
-    /// 快速连接
+    /// 在外部编辑器中打开配置文件，编辑结束后重新加载
     ///
-    /// # 参数
-    /// - `target`: 目标配置（编号或别名）
+    /// 编辑器依次取自 `$VISUAL`、`$EDITOR`，都未设置时使用系统默认编辑器。
     ///
     /// # 返回
     /// 返回 Result，成功为 Ok(())，失败为 Err
-    pub fn quick_connect(&mut self, target: &str) -> Result<(), Box<dyn std::error::Error>> {
-        if let Ok(index) = target.parse::<usize>() {
-            if index > 0 && index <= self.config_manager.configs.len() {
-                let config = &self.config_manager.configs[index - 1];
-                println!("正在连接到 {} (编号: {})", config.alias, index);
-                self.ssh_manager.connect(config)?;
-                return Ok(());
-            } else {
-                return Err(format!(
-                    "编号 {} 超出范围 (1-{})",
-                    index,
-                    self.config_manager.configs.len()
-                )
-                .into());
-            }
+    pub fn open_config_in_editor(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| {
+                if cfg!(windows) {
+                    "notepad".to_string()
+                } else {
+                    "vi".to_string()
+                }
+            });
+        let mut parts = editor.split_whitespace();
+        let program = parts.next().ok_or("编辑器命令为空")?;
+
+        // 退出 TUI 模式，把终端交给编辑器
+        disable_raw_mode()?;
+        execute!(std::io::stdout(), LeaveAlternateScreen, Show)?;
+        std::io::stdout().flush()?;
+
+        let status = Command::new(program)
+            .args(parts)
+            .arg(self.config_manager.config_path())
+            .status();
+
+        execute!(std::io::stdout(), EnterAlternateScreen)?;
+        enable_raw_mode()?;
+
+        let status = status.map_err(|e| format!("无法启动编辑器 {program}: {e}"))?;
+        if !status.success() {
+            return Err(format!("编辑器退出码: {:?}", status.code()).into());
         }
 
-        for (i, config) in self.config_manager.configs.iter().enumerate() {
-            if config.alias == target {
-                println!("正在连接到 {} (编号: {})", config.alias, i + 1);
-                self.ssh_manager.connect(config)?;
-                return Ok(());
+        self.reload_config()
+    }
+
+    /// 重新加载配置文件
+    ///
+    /// 加载成功时退出只读模式并回到列表，失败时更新错误信息。
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    pub fn reload_config(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let config_path = self.config_manager.config_path().to_path_buf();
+        match ConfigManager::load(config_path) {
+            Ok(manager) => {
+                self.ssh_manager.global_config = manager.global_config.clone();
+                self.config_manager = manager;
+                self.config_error = None;
+                self.navigation.return_to_list();
+                Ok(())
+            }
+            Err(e) => {
+                self.config_error = Some(e.to_string());
+                Err(e.into())
             }
         }
+    }
 
-        let mut matches = Vec::new();
-        for (i, config) in self.config_manager.configs.iter().enumerate() {
-            if config.alias.contains(target)
-                || config.address.contains(target)
-                || config.user.as_ref().is_some_and(|u| u.contains(target))
-            {
-                matches.push((i + 1, config));
+    /// 检查配置文件是否在外部被修改
+    ///
+    /// 没有未保存的修改时直接重新加载并保持选中的主机；正在编辑表单或对话框时
+    /// 进入冲突提示，由用户选择放弃修改或在新的配置上继续编辑。
+    pub fn check_config_file(&mut self) {
+        let manager = match self.config_manager.check_external_change() {
+            Ok(Some(manager)) => manager,
+            Ok(None) => return,
+            Err(e) => {
+                // 只读模式下文件仍然无法加载，错误已显示在界面上
+                if !self.config_manager.is_read_only() {
+                    self.message_manager
+                        .set_error_message(format!("配置文件已在外部修改，但无法加载: {e}"));
+                }
+                return;
             }
-        }
+        };
 
-        if matches.len() == 1 {
-            let (index, config) = matches[0];
-            println!("正在连接到 {} (编号: {})", config.alias, index);
-            self.ssh_manager.connect(config)?;
-            return Ok(());
-        } else if matches.len() > 1 {
-            println!("找到多个匹配的配置:");
-            for (index, config) in matches {
-                println!("  {}: {} ({})", index, config.alias, config.address);
+        match self.mode().clone() {
+            AppMode::List | AppMode::Search | AppMode::ConfigError | AppMode::Keys => {
+                self.apply_reload(manager);
+                self.message_manager
+                    .set_success_message("配置文件已在外部修改，已重新加载".to_string());
+            }
+            AppMode::ConfigConflict => {
+                if let Some(conflict) = &mut self.config_conflict {
+                    conflict.manager = manager;
+                }
             }
-            return Err("请使用更具体的编号或别名".into());
+            previous_mode => {
+                self.config_conflict = Some(ConfigConflict {
+                    manager,
+                    previous_mode,
+                });
+                self.navigation.set_mode(AppMode::ConfigConflict);
+            }
+        }
+    }
+
+    /// 处理配置冲突
+    ///
+    /// # 参数
+    /// - `discard`: true 为放弃未保存的修改并回到列表，false 为回到被打断的界面，
+    ///   之后的保存基于重新加载的配置进行
+    pub fn resolve_config_conflict(&mut self, discard: bool) {
+        let Some(conflict) = self.config_conflict.take() else {
+            return;
+        };
+        if discard {
+            self.form_manager.clear();
+            self.key_permission_prompt = None;
+            self.navigation.return_to_list();
+        } else {
+            self.navigation.set_mode(conflict.previous_mode);
+        }
+        self.apply_reload(conflict.manager);
+    }
+
+    /// 使用重新加载的配置，保持选中的主机或分组
+    ///
+    /// # 参数
+    /// - `manager`: 重新加载的配置管理器
+    fn apply_reload(&mut self, manager: ConfigManager) {
+        let selected = self.selected_row();
+        let alias = selected
+            .as_ref()
+            .and_then(|row| row.host_index())
+            .map(|i| self.config_manager.configs[i].alias.clone());
+
+        self.ssh_manager.global_config = manager.global_config.clone();
+        self.config_manager = manager;
+        self.navigation
+            .key_manager
+            .set_hosts(&self.config_manager.configs);
+        if self.config_error.take().is_some() {
+            self.navigation.return_to_list();
+        }
+
+        match (alias, selected.as_ref().and_then(|row| row.group_path())) {
+            (Some(alias), _) => self.select_host(&alias),
+            (None, Some(path)) => self.select_group(path),
+            _ => {}
         }
+    }
 
-        Err(format!("未找到匹配的配置: {target}").into())
+    /// 忽略配置错误，以只读模式继续启动
+    pub fn start_read_only(&mut self) {
+        self.navigation.return_to_list();
+        self.message_manager
+            .set_error_message("只读模式：修改不会被保存".to_string());
     }
 
     /// 检查并清理过期消息
@@ -434,6 +1835,102 @@ impl App {
         self.message_manager.check_and_clear_expired();
     }
 
+    /// 到达刷新间隔时刷新后台隧道的运行状态
+    pub fn refresh_tunnels(&mut self) {
+        if self.tunnel_manager.is_due() {
+            self.tunnel_manager.refresh();
+        }
+    }
+
+    /// 到达刷新间隔时在后台重新查询 ssh-agent 中已加载的密钥
+    pub fn refresh_agent(&mut self) {
+        if self.agent_manager.is_due() {
+            let keys = self
+                .config_manager
+                .configs
+                .iter()
+                .filter_map(|config| config.key.clone())
+                .collect();
+            // 无法启动后台线程时在下个刷新间隔重试
+            let _ = self
+                .agent_manager
+                .start_refresh(keys, self.event_sender.clone());
+        }
+    }
+
+    /// 检测所有主机的可达性
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，检测正在进行或无法启动时为 Err
+    pub fn probe_hosts(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        self.start_probe(false)
+    }
+
+    /// 在后台检测主机的可达性
+    ///
+    /// 经代理检测时需要代理密码。`keyring`、`pass` 和 `cmd` 引用需要运行外部命令，
+    /// 可能很慢或弹出解锁窗口，因此只在用户手动检测时读取，定时检测跳过这些主机。
+    ///
+    /// # 参数
+    /// - `automatic`: 是否为定时检测
+    ///
+    /// # 返回
+    /// 返回 Result，成功为提示信息，检测正在进行或无法启动时为 Err
+    fn start_probe(&mut self, automatic: bool) -> Result<String, Box<dyn std::error::Error>> {
+        if self.health_manager.is_running() {
+            return Err("检测正在进行中".into());
+        }
+        if self.config_manager.configs.is_empty() {
+            return Err("没有可检测的主机".into());
+        }
+        let configs = self.config_manager.configs.clone();
+        self.ensure_vault(&configs.iter().collect::<Vec<_>>())?;
+        let (mut targets, skipped): (Vec<ProbeTarget>, Vec<ProbeTarget>) = self
+            .config_manager
+            .configs
+            .iter()
+            .map(|config| ProbeTarget::from_config(config, &self.config_manager.global_config))
+            .partition(|target| {
+                !automatic
+                    || !target
+                        .proxy
+                        .as_ref()
+                        .and_then(|proxy| proxy.password.as_deref())
+                        .is_some_and(|password| SecretRef::parse(password).is_external())
+            });
+        for proxy in targets.iter_mut().filter_map(|t| t.proxy.as_mut()) {
+            proxy.password = self.ssh_manager.proxy_password(proxy)?;
+        }
+        let count = targets.len();
+        let skipped: Vec<String> = skipped.into_iter().map(|target| target.alias).collect();
+        self.health_manager
+            .start(targets, &skipped, self.event_sender.clone())?;
+        Ok(format!("正在检测 {count} 个主机"))
+    }
+
+    /// 到达设置的间隔时重新检测主机
+    pub fn refresh_health(&mut self) {
+        let Some(interval) = self.config_manager.global_config.probe_interval_secs else {
+            return;
+        };
+        // 密码库未解锁时不自动检测，避免反复弹出主密码提示
+        let locked = self
+            .config_manager
+            .configs
+            .iter()
+            .any(|config| self.ssh_manager.needs_vault(config));
+        if !self.config_manager.configs.is_empty()
+            && !locked
+            && !self.health_manager.is_running()
+            && self
+                .health_manager
+                .is_due(Duration::from_secs(interval.max(1)))
+        {
+            // 定时检测失败时不打扰用户，下次到期时重试
+            let _ = self.start_probe(true);
+        }
+    }
+
     /// 导入相关访问器（向后兼容）
     /// 获取导入候选列表
     ///
@@ -459,3 +1956,125 @@ impl App {
         self.navigation.import_manager.selected_index
     }
 }
+
+/// 退出 TUI 在当前终端中运行命令，命令结束后重新进入 TUI
+///
+/// # 参数
+/// - `title`: 运行前显示的提示
+/// - `cmd`: 要运行的命令，继承终端的输入输出
+///
+/// # 返回
+/// 返回 Result，成功为命令的退出状态，无法启动命令或切换终端状态失败时为 Err
+fn run_in_terminal(
+    title: &str,
+    mut cmd: Command,
+) -> Result<std::process::ExitStatus, Box<dyn std::error::Error>> {
+    disable_raw_mode()?;
+    execute!(std::io::stdout(), LeaveAlternateScreen, Show)?;
+    println!("\x1b[33m{title}\x1b[0m");
+    std::io::stdout().flush()?;
+
+    let status = cmd.status();
+
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    Ok(status?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn host(alias: &str, group: Option<&str>) -> SSHConfig {
+        SSHConfig {
+            alias: alias.to_string(),
+            address: format!("{alias}.example.com"),
+            port: None,
+            user: None,
+            key: None,
+            group: group.map(str::to_string),
+            tags: Vec::new(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy: None,
+            use_global_proxy: false,
+        }
+    }
+
+    /// 在临时目录中创建带有指定主机的应用，目录在返回的 TempDir 销毁时删除
+    fn app(name: &str, hosts: Vec<SSHConfig>) -> (TempDir, App) {
+        let dir = TempDir::new(name);
+        let mut manager = ConfigManager::load(dir.join("config.toml")).unwrap();
+        manager.add_configs(hosts).unwrap();
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        let config_path = Some(dir.join("config.toml").to_string_lossy().to_string());
+        let app = App::new(config_path, sender).unwrap();
+        (dir, app)
+    }
+
+    fn aliases(configs: &[SSHConfig]) -> Vec<&str> {
+        configs.iter().map(|c| c.alias.as_str()).collect()
+    }
+
+    #[test]
+    fn moving_at_boundaries_keeps_order() {
+        let (_dir, mut app) = app("move-bounds", vec![host("a", None), host("b", None)]);
+        app.select_host("a");
+        app.move_selected(true).unwrap();
+        app.select_host("b");
+        app.move_selected(false).unwrap();
+        assert_eq!(aliases(&app.config_manager.configs), ["a", "b"]);
+        assert_eq!(app.get_selected_config().unwrap().alias, "b");
+    }
+
+    #[test]
+    fn moving_swaps_within_group_and_persists() {
+        let hosts = vec![
+            host("web1", Some("web")),
+            host("db", Some("db")),
+            host("web2", Some("web")),
+            host("cache", None),
+        ];
+        let (dir, mut app) = app("move-group", hosts);
+        app.select_host("web2");
+        app.move_selected(true).unwrap();
+        // 跳过其他分组的主机，与同组相邻的主机交换
+        assert_eq!(
+            aliases(&app.config_manager.configs),
+            ["web2", "db", "web1", "cache"]
+        );
+        assert_eq!(app.get_selected_config().unwrap().alias, "web2");
+        // 已是分组中的第一个
+        app.move_selected(true).unwrap();
+
+        let reloaded = ConfigManager::load(dir.join("config.toml")).unwrap();
+        assert_eq!(aliases(&reloaded.configs), ["web2", "db", "web1", "cache"]);
+    }
+
+    #[test]
+    fn moving_is_rejected_while_searching_or_sorted() {
+        let (_dir, mut app) = app("move-rejected", vec![host("a", None), host("b", None)]);
+        app.navigation.search_manager.push('b');
+        app.select_host("b");
+        assert!(app.move_selected(true).is_err());
+
+        app.navigation.search_manager.clear();
+        app.cycle_sort_mode().unwrap();
+        app.select_host("b");
+        assert!(app.move_selected(true).is_err());
+        assert_eq!(aliases(&app.config_manager.configs), ["a", "b"]);
+    }
+
+    #[test]
+    fn display_order_follows_sort_mode() {
+        let hosts = vec![host("b", None), host("C", None), host("a", None)];
+        let (_dir, mut app) = app("display-order", hosts);
+        assert_eq!(app.display_order(), [0, 1, 2]);
+
+        app.config_manager.global_config.sort_mode = SortMode::Alias;
+        // 按别名排序时不区分大小写，配置文件中的顺序不变
+        assert_eq!(app.display_order(), [2, 0, 1]);
+        assert_eq!(aliases(&app.config_manager.configs), ["b", "C", "a"]);
+    }
+}
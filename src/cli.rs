@@ -0,0 +1,1533 @@
+use crate::agent::{decrypt_key, load_key, public_key_blob, AgentClient, DEFAULT_LIFETIME_MINS};
+use crate::config::{
+    normalize_group, parse_tags, tag_matches, ConfigManager, ForwardKind, PortForward, SSHConfig,
+};
+use crate::forms::FormData;
+use crate::history_manager::{format_time, HistoryEntry, HistoryManager};
+use crate::key_manager;
+use crate::proxy::{GlobalConfig, ProxyConfig, ProxyType};
+use crate::proxy_connect::split_host_port;
+use crate::ssh::SSHManager;
+use crate::ssh_config::{self, OpenSshConfig};
+use crate::tunnel_manager::{unix_now, TunnelManager};
+use crate::vault::{self, Vault};
+use clap::{Args, Parser, Subcommand};
+use ssh_key::PrivateKey;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// 退出码：成功
+pub const EXIT_OK: i32 = 0;
+/// 退出码：操作失败，如配置文件无法读写、参数校验失败
+pub const EXIT_FAILURE: i32 = 1;
+/// 退出码：找不到指定的主机（参数错误由 clap 以退出码 2 报告）
+pub const EXIT_NOT_FOUND: i32 = 3;
+
+/// SSH Manager - SSH 配置管理工具
+///
+/// 不带子命令时启动 TUI 界面；指定编号或别名时直接连接。
+#[derive(Debug, Parser)]
+#[command(
+    name = "mssh",
+    version,
+    after_help = "退出码: 0 成功, 1 操作失败, 2 参数错误, 3 找不到主机；connect 返回 ssh 的退出码"
+)]
+pub struct Cli {
+    /// 指定配置文件路径
+    #[arg(short = 'c', long = "config", value_name = "路径", global = true)]
+    pub config: Option<String>,
+
+    /// 启动时显示 SSH 配置导入界面
+    #[arg(long)]
+    pub import_ssh: bool,
+
+    /// 快速连接到指定编号或别名的配置
+    #[arg(short = 'C', value_name = "目标")]
+    pub quick_connect: Option<String>,
+
+    /// 快速连接的编号、别名或关键字
+    #[arg(value_name = "目标")]
+    pub target: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 列出所有配置
+    #[command(alias = "ls")]
+    List {
+        /// 以 JSON 数组输出，代理已按全局配置展开
+        #[arg(long)]
+        json: bool,
+        /// 只列出带有指定标签的主机，可重复或以逗号分隔，需全部匹配；
+        /// 只写 key 时匹配所有 key:value 标签，如 env 匹配 env:prod
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+    },
+    /// 显示配置详情
+    Show {
+        /// 编号或别名
+        target: String,
+        /// 以 JSON 输出，代理已按全局配置展开
+        #[arg(long)]
+        json: bool,
+    },
+    /// 添加配置
+    Add {
+        /// 主机别名
+        #[arg(long)]
+        alias: String,
+        /// 连接地址
+        #[arg(long)]
+        address: String,
+        #[command(flatten)]
+        options: HostOptions,
+    },
+    /// 编辑配置，只修改指定的字段
+    Edit {
+        /// 编号或别名
+        target: String,
+        /// 新的主机别名
+        #[arg(long)]
+        alias: Option<String>,
+        /// 新的连接地址
+        #[arg(long)]
+        address: Option<String>,
+        /// 清除已有的端口转发，可与 -L/-R/-D 一起使用以替换全部转发
+        #[arg(long)]
+        clear_forwards: bool,
+        #[command(flatten)]
+        options: HostOptions,
+    },
+    /// 删除配置
+    #[command(alias = "remove")]
+    Rm {
+        /// 编号或别名
+        target: String,
+    },
+    /// 连接到配置，仅隧道的主机会在后台启动隧道
+    Connect {
+        /// 编号、别名或关键字
+        #[arg(required_unless_present = "tags")]
+        target: Option<String>,
+        /// 只在带有指定标签的主机中查找，匹配多个时交互选择
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+    },
+    /// 从 ~/.ssh/config 导入主机
+    Import {
+        /// 要导入的主机别名，不指定时导入所有尚未添加的主机
+        aliases: Vec<String>,
+    },
+    /// 导出为 OpenSSH 配置片段
+    #[command(alias = "export-ssh")]
+    Export {
+        /// 导出文件路径，默认 ~/.ssh/config.d/mssh.conf
+        #[arg(short, long, value_name = "路径")]
+        output: Option<String>,
+    },
+    /// 查看连接记录，最近的在前
+    History {
+        /// 只显示指定编号或别名的主机
+        target: Option<String>,
+        /// 最多显示的记录数
+        #[arg(short = 'n', long, value_name = "数量", default_value_t = 20)]
+        limit: usize,
+        /// 以 JSON 数组输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 管理后台隧道
+    Tunnels {
+        #[command(subcommand)]
+        action: Option<TunnelAction>,
+    },
+    /// 管理加密保存代理密码的密码库
+    Vault {
+        #[command(subcommand)]
+        action: Option<VaultAction>,
+    },
+    /// 查看 ssh-agent 中的密钥，或将主机使用的密钥添加到 ssh-agent
+    Agent {
+        #[command(subcommand)]
+        action: Option<AgentAction>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AgentAction {
+    /// 列出 ssh-agent 中已加载的密钥及使用它们的主机
+    List,
+    /// 将主机配置的密钥添加到 ssh-agent，口令优先从密码库读取，否则在终端中输入
+    Add {
+        /// 编号、别名或关键字
+        target: String,
+        /// 有效期（分钟），默认使用配置中的 agent_lifetime_mins，未设置时为 60
+        #[arg(short = 't', long, value_name = "分钟")]
+        minutes: Option<u64>,
+        /// 将输入的口令保存到密码库，之后添加时不再输入
+        #[arg(long)]
+        save_passphrase: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum VaultAction {
+    /// 列出密码库中的条目及引用它们的代理
+    List,
+    /// 添加或修改条目，内容从终端读取
+    Set {
+        /// 条目名称，如 proxy/global，配置中以 vault:<名称> 引用
+        id: String,
+    },
+    /// 删除条目
+    Rm {
+        /// 条目名称
+        id: String,
+    },
+    /// 将配置文件中的明文代理密码移入密码库
+    Migrate,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TunnelAction {
+    /// 列出后台运行的隧道
+    List,
+    /// 停止指定主机的隧道
+    Stop {
+        /// 主机别名
+        alias: String,
+    },
+}
+
+/// add 和 edit 共用的主机选项
+#[derive(Debug, Args)]
+pub struct HostOptions {
+    /// SSH 端口
+    #[arg(short, long)]
+    port: Option<u16>,
+    /// 登录用户，传入空字符串表示清除
+    #[arg(short, long)]
+    user: Option<String>,
+    /// 私钥路径，传入空字符串表示清除
+    #[arg(short = 'i', long)]
+    key: Option<String>,
+    /// 分组路径，如 prod/db，传入空字符串表示移出分组
+    #[arg(short, long)]
+    group: Option<String>,
+    /// 添加标签，可重复或以逗号分隔，如 env:prod
+    #[arg(short, long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+    /// 移除标签，只写 key 时移除所有 key:value 标签
+    #[arg(long = "untag", value_name = "TAG", value_delimiter = ',')]
+    untags: Vec<String>,
+    /// 本地转发 [bind:]port:host:hostport，可重复
+    #[arg(short = 'L', value_name = "SPEC")]
+    local_forward: Vec<String>,
+    /// 远程转发 [bind:]port:host:hostport，可重复
+    #[arg(short = 'R', value_name = "SPEC")]
+    remote_forward: Vec<String>,
+    /// 动态转发 [bind:]port，可重复
+    #[arg(short = 'D', value_name = "SPEC")]
+    dynamic_forward: Vec<String>,
+    /// 仅隧道：连接时只在后台运行端口转发
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    tunnel_only: Option<bool>,
+    /// 代理: global、none、socks5://主机:端口 或 http://主机:端口
+    #[arg(long, conflicts_with = "jump")]
+    proxy: Option<String>,
+    /// 自定义代理的用户名
+    #[arg(long)]
+    proxy_user: Option<String>,
+    /// 输入自定义代理的密码：在终端中输入，标准输入不是终端时读取第一行；
+    /// 也可以输入 env:、cmd: 等密码引用，留空则清除密码
+    #[arg(long)]
+    proxy_password: bool,
+    /// 跳板机链，按连接顺序以逗号分隔的主机别名
+    #[arg(short = 'J', long, value_delimiter = ',')]
+    jump: Vec<String>,
+}
+
+impl HostOptions {
+    /// 将选项应用到配置
+    ///
+    /// # 参数
+    /// - `config`: 要修改的配置
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为包含错误信息的 Err
+    fn apply(&self, config: &mut SSHConfig) -> Result<(), String> {
+        if let Some(port) = self.port {
+            config.port = Some(port);
+        }
+        if let Some(user) = &self.user {
+            config.user = Some(user.clone()).filter(|u| !u.is_empty());
+        }
+        if let Some(key) = &self.key {
+            config.key = Some(key.clone()).filter(|k| !k.is_empty());
+        }
+        if let Some(group) = &self.group {
+            config.group = normalize_group(group);
+        }
+        config
+            .tags
+            .retain(|tag| !self.untags.iter().any(|filter| tag_matches(tag, filter)));
+        for tag in self.tags.iter().flat_map(|tags| parse_tags(tags)) {
+            if !config.tags.contains(&tag) {
+                config.tags.push(tag);
+            }
+        }
+
+        let specs = self
+            .local_forward
+            .iter()
+            .map(|spec| (ForwardKind::Local, spec))
+            .chain(
+                self.remote_forward
+                    .iter()
+                    .map(|spec| (ForwardKind::Remote, spec)),
+            )
+            .chain(
+                self.dynamic_forward
+                    .iter()
+                    .map(|spec| (ForwardKind::Dynamic, spec)),
+            );
+        for (kind, spec) in specs {
+            let forward = PortForward::from_spec(kind, spec)?;
+            if !config.port_forwards.contains(&forward) {
+                config.port_forwards.push(forward);
+            }
+        }
+
+        if let Some(tunnel_only) = self.tunnel_only {
+            config.tunnel_only = tunnel_only;
+        }
+
+        if let Some(proxy) = &self.proxy {
+            apply_proxy(config, proxy)?;
+        }
+        if !self.jump.is_empty() {
+            config.use_global_proxy = false;
+            config.proxy = Some(ProxyConfig {
+                proxy_type: ProxyType::Jump,
+                jump_hosts: self.jump.clone(),
+                ..ProxyConfig::default()
+            });
+        }
+
+        if self.proxy_user.is_some() || self.proxy_password {
+            let proxy = config
+                .proxy
+                .as_mut()
+                .filter(|p| matches!(p.proxy_type, ProxyType::Socks5 | ProxyType::Http))
+                .filter(|_| !config.use_global_proxy)
+                .ok_or("--proxy-user 和 --proxy-password 需要配合自定义代理使用")?;
+            if let Some(user) = &self.proxy_user {
+                proxy.username = Some(user.clone()).filter(|u| !u.is_empty());
+            }
+            if self.proxy_password {
+                let password =
+                    read_proxy_password().map_err(|e| format!("无法读取代理密码: {e}"))?;
+                proxy.password = Some(password.to_string()).filter(|p| !p.is_empty());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 读取 `--proxy-password` 的密码，密码不会出现在命令行参数中
+///
+/// # 返回
+/// 返回 Result，成功为输入的密码，读取失败或取消时为 Err
+fn read_proxy_password() -> io::Result<Zeroizing<String>> {
+    if io::stdin().is_terminal() {
+        return vault::read_password("代理密码: ");
+    }
+    let mut line = Zeroizing::new(String::new());
+    io::stdin().read_line(&mut line)?;
+    Ok(Zeroizing::new(
+        line.trim_end_matches(['\r', '\n']).to_string(),
+    ))
+}
+
+/// 解析 `--proxy` 参数并应用到配置
+///
+/// # 参数
+/// - `config`: 要修改的配置
+/// - `value`: `global`、`none`、`socks5://主机:端口` 或 `http://主机:端口`
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为包含错误信息的 Err
+fn apply_proxy(config: &mut SSHConfig, value: &str) -> Result<(), String> {
+    let invalid = || {
+        format!("代理格式无效: {value}，应为 global、none、socks5://主机:端口 或 http://主机:端口")
+    };
+
+    let (proxy_type, address) = match value {
+        "global" => {
+            config.use_global_proxy = true;
+            config.proxy = None;
+            return Ok(());
+        }
+        "none" => {
+            config.use_global_proxy = false;
+            config.proxy = None;
+            return Ok(());
+        }
+        _ => match value.split_once("://") {
+            Some(("socks5", address)) => (ProxyType::Socks5, address),
+            Some(("http", address)) => (ProxyType::Http, address),
+            _ => return Err(invalid()),
+        },
+    };
+
+    let (host, port) = split_host_port(address)?;
+    // 修改代理地址时保留已有的认证信息
+    let (username, password) = match config.proxy.take() {
+        Some(old) if old.proxy_type == proxy_type => (old.username, old.password),
+        _ => (None, None),
+    };
+    config.use_global_proxy = false;
+    config.proxy = Some(ProxyConfig {
+        proxy_type,
+        host,
+        port: Some(port),
+        username,
+        password,
+        jump_hosts: Vec::new(),
+    });
+    Ok(())
+}
+
+/// 子命令执行失败的原因及退出码
+#[derive(Debug)]
+pub struct CliError {
+    code: i32,
+    message: String,
+}
+
+impl CliError {
+    /// 创建找不到主机的错误
+    ///
+    /// # 参数
+    /// - `message`: 错误信息
+    ///
+    /// # 返回
+    /// 返回退出码为 `EXIT_NOT_FOUND` 的错误
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            code: EXIT_NOT_FOUND,
+            message: message.into(),
+        }
+    }
+}
+
+impl<E: std::fmt::Display> From<E> for CliError {
+    fn from(error: E) -> Self {
+        Self {
+            code: EXIT_FAILURE,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// 执行子命令
+///
+/// # 参数
+/// - `command`: 子命令
+/// - `config_path`: 配置文件路径，可选
+///
+/// # 返回
+/// 返回进程退出码
+pub fn run(command: Command, config_path: Option<String>) -> i32 {
+    let result = match command {
+        Command::List { json, tags } => {
+            load(config_path).and_then(|manager| list(&manager, json, &tags))
+        }
+        Command::Show { target, json } => {
+            load(config_path).and_then(|manager| show(&manager, &target, json))
+        }
+        Command::Add {
+            alias,
+            address,
+            options,
+        } => add(config_path, alias, address, &options),
+        Command::Edit {
+            target,
+            alias,
+            address,
+            clear_forwards,
+            options,
+        } => edit(
+            config_path,
+            &target,
+            alias,
+            address,
+            clear_forwards,
+            &options,
+        ),
+        Command::Rm { target } => remove(config_path, &target),
+        Command::Connect { target, tags } => return connect(config_path, target.as_deref(), &tags),
+        Command::Import { aliases } => import(config_path, &aliases),
+        Command::Export { output } => export(config_path, output),
+        Command::History {
+            target,
+            limit,
+            json,
+        } => {
+            load(config_path).and_then(|manager| history(&manager, target.as_deref(), limit, json))
+        }
+        Command::Tunnels { action } => tunnels(action),
+        Command::Vault { action } => {
+            load(config_path).and_then(|mut manager| vault(&mut manager, action))
+        }
+        Command::Agent { action } => load(config_path).and_then(|manager| agent(&manager, action)),
+    };
+
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(e) => {
+            eprintln!("错误: {}", e.message);
+            e.code
+        }
+    }
+}
+
+/// 连接到指定配置，供 `connect` 子命令和快速连接使用
+///
+/// # 参数
+/// - `config_path`: 配置文件路径，可选
+/// - `target`: 编号、别名或关键字，指定标签时可省略
+/// - `tags`: 标签过滤条件
+///
+/// # 返回
+/// 返回进程退出码，ssh 正常运行时为 ssh 的退出码
+pub fn connect(config_path: Option<String>, target: Option<&str>, tags: &[String]) -> i32 {
+    let result = load(config_path).and_then(|manager| {
+        let index = match_target_with_tags(&manager, target, tags)?;
+        let config = &manager.configs[index];
+        let jump_chain = manager.resolve_jump_chain(config)?;
+        let mut ssh_manager = SSHManager::new(
+            manager.global_config.clone(),
+            Vault::new(manager.config_path()),
+            manager.config_path().to_path_buf(),
+        );
+        if ssh_manager.needs_vault(config) {
+            ssh_manager.vault.unlock_from_terminal()?;
+        }
+
+        if config.tunnel_only {
+            if config.enabled_forwards().next().is_none() {
+                return Err(format!("{} 没有启用的端口转发", config.alias).into());
+            }
+            let cmd = ssh_manager.tunnel_command(config, &jump_chain)?;
+            let mut tunnel_manager = TunnelManager::new();
+            let tunnel = tunnel_manager.start(config, cmd)?;
+            println!("隧道已启动: {} (PID {})", tunnel.alias, tunnel.pid);
+            println!("使用 mssh tunnels stop {} 停止隧道", tunnel.alias);
+            return Ok(EXIT_OK);
+        }
+
+        println!("正在连接到 {} (编号: {})", config.alias, index + 1);
+        let started_at = unix_now();
+        let status = ssh_manager.run(config, &jump_chain);
+        let exit_code = status.as_ref().ok().and_then(|s| s.code());
+        let mut history = HistoryManager::load(manager.config_path());
+        if let Err(e) = history.record(&config.alias, started_at, exit_code) {
+            eprintln!("警告: 无法写入连接记录: {e}");
+        }
+        let status = status?;
+        Ok(status.code().unwrap_or(EXIT_FAILURE))
+    });
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("错误: {}", e.message);
+            e.code
+        }
+    }
+}
+
+/// 在带有指定标签的主机中查找目标
+///
+/// 没有标签时等同于 `ConfigManager::match_target`。有标签时先按标签过滤，再按编号、
+/// 别名或关键字查找；匹配多个主机时，在终端中交互选择。
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `target`: 编号、别名或关键字，可选
+/// - `tags`: 标签过滤条件，需全部匹配
+///
+/// # 返回
+/// 返回 Result，成功为配置的下标，失败为 CliError
+fn match_target_with_tags(
+    manager: &ConfigManager,
+    target: Option<&str>,
+    tags: &[String],
+) -> Result<usize, CliError> {
+    if tags.is_empty() {
+        let target = target.ok_or("请指定目标或 --tag")?;
+        return manager.match_target(target).map_err(CliError::not_found);
+    }
+
+    let mut candidates: Vec<usize> = (0..manager.configs.len())
+        .filter(|&i| has_tags(&manager.configs[i], tags))
+        .collect();
+    if let Some(target) = target {
+        match manager.find_index(target) {
+            Some(index) if candidates.contains(&index) => candidates = vec![index],
+            Some(_) => candidates.clear(),
+            None => candidates.retain(|&i| {
+                let config = &manager.configs[i];
+                config.alias.contains(target)
+                    || config.address.contains(target)
+                    || config.user.as_ref().is_some_and(|u| u.contains(target))
+            }),
+        }
+    }
+
+    match candidates.as_slice() {
+        [] => Err(CliError::not_found(format!(
+            "未找到带有标签 {} 的配置{}",
+            tags.join(", "),
+            target.map(|t| format!(": {t}")).unwrap_or_default()
+        ))),
+        [index] => Ok(*index),
+        _ => pick(manager, &candidates),
+    }
+}
+
+/// 检查主机是否带有所有指定的标签
+///
+/// # 参数
+/// - `config`: SSH 配置
+/// - `tags`: 标签过滤条件
+///
+/// # 返回
+/// 返回 true 表示全部匹配
+fn has_tags(config: &SSHConfig, tags: &[String]) -> bool {
+    tags.iter().all(|tag| config.has_tag(tag))
+}
+
+/// 在终端中从多个候选主机中选择一个
+///
+/// 标准输入不是终端时不进行交互，直接列出候选主机并返回错误。
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `candidates`: 候选主机的下标
+///
+/// # 返回
+/// 返回 Result，成功为选中主机的下标，取消或无效输入时为 CliError
+fn pick(manager: &ConfigManager, candidates: &[usize]) -> Result<usize, CliError> {
+    let describe = |i: usize| {
+        let config = &manager.configs[i];
+        format!(
+            "{} ({}@{}){}",
+            config.alias,
+            config.user.as_deref().unwrap_or("root"),
+            config.address,
+            if config.tags.is_empty() {
+                String::new()
+            } else {
+                format!(" [标签: {}]", config.tags.join(", "))
+            }
+        )
+    };
+
+    if !io::stdin().is_terminal() {
+        let mut message = "找到多个匹配的配置:".to_string();
+        for &i in candidates {
+            message.push_str(&format!("\n  {}: {}", i + 1, describe(i)));
+        }
+        message.push_str("\n请使用更具体的编号、别名或标签");
+        return Err(CliError::not_found(message));
+    }
+
+    eprintln!("找到 {} 个匹配的配置:", candidates.len());
+    for (n, &i) in candidates.iter().enumerate() {
+        eprintln!("  {}) {}", n + 1, describe(i));
+    }
+    eprint!("请选择 [1-{}]: ", candidates.len());
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("已取消".into());
+    }
+    input
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|n| candidates.get(n).copied())
+        .ok_or_else(|| format!("无效的选择: {input}").into())
+}
+
+/// 加载配置文件
+///
+/// # 参数
+/// - `config_path`: 配置文件路径，可选
+///
+/// # 返回
+/// 返回 Result，成功为配置管理器，失败为 CliError
+fn load(config_path: Option<String>) -> Result<ConfigManager, CliError> {
+    Ok(ConfigManager::load(ConfigManager::resolve_path(
+        config_path,
+    ))?)
+}
+
+/// 查找编号或别名对应的配置下标
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `target`: 编号或别名
+///
+/// # 返回
+/// 返回 Result，成功为配置下标，找不到时为 CliError
+fn find(manager: &ConfigManager, target: &str) -> Result<usize, CliError> {
+    manager
+        .find_index(target)
+        .ok_or_else(|| CliError::not_found(format!("未找到配置: {target}")))
+}
+
+/// 按表单的规则校验配置，私钥权限过宽时输出警告
+///
+/// 与表单一致，私钥路径与修改前相同时不检查私钥文件。
+///
+/// # 参数
+/// - `config`: SSH 配置
+/// - `previous`: 修改前的配置，添加主机时为 None
+///
+/// # 返回
+/// 返回 Result，成功为校验后的配置，失败为 CliError
+fn validate(config: &SSHConfig, previous: Option<&SSHConfig>) -> Result<SSHConfig, CliError> {
+    let mut form = FormData::from_config(config);
+    form.original_key = previous.and_then(|previous| previous.key.clone());
+    let config = form.to_ssh_config()?;
+    if let Some(key) = &config.key {
+        if let Some(mode) = key_manager::insecure_mode(key) {
+            eprintln!(
+                "警告: 私钥 {key} 的权限为 0{mode:o}，ssh 会拒绝使用，可运行 chmod 600 {}",
+                shellexpand::tilde(key)
+            );
+        }
+    }
+    Ok(config)
+}
+
+/// 展开配置实际生效的代理，供 JSON 输出使用
+///
+/// 使用全局代理时以全局代理替换 `proxy`，代理未启用时 `proxy` 为空。
+/// 代理密码只保留 `vault:`、`env:` 等引用，明文密码不输出，
+/// 是否设置了密码由 `proxy.has_password` 表示。
+///
+/// # 参数
+/// - `config`: SSH 配置
+/// - `global_config`: 全局配置
+///
+/// # 返回
+/// 返回 Result，成功为展开代理后的配置，序列化失败时为 CliError
+fn resolved_config(
+    config: &SSHConfig,
+    global_config: &GlobalConfig,
+) -> Result<serde_json::Value, CliError> {
+    let mut resolved = config.clone();
+    resolved.proxy = match config.jump_hosts() {
+        [] => config.effective_proxy(global_config).cloned(),
+        _ => config.proxy.clone(),
+    };
+    let mut has_password = false;
+    if let Some(proxy) = &mut resolved.proxy {
+        has_password = proxy.password.as_deref().is_some_and(|p| !p.is_empty());
+        proxy.password = proxy
+            .password
+            .take()
+            .filter(|p| !p.is_empty() && !vault::is_plaintext(p));
+    }
+
+    let mut value = serde_json::to_value(&resolved)?;
+    if let Some(proxy) = value
+        .get_mut("proxy")
+        .and_then(serde_json::Value::as_object_mut)
+    {
+        proxy.insert("has_password".to_string(), has_password.into());
+    }
+    Ok(value)
+}
+
+/// 以 JSON 输出到标准输出
+///
+/// 下游程序（如 `head`）提前关闭管道时视为成功。
+///
+/// # 参数
+/// - `value`: 要输出的值
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), CliError> {
+    let mut stdout = io::stdout().lock();
+    let result = serde_json::to_writer_pretty(&mut stdout, value)
+        .map_err(io::Error::from)
+        .and_then(|()| writeln!(stdout));
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// 执行 list 子命令
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `json`: 是否以 JSON 输出
+/// - `tags`: 标签过滤条件，为空时列出所有主机
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn list(manager: &ConfigManager, json: bool, tags: &[String]) -> Result<(), CliError> {
+    // 编号保持为配置列表中的编号，过滤后仍可用于连接
+    let configs: Vec<(usize, &SSHConfig)> = manager
+        .configs
+        .iter()
+        .enumerate()
+        .filter(|(_, config)| has_tags(config, tags))
+        .collect();
+
+    if json {
+        let configs = configs
+            .iter()
+            .map(|(_, config)| resolved_config(config, &manager.global_config))
+            .collect::<Result<Vec<_>, _>>()?;
+        return print_json(&configs);
+    }
+
+    if manager.configs.is_empty() {
+        println!("暂无配置");
+    } else if configs.is_empty() {
+        println!("没有带有标签 {} 的配置", tags.join(", "));
+    }
+    for (i, config) in configs {
+        println!(
+            "{}. {} ({}@{}:{}){}",
+            i + 1,
+            config.alias,
+            config.user.as_deref().unwrap_or("root"),
+            config.address,
+            config.port.unwrap_or(22),
+            summary_tags(config, &manager.global_config)
+        );
+    }
+    Ok(())
+}
+
+/// 执行 history 子命令
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `target`: 编号或别名，可选；已删除的主机可直接使用别名
+/// - `limit`: 最多显示的记录数
+/// - `json`: 是否以 JSON 输出
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn history(
+    manager: &ConfigManager,
+    target: Option<&str>,
+    limit: usize,
+    json: bool,
+) -> Result<(), CliError> {
+    let alias = target.map(|target| {
+        manager
+            .find_index(target)
+            .map_or(target, |i| manager.configs[i].alias.as_str())
+    });
+    let history = HistoryManager::load(manager.config_path());
+    let entries: Vec<&HistoryEntry> = history
+        .entries
+        .iter()
+        .rev()
+        .filter(|entry| alias.map_or(true, |alias| entry.alias == alias))
+        .take(limit)
+        .collect();
+
+    if json {
+        return print_json(&entries);
+    }
+
+    if entries.is_empty() {
+        match alias {
+            Some(alias) => println!("{alias} 暂无连接记录"),
+            None => println!("暂无连接记录"),
+        }
+    }
+    for entry in entries {
+        println!(
+            "{}  {}  {}",
+            format_time(entry.started_at),
+            entry.alias,
+            entry.summary()
+        );
+    }
+    Ok(())
+}
+
+/// 生成列表中附加在主机后的标记
+///
+/// # 参数
+/// - `config`: SSH 配置
+/// - `global_config`: 全局配置
+///
+/// # 返回
+/// 返回形如 ` [全局代理] [端口转发: 2 条]` 的文本
+fn summary_tags(config: &SSHConfig, global_config: &GlobalConfig) -> String {
+    let mut tags = String::new();
+    if let Some(group) = &config.group {
+        tags.push_str(&format!(" [分组: {group}]"));
+    }
+    if !config.tags.is_empty() {
+        tags.push_str(&format!(" [标签: {}]", config.tags.join(", ")));
+    }
+    if !config.jump_hosts().is_empty() {
+        tags.push_str(&format!(" [跳板机: {}]", config.jump_hosts().join(" → ")));
+    } else if let Some(proxy) = config.effective_proxy(global_config) {
+        if config.use_global_proxy {
+            tags.push_str(" [全局代理]");
+        } else {
+            tags.push_str(&format!(" [{}代理]", proxy_type_label(&proxy.proxy_type)));
+        }
+    }
+
+    let forwards: Vec<_> = config.enabled_forwards().collect();
+    match forwards.as_slice() {
+        [] => {}
+        [pf] => tags.push_str(&format!(" [端口转发: {}]", pf.summary())),
+        _ => tags.push_str(&format!(" [端口转发: {} 条]", forwards.len())),
+    }
+    if config.tunnel_only {
+        tags.push_str(" [仅隧道]");
+    }
+    tags
+}
+
+/// 获取代理类型的显示名称
+///
+/// # 参数
+/// - `proxy_type`: 代理类型
+///
+/// # 返回
+/// 返回显示名称
+fn proxy_type_label(proxy_type: &ProxyType) -> &'static str {
+    match proxy_type {
+        ProxyType::Socks5 => "SOCKS5",
+        ProxyType::Http => "HTTP",
+        ProxyType::Jump => "跳板机",
+        ProxyType::None => "无",
+    }
+}
+
+/// 执行 show 子命令
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `target`: 编号或别名
+/// - `json`: 是否以 JSON 输出
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn show(manager: &ConfigManager, target: &str, json: bool) -> Result<(), CliError> {
+    let config = &manager.configs[find(manager, target)?];
+    if json {
+        return print_json(&resolved_config(config, &manager.global_config)?);
+    }
+
+    println!("别名: {}", config.alias);
+    println!("地址: {}", config.address);
+    println!("端口: {}", config.port.unwrap_or(22));
+    println!("用户: {}", config.user.as_deref().unwrap_or("root"));
+    println!("密钥: {}", config.key.as_deref().unwrap_or("无"));
+    if let Some(group) = &config.group {
+        println!("分组: {group}");
+    }
+    if !config.tags.is_empty() {
+        println!("标签: {}", config.tags.join(", "));
+    }
+
+    if config.port_forwards.is_empty() {
+        println!("端口转发: 无");
+    } else {
+        println!("端口转发:");
+        for pf in &config.port_forwards {
+            let state = if pf.enabled { "" } else { " (禁用)" };
+            println!("  {}{state}", pf.summary());
+        }
+    }
+    if config.tunnel_only {
+        println!("仅隧道: 是");
+    }
+
+    if !config.jump_hosts().is_empty() {
+        println!("代理: 跳板机 {}", config.jump_hosts().join(" → "));
+    } else if let Some(proxy) = config.effective_proxy(&manager.global_config) {
+        println!(
+            "代理: {} {} {}:{}{}",
+            if config.use_global_proxy {
+                "全局代理"
+            } else {
+                "自定义代理"
+            },
+            proxy_type_label(&proxy.proxy_type),
+            proxy.host,
+            proxy.effective_port().unwrap_or(0),
+            if proxy.has_credentials() {
+                " (认证)"
+            } else {
+                ""
+            }
+        );
+    } else {
+        println!("代理: 无");
+    }
+    Ok(())
+}
+
+/// 执行 add 子命令
+///
+/// # 参数
+/// - `config_path`: 配置文件路径，可选
+/// - `alias`: 主机别名
+/// - `address`: 连接地址
+/// - `options`: 其他主机选项
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn add(
+    config_path: Option<String>,
+    alias: String,
+    address: String,
+    options: &HostOptions,
+) -> Result<(), CliError> {
+    let mut manager = load(config_path)?;
+
+    // 与表单一致，新配置默认使用全局代理
+    let mut config = SSHConfig {
+        alias,
+        address,
+        port: None,
+        user: None,
+        key: None,
+        group: None,
+        tags: Vec::new(),
+        port_forwards: Vec::new(),
+        tunnel_only: false,
+        proxy: None,
+        use_global_proxy: true,
+    };
+    options.apply(&mut config)?;
+    let mut config = validate(&config, None)?;
+    seal_proxy_password(&manager, &mut config)?;
+
+    let alias = config.alias.clone();
+    manager.add_config(config)?;
+    println!("已添加: {alias}");
+    Ok(())
+}
+
+/// 将 `--proxy-password` 输入的明文密码移入密码库，需要时在终端中输入主密码
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `config`: 要保存的配置
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn seal_proxy_password(manager: &ConfigManager, config: &mut SSHConfig) -> Result<(), CliError> {
+    let id = vault::proxy_secret_id(Some(&config.alias));
+    let Some(password) = config
+        .proxy
+        .as_mut()
+        .map(|proxy| &mut proxy.password)
+        .filter(|password| password.as_deref().is_some_and(vault::is_plaintext))
+    else {
+        return Ok(());
+    };
+    let mut vault = Vault::new(manager.config_path());
+    vault.unlock_from_terminal()?;
+    vault.seal(password, &id)?;
+    Ok(())
+}
+
+/// 主机删除或代理密码被替换后，从密码库移除不再使用的条目
+///
+/// 移除失败只打印警告，不影响已保存的配置。
+///
+/// # 参数
+/// - `manager`: 已保存修改的配置管理器
+/// - `previous`: 修改或删除前的主机配置
+fn release_secret(manager: &ConfigManager, previous: &SSHConfig) {
+    let password = previous.proxy.as_ref().and_then(|p| p.password.as_deref());
+    let Some(id) = vault::host_secret_id(password) else {
+        return;
+    };
+    if manager.references_secret(id) {
+        return;
+    }
+    let mut vault = Vault::new(manager.config_path());
+    if !vault.exists() {
+        return;
+    }
+    let result = vault
+        .unlock_from_terminal()
+        .and_then(|()| vault.remove(id).map_err(Into::into));
+    if let Err(e) = result {
+        eprintln!("警告: 无法从密码库移除 {id}: {e}，可运行 mssh vault rm {id}");
+    }
+}
+
+/// 执行 edit 子命令
+///
+/// # 参数
+/// - `config_path`: 配置文件路径，可选
+/// - `target`: 编号或别名
+/// - `alias`: 新的主机别名，可选
+/// - `address`: 新的连接地址，可选
+/// - `clear_forwards`: 是否清除已有的端口转发
+/// - `options`: 其他主机选项
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn edit(
+    config_path: Option<String>,
+    target: &str,
+    alias: Option<String>,
+    address: Option<String>,
+    clear_forwards: bool,
+    options: &HostOptions,
+) -> Result<(), CliError> {
+    let mut manager = load(config_path)?;
+    let previous = manager.configs[find(&manager, target)?].clone();
+    let mut config = previous.clone();
+    let old_alias = config.alias.clone();
+
+    if let Some(alias) = alias {
+        config.alias = alias;
+    }
+    if let Some(address) = address {
+        config.address = address;
+    }
+    if clear_forwards {
+        config.port_forwards.clear();
+    }
+    options.apply(&mut config)?;
+    let mut config = validate(&config, Some(&previous))?;
+    seal_proxy_password(&manager, &mut config)?;
+
+    let alias = config.alias.clone();
+    manager.update_config(&old_alias, config)?;
+    release_secret(&manager, &previous);
+    // 与界面中编辑一致，连接记录随别名一起修改，写入失败不影响保存配置
+    let mut history = HistoryManager::load(manager.config_path());
+    if let Err(e) = history.rename(&old_alias, &alias) {
+        eprintln!("警告: 无法更新连接记录: {e}");
+    }
+    println!("已更新: {alias}");
+    Ok(())
+}
+
+/// 执行 rm 子命令，同时停止该主机的后台隧道
+///
+/// # 参数
+/// - `config_path`: 配置文件路径，可选
+/// - `target`: 编号或别名
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn remove(config_path: Option<String>, target: &str) -> Result<(), CliError> {
+    let mut manager = load(config_path)?;
+    let removed = manager.configs[find(&manager, target)?].clone();
+    let alias = removed.alias.clone();
+    manager.remove_config(&alias)?;
+    release_secret(&manager, &removed);
+
+    let mut tunnel_manager = TunnelManager::new();
+    if tunnel_manager.get(&alias).is_some() {
+        tunnel_manager.stop(&alias)?;
+    }
+    println!("已删除: {alias}");
+    Ok(())
+}
+
+/// 执行 import 子命令
+///
+/// # 参数
+/// - `config_path`: 配置文件路径，可选
+/// - `aliases`: 要导入的主机别名，为空时导入所有尚未添加的主机
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn import(config_path: Option<String>, aliases: &[String]) -> Result<(), CliError> {
+    let mut manager = load(config_path)?;
+    let ssh_config_path = ssh_config::default_config_path().ok_or("无法获取用户主目录")?;
+    let candidates = OpenSshConfig::load(&ssh_config_path)
+        .map_err(|e| format!("无法读取 {}: {e}", ssh_config_path.display()))?
+        .to_ssh_configs();
+
+    let selected: Vec<SSHConfig> = if aliases.is_empty() {
+        candidates
+            .iter()
+            .filter(|host| manager.find_index(&host.alias).is_none())
+            .cloned()
+            .collect()
+    } else {
+        let mut selected = Vec::new();
+        for alias in aliases {
+            let host = candidates
+                .iter()
+                .find(|host| &host.alias == alias)
+                .ok_or_else(|| CliError::not_found(format!("~/.ssh/config 中没有主机: {alias}")))?;
+            selected.push(host.clone());
+        }
+        selected
+    };
+
+    if selected.is_empty() {
+        println!("没有可导入的主机");
+        return Ok(());
+    }
+    for alias in manager.import_configs(selected, &candidates)? {
+        println!("已导入: {alias}");
+    }
+    Ok(())
+}
+
+/// 执行 export 子命令
+///
+/// # 参数
+/// - `config_path`: 配置文件路径，可选
+/// - `output`: 导出文件路径，可选
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn export(config_path: Option<String>, output: Option<String>) -> Result<(), CliError> {
+    let manager = load(config_path)?;
+
+    let output = output
+        .map(|path| PathBuf::from(shellexpand::tilde(&path).to_string()))
+        .or_else(ssh_config::default_export_path)
+        .ok_or("无法获取用户主目录")?;
+
+    let count = ssh_config::export_to_file(
+        &output,
+        &manager.configs,
+        &manager.global_config,
+        manager.config_path(),
+    )
+    .map_err(|e| format!("导出失败: {e}"))?;
+    println!("已导出 {count} 个主机到 {}", output.display());
+
+    let included = ssh_config::default_config_path()
+        .and_then(|path| OpenSshConfig::load(&path).ok())
+        .is_some_and(|ssh_config| ssh_config.includes(&output));
+    if !included {
+        println!("提示: 请在 ~/.ssh/config 开头添加以下内容以启用这些主机:");
+        println!("  Include {}", output.display());
+    }
+    Ok(())
+}
+
+/// 执行 vault 子命令
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `action`: 密码库操作，不指定时列出条目
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn vault(manager: &mut ConfigManager, action: Option<VaultAction>) -> Result<(), CliError> {
+    let mut vault = Vault::new(manager.config_path());
+    let action = action.unwrap_or(VaultAction::List);
+    if matches!(action, VaultAction::List | VaultAction::Rm { .. }) && !vault.exists() {
+        return Err(format!("密码库 {} 尚未创建", vault.path().display()).into());
+    }
+    vault.unlock_from_terminal()?;
+
+    match action {
+        VaultAction::List => {
+            let proxies = manager.proxies();
+            let ids = vault.ids();
+            if ids.is_empty() {
+                println!("密码库中没有条目");
+            }
+            for id in ids {
+                let mut users: Vec<&str> = proxies
+                    .iter()
+                    .filter(|(_, proxy)| {
+                        proxy.password.as_deref().and_then(vault::secret_id) == Some(id)
+                    })
+                    .map(|(alias, _)| alias.unwrap_or("全局代理"))
+                    .collect();
+                // 私钥口令的条目由使用该密钥的主机使用
+                users.extend(
+                    manager
+                        .configs
+                        .iter()
+                        .filter(|config| {
+                            config.key.as_deref().map(vault::key_secret_id).as_deref() == Some(id)
+                        })
+                        .map(|config| config.alias.as_str()),
+                );
+                if users.is_empty() {
+                    println!("{id}  (未使用)");
+                } else {
+                    println!("{id}  {}", users.join(", "));
+                }
+            }
+        }
+        VaultAction::Set { id } => {
+            let secret = vault::read_password(&format!("{id} 的内容: "))?;
+            vault.set(&id, &secret)?;
+            println!("已保存: {id}，在配置中以 {} 引用", vault::reference(&id));
+        }
+        VaultAction::Rm { id } => {
+            vault.remove(&id)?;
+            println!("已删除: {id}");
+        }
+        VaultAction::Migrate => {
+            let count = manager.migrate_passwords(&mut vault)?;
+            if count == 0 {
+                println!("配置文件中没有明文密码");
+            } else {
+                println!(
+                    "已将 {count} 个明文密码移入密码库 {}",
+                    vault.path().display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 执行 agent 子命令
+///
+/// # 参数
+/// - `manager`: 配置管理器
+/// - `action`: ssh-agent 操作，不指定时列出已加载的密钥
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn agent(manager: &ConfigManager, action: Option<AgentAction>) -> Result<(), CliError> {
+    let client = AgentClient::from_env()?;
+
+    match action.unwrap_or(AgentAction::List) {
+        AgentAction::List => {
+            let identities = client.identities()?;
+            if identities.is_empty() {
+                println!("ssh-agent 中没有密钥");
+            }
+            let host_keys: Vec<(&str, Vec<u8>)> = manager
+                .configs
+                .iter()
+                .filter_map(|config| {
+                    let blob = public_key_blob(config.key.as_deref()?).ok()?;
+                    Some((config.alias.as_str(), blob))
+                })
+                .collect();
+            for identity in identities {
+                let hosts: Vec<&str> = host_keys
+                    .iter()
+                    .filter(|(_, blob)| *blob == identity.key_blob)
+                    .map(|(alias, _)| *alias)
+                    .collect();
+                println!(
+                    "{}  {}{}",
+                    identity
+                        .fingerprint()
+                        .unwrap_or_else(|| "未知格式".to_string()),
+                    identity.comment,
+                    if hosts.is_empty() {
+                        String::new()
+                    } else {
+                        format!("  ({})", hosts.join(", "))
+                    }
+                );
+            }
+        }
+        AgentAction::Add {
+            target,
+            minutes,
+            save_passphrase,
+        } => {
+            let config =
+                &manager.configs[manager.match_target(&target).map_err(CliError::not_found)?];
+            let key = config
+                .key
+                .as_deref()
+                .ok_or_else(|| format!("{} 没有配置密钥", config.alias))?;
+            let minutes = minutes
+                .or(manager.global_config.agent_lifetime_mins)
+                .unwrap_or(DEFAULT_LIFETIME_MINS);
+            if minutes == 0 {
+                return Err("有效期必须是大于 0 的分钟数".into());
+            }
+
+            let private_key = load_key(key)?;
+            let private_key = if private_key.is_encrypted() {
+                decrypt_with_vault(manager, key, &private_key, save_passphrase)?
+            } else {
+                private_key
+            };
+            client.add_identity(&private_key, Some(Duration::from_secs(minutes * 60)))?;
+            println!("已添加 {key} 到 ssh-agent，有效期 {minutes} 分钟");
+        }
+    }
+    Ok(())
+}
+
+/// 解密加密的私钥
+///
+/// 密码库中保存了该密钥的口令时直接使用，否则在终端中输入口令。
+/// 不保存口令时密码库可以不解锁，主密码直接回车即可跳过。
+///
+/// # 参数
+/// - `manager`: 配置管理器，用于定位密码库
+/// - `key`: 主机配置中的密钥路径
+/// - `private_key`: 已加密的私钥
+/// - `save`: 是否将输入的口令保存到密码库
+///
+/// # 返回
+/// 返回 Result，成功为解密后的私钥，口令错误或保存失败时为 CliError
+fn decrypt_with_vault(
+    manager: &ConfigManager,
+    key: &str,
+    private_key: &PrivateKey,
+    save: bool,
+) -> Result<PrivateKey, CliError> {
+    let id = vault::key_secret_id(key);
+    let mut vault = Vault::new(manager.config_path());
+    if vault.exists() || save {
+        match vault.unlock_from_terminal() {
+            Ok(()) => {}
+            Err(e) if save => return Err(e.into()),
+            Err(e) => eprintln!("警告: 未解锁密码库: {e}"),
+        }
+    }
+    if let Ok(stored) = vault.get(&id) {
+        match decrypt_key(private_key, stored) {
+            Ok(decrypted) => return Ok(decrypted),
+            Err(_) => eprintln!("警告: 密码库中 {id} 的口令无效"),
+        }
+    }
+
+    let passphrase = vault::read_password(&format!("{key} 的口令: "))?;
+    let decrypted = decrypt_key(private_key, &passphrase)?;
+    if save {
+        vault.set(&id, &passphrase)?;
+        println!("已将口令保存到密码库: {id}");
+    }
+    Ok(decrypted)
+}
+
+/// 执行 tunnels 子命令
+///
+/// # 参数
+/// - `action`: 隧道操作，不指定时列出隧道
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 CliError
+fn tunnels(action: Option<TunnelAction>) -> Result<(), CliError> {
+    let mut tunnel_manager = TunnelManager::new();
+
+    match action.unwrap_or(TunnelAction::List) {
+        TunnelAction::List => {
+            if tunnel_manager.tunnels.is_empty() {
+                println!("没有运行中的隧道");
+            }
+            for tunnel in &tunnel_manager.tunnels {
+                println!(
+                    "{}  PID {}  已运行 {}",
+                    tunnel.alias,
+                    tunnel.pid,
+                    tunnel.uptime()
+                );
+                for forward in &tunnel.forwards {
+                    println!("    {forward}");
+                }
+            }
+        }
+        TunnelAction::Stop { alias } => {
+            if tunnel_manager.get(&alias).is_none() {
+                return Err(CliError::not_found(format!("没有运行中的隧道: {alias}")));
+            }
+            tunnel_manager.stop(&alias)?;
+            println!("隧道已停止: {alias}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(alias: &str, proxy: Option<ProxyConfig>, use_global_proxy: bool) -> SSHConfig {
+        SSHConfig {
+            alias: alias.to_string(),
+            address: format!("{alias}.example.com"),
+            port: None,
+            user: None,
+            key: None,
+            group: None,
+            tags: Vec::new(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy,
+            use_global_proxy,
+        }
+    }
+
+    fn socks(password: Option<&str>) -> ProxyConfig {
+        ProxyConfig {
+            proxy_type: ProxyType::Socks5,
+            host: "proxy.example.com".to_string(),
+            password: password.map(str::to_string),
+            ..ProxyConfig::default()
+        }
+    }
+
+    #[test]
+    fn json_output_hides_plaintext_passwords() {
+        let global = GlobalConfig {
+            proxy: socks(Some("global-secret")),
+            ..GlobalConfig::default()
+        };
+
+        // 使用全局代理时也不输出复制过来的全局代理密码
+        let value = resolved_config(&host("web", None, true), &global).unwrap();
+        assert_eq!(value["proxy"]["password"], serde_json::Value::Null);
+        assert_eq!(value["proxy"]["has_password"], true);
+        assert!(!value.to_string().contains("global-secret"));
+
+        let own = host("db", Some(socks(Some("db-secret"))), false);
+        let value = resolved_config(&own, &global).unwrap();
+        assert!(!value.to_string().contains("db-secret"));
+        assert_eq!(value["proxy"]["has_password"], true);
+
+        // 引用不是密码本身，原样输出
+        let referenced = host("app", Some(socks(Some("vault:proxy/app-0000abcd"))), false);
+        let value = resolved_config(&referenced, &global).unwrap();
+        assert_eq!(value["proxy"]["password"], "vault:proxy/app-0000abcd");
+        assert_eq!(value["proxy"]["has_password"], true);
+
+        let value = resolved_config(&host("cache", Some(socks(None)), false), &global).unwrap();
+        assert_eq!(value["proxy"]["has_password"], false);
+        let value = resolved_config(&host("plain", None, false), &global).unwrap();
+        assert_eq!(value["proxy"], serde_json::Value::Null);
+    }
+}
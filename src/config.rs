@@ -1,15 +1,359 @@
-use crate::proxy::{GlobalConfig, ProxyConfig};
-use serde::{Deserialize, Serialize};
+use crate::proxy::{GlobalConfig, ProxyConfig, ProxyType};
+use crate::ssh_config;
+use crate::vault::{self, Vault, VaultError};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 保留的配置备份数量
+const MAX_BACKUPS: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum ForwardKind {
+    /// 本地转发（-L）
+    #[default]
+    Local,
+    /// 远程转发（-R）
+    Remote,
+    /// 动态转发，即本地 SOCKS 代理（-D）
+    Dynamic,
+}
+
+impl ForwardKind {
+    /// 获取对应的 ssh 命令行参数
+    ///
+    /// # 返回
+    /// 返回 `-L`、`-R` 或 `-D`
+    pub fn flag(&self) -> &'static str {
+        match self {
+            ForwardKind::Local => "-L",
+            ForwardKind::Remote => "-R",
+            ForwardKind::Dynamic => "-D",
+        }
+    }
+
+    /// 获取简短的显示名称
+    ///
+    /// # 返回
+    /// 返回转发类型的中文名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ForwardKind::Local => "本地",
+            ForwardKind::Remote => "远程",
+            ForwardKind::Dynamic => "动态",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PortForward {
+    #[serde(default)]
+    pub kind: ForwardKind,
     pub enabled: bool,
-    pub local: String,  // "0.0.0.0:4422"
+    /// 监听地址 `[bind:]port`，远程转发时监听在服务器一侧
+    pub local: String, // "0.0.0.0:4422"
+    /// 目标地址 `host:port`，动态转发时为空
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub remote: String, // "127.0.0.1:22"
 }
 
+impl PortForward {
+    /// 验证转发配置
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为包含错误信息的 Err
+    pub fn validate(&self) -> Result<(), String> {
+        validate_listen_address(&self.local, self.kind == ForwardKind::Remote)?;
+        match self.kind {
+            ForwardKind::Dynamic => {
+                if !self.remote.is_empty() {
+                    return Err("动态转发不需要目标地址".to_string());
+                }
+            }
+            ForwardKind::Local | ForwardKind::Remote => validate_target_address(&self.remote)?,
+        }
+        Ok(())
+    }
+
+    /// 解析 ssh 命令行形式的转发参数
+    ///
+    /// # 参数
+    /// - `kind`: 转发类型
+    /// - `spec`: `-L`/`-R` 的 `[bind:]port:host:hostport`，或 `-D` 的 `[bind:]port`
+    ///
+    /// # 返回
+    /// 返回 Result，成功为已启用的端口转发，失败为包含错误信息的 Err
+    pub fn from_spec(kind: ForwardKind, spec: &str) -> Result<Self, String> {
+        let (local, remote) = match kind {
+            ForwardKind::Dynamic => (spec.to_string(), String::new()),
+            ForwardKind::Local | ForwardKind::Remote => {
+                let invalid = || format!("转发参数无效: {spec}");
+                let (rest, port) = spec.rsplit_once(':').ok_or_else(invalid)?;
+                let host_start = if rest.ends_with(']') {
+                    rest.rfind('[').ok_or_else(invalid)?
+                } else {
+                    rest.rfind(':').map(|i| i + 1).ok_or_else(invalid)?
+                };
+                let local = rest[..host_start].trim_end_matches(':');
+                (local.to_string(), format!("{}:{port}", &rest[host_start..]))
+            }
+        };
+        let forward = PortForward {
+            kind,
+            enabled: true,
+            local,
+            remote,
+        };
+        forward.validate()?;
+        Ok(forward)
+    }
+
+    /// 生成 ssh 转发参数的值
+    ///
+    /// # 返回
+    /// 返回 `-L`/`-R`/`-D` 之后的参数值
+    pub fn spec(&self) -> String {
+        match self.kind {
+            ForwardKind::Dynamic => self.local.clone(),
+            ForwardKind::Local | ForwardKind::Remote => format!("{}:{}", self.local, self.remote),
+        }
+    }
+
+    /// 生成简短的显示文本
+    ///
+    /// # 返回
+    /// 返回形如 `本地 8080 -> localhost:80` 的文本
+    pub fn summary(&self) -> String {
+        match self.kind {
+            ForwardKind::Dynamic => format!("{} {} (SOCKS)", self.kind.label(), self.local),
+            ForwardKind::Local | ForwardKind::Remote => {
+                format!("{} {} -> {}", self.kind.label(), self.local, self.remote)
+            }
+        }
+    }
+}
+
+/// 主机列表的排序方式
+///
+/// 排序只影响列表的显示顺序，配置文件中的顺序（即主机编号）始终是手动调整的顺序。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
+    /// 按配置文件中的顺序
+    #[default]
+    Manual,
+    /// 按别名
+    Alias,
+    /// 按连接地址
+    Address,
+    /// 最近连接的在前
+    LastUsed,
+    /// 连接次数多的在前
+    MostUsed,
+}
+
+impl SortMode {
+    /// 获取排序方式的显示名称
+    ///
+    /// # 返回
+    /// 返回显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::Manual => "手动",
+            SortMode::Alias => "别名",
+            SortMode::Address => "地址",
+            SortMode::LastUsed => "最近连接",
+            SortMode::MostUsed => "最常连接",
+        }
+    }
+
+    /// 获取下一个排序方式
+    ///
+    /// # 返回
+    /// 返回按 手动、别名、地址、最近连接、最常连接 循环的下一个排序方式
+    pub fn next(&self) -> Self {
+        match self {
+            SortMode::Manual => SortMode::Alias,
+            SortMode::Alias => SortMode::Address,
+            SortMode::Address => SortMode::LastUsed,
+            SortMode::LastUsed => SortMode::MostUsed,
+            SortMode::MostUsed => SortMode::Manual,
+        }
+    }
+
+    /// 检查是否为手动排序
+    ///
+    /// # 返回
+    /// 返回 true 表示按配置文件中的顺序显示
+    pub fn is_manual(&self) -> bool {
+        *self == SortMode::Manual
+    }
+}
+
+/// 规范化分组路径：去掉多余的空白和 `/`
+///
+/// # 参数
+/// - `group`: 用户输入的分组路径，如 ` prod//db/ `
+///
+/// # 返回
+/// 返回规范化后的路径，如 `prod/db`；路径为空时返回 None
+pub fn normalize_group(group: &str) -> Option<String> {
+    let path = group
+        .split('/')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    (!path.is_empty()).then_some(path)
+}
+
+/// 解析标签列表，标签之间以逗号或空白分隔
+///
+/// # 参数
+/// - `input`: 用户输入，如 `env:prod, team:payments k8s-node`
+///
+/// # 返回
+/// 返回去重后的标签，保持输入顺序
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// 检查标签是否匹配过滤条件，不区分大小写
+///
+/// # 参数
+/// - `tag`: 主机的标签，如 `env:prod`
+/// - `filter`: 过滤条件，完整标签或 `key:value` 标签的 key，如 `env`
+///
+/// # 返回
+/// 返回 true 表示匹配
+pub fn tag_matches(tag: &str, filter: &str) -> bool {
+    tag.eq_ignore_ascii_case(filter)
+        || (!filter.contains(':')
+            && tag
+                .split_once(':')
+                .is_some_and(|(key, _)| key.eq_ignore_ascii_case(filter)))
+}
+
+/// 验证转发的监听地址 `[bind_address:]port`
+///
+/// # 参数
+/// - `spec`: 监听地址
+/// - `allow_zero`: 是否允许端口 0（远程转发时由服务器分配端口）
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为包含错误信息的 Err
+fn validate_listen_address(spec: &str, allow_zero: bool) -> Result<(), String> {
+    if spec.is_empty() {
+        return Err("监听地址不能为空".to_string());
+    }
+    // Unix 域套接字
+    if spec.contains('/') {
+        return Ok(());
+    }
+
+    let (bind, port) = match spec.rsplit_once(':') {
+        Some((bind, port)) => (Some(bind), port),
+        None => (None, spec),
+    };
+    let port: u16 = port.parse().map_err(|_| format!("监听端口无效: {spec}"))?;
+    if port == 0 && !allow_zero {
+        return Err(format!("监听端口必须是1-65535之间的有效数字: {spec}"));
+    }
+    if let Some(bind) = bind {
+        if !is_valid_bind_address(bind) {
+            return Err(format!("绑定地址无效: {bind}"));
+        }
+    }
+    Ok(())
+}
+
+/// 验证转发的目标地址 `host:port`
+///
+/// # 参数
+/// - `spec`: 目标地址
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为包含错误信息的 Err
+fn validate_target_address(spec: &str) -> Result<(), String> {
+    if spec.is_empty() {
+        return Err("目标地址不能为空".to_string());
+    }
+    if spec.contains('/') {
+        return Ok(());
+    }
+
+    let (host, port) = spec
+        .rsplit_once(':')
+        .ok_or_else(|| format!("目标地址需要为 主机:端口 格式: {spec}"))?;
+    match port.parse::<u16>() {
+        Ok(port) if port > 0 => {}
+        _ => return Err(format!("目标端口必须是1-65535之间的有效数字: {spec}")),
+    }
+    if host.is_empty() || !is_valid_bind_address(host) || host == "*" {
+        return Err(format!("目标主机无效: {host}"));
+    }
+    Ok(())
+}
+
+/// 检查绑定地址或主机名是否合法
+///
+/// # 参数
+/// - `address`: 地址，IPv6 需要用方括号包裹
+///
+/// # 返回
+/// 返回 true 表示合法
+fn is_valid_bind_address(address: &str) -> bool {
+    if address.is_empty() || address == "*" {
+        return true;
+    }
+    if let Some(ipv6) = address
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        return ipv6.parse::<IpAddr>().is_ok();
+    }
+    if address.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return address.parse::<IpAddr>().is_ok();
+    }
+    address.parse::<IpAddr>().is_ok()
+        || address
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
+}
+
+/// 反序列化端口转发列表，兼容旧版本的单个 `port_forward` 表
+///
+/// # 参数
+/// - `deserializer`: 反序列化器
+///
+/// # 返回
+/// 返回端口转发列表
+fn deserialize_port_forwards<'de, D>(deserializer: D) -> Result<Vec<PortForward>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PortForward),
+        Many(Vec<PortForward>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(forward) => vec![forward],
+        OneOrMany::Many(forwards) => forwards,
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SSHConfig {
     pub alias: String,   // 主机别名
@@ -17,13 +361,114 @@ pub struct SSHConfig {
     pub port: Option<u16>,
     pub user: Option<String>,
     pub key: Option<String>,
-    pub port_forward: Option<PortForward>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>, // 分组路径，如 prod/db
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>, // 标签，如 env:prod
+    #[serde(
+        default,
+        alias = "port_forward",
+        deserialize_with = "deserialize_port_forwards",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub port_forwards: Vec<PortForward>, // 端口转发列表
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tunnel_only: bool, // 仅隧道：不打开 shell，只在后台运行端口转发
     #[serde(default)]
     pub proxy: Option<ProxyConfig>, // 代理配置
     #[serde(default)]
     pub use_global_proxy: bool, // 是否使用全局代理
 }
 
+impl SSHConfig {
+    /// 获取实际生效的代理配置
+    ///
+    /// # 参数
+    /// - `global_config`: 全局配置
+    ///
+    /// # 返回
+    /// 使用全局代理时返回全局代理，否则返回自定义代理；代理未启用时返回 None
+    pub fn effective_proxy<'a>(
+        &'a self,
+        global_config: &'a GlobalConfig,
+    ) -> Option<&'a ProxyConfig> {
+        let proxy = if self.use_global_proxy {
+            Some(&global_config.proxy)
+        } else {
+            self.proxy.as_ref()
+        };
+        proxy.filter(|p| p.is_enabled())
+    }
+
+    /// 获取已启用的端口转发
+    ///
+    /// # 返回
+    /// 返回已启用的端口转发迭代器
+    pub fn enabled_forwards(&self) -> impl Iterator<Item = &PortForward> {
+        self.port_forwards.iter().filter(|pf| pf.enabled)
+    }
+
+    /// 获取自定义的跳板机链
+    ///
+    /// # 返回
+    /// 返回跳板机别名列表，未使用跳板机时为空
+    pub fn jump_hosts(&self) -> &[String] {
+        match &self.proxy {
+            Some(proxy) if !self.use_global_proxy && proxy.proxy_type == ProxyType::Jump => {
+                &proxy.jump_hosts
+            }
+            _ => &[],
+        }
+    }
+
+    /// 检查主机是否属于指定分组（包括子分组）
+    ///
+    /// # 参数
+    /// - `group`: 分组路径
+    ///
+    /// # 返回
+    /// 返回 true 表示主机在该分组或其子分组中
+    pub fn in_group(&self, group: &str) -> bool {
+        self.group.as_deref().is_some_and(|own| {
+            own == group
+                || own
+                    .strip_prefix(group)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// 检查主机是否带有匹配的标签
+    ///
+    /// # 参数
+    /// - `filter`: 过滤条件，参见 `tag_matches`
+    ///
+    /// # 返回
+    /// 返回 true 表示至少有一个标签匹配
+    pub fn has_tag(&self, filter: &str) -> bool {
+        self.tags.iter().any(|tag| tag_matches(tag, filter))
+    }
+
+    /// 获取作为跳板机时的 `-J` 目标
+    ///
+    /// # 返回
+    /// 返回 `user@host:port` 形式的字符串，IPv6 地址加方括号，如 `user@[::1]:22`
+    pub fn jump_target(&self) -> String {
+        let address = if self.address.contains(':') {
+            format!("[{}]", self.address)
+        } else {
+            self.address.clone()
+        };
+        let mut target = match &self.user {
+            Some(user) => format!("{user}@{address}"),
+            None => address,
+        };
+        if let Some(port) = self.port {
+            target.push_str(&format!(":{port}"));
+        }
+        target
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigFile {
     #[serde(default)]
@@ -32,10 +477,42 @@ pub struct ConfigFile {
     pub servers: Vec<SSHConfig>,
 }
 
+/// 配置文件读写错误
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("无法读取配置文件 {}: {source}", .path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("配置文件 {} 第 {line} 行第 {column} 列解析失败: {message}", .path.display())]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("无法写入配置文件 {}: {source}", .path.display())]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("无法序列化配置: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("配置文件 {} 解析失败，当前为只读模式，修改不会被保存", .path.display())]
+    ReadOnly { path: PathBuf },
+}
+
 #[derive(Debug, Clone)]
 pub struct ConfigManager {
     pub configs: Vec<SSHConfig>,
     pub global_config: GlobalConfig,
+    config_path: PathBuf,
+    read_only: bool,
+    /// 上次检查时配置文件的修改时间，用于发现外部修改
+    modified: Option<SystemTime>,
 }
 
 impl ConfigManager {
@@ -43,182 +520,849 @@ impl ConfigManager {
     ///
     /// # 返回
     /// 返回配置目录路径，如果无法获取则返回 None
-    fn get_config_dir() -> Option<PathBuf> {
+    pub fn get_config_dir() -> Option<PathBuf> {
         dirs::config_dir().map(|config| config.join("mssh"))
     }
 
-    /// 创建新的配置管理器
+    /// 解析配置文件路径
     ///
     /// # 参数
-    /// - `config_path`: 配置文件路径，可选
+    /// - `config_path`: 命令行指定的配置文件路径，可选
     ///
     /// # 返回
-    /// 返回 Result，成功为 ConfigManager 实例，失败为 Err
-    pub fn new(config_path: Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_path = config_path.map(PathBuf::from).unwrap_or_else(|| {
-            if let Some(config_dir) = Self::get_config_dir() {
-                config_dir.join("config.toml")
-            } else {
-                PathBuf::from("config.toml")
+    /// 返回实际使用的配置文件路径，未指定时为默认路径
+    pub fn resolve_path(config_path: Option<String>) -> PathBuf {
+        config_path
+            .map(|path| PathBuf::from(shellexpand::tilde(&path).to_string()))
+            .unwrap_or_else(|| {
+                if let Some(config_dir) = Self::get_config_dir() {
+                    config_dir.join("config.toml")
+                } else {
+                    PathBuf::from("config.toml")
+                }
+            })
+    }
+
+    /// 从指定路径加载配置
+    ///
+    /// 文件不存在时视为空配置；文件无法读取或解析失败时返回错误，
+    /// 绝不会用空配置代替。
+    ///
+    /// # 参数
+    /// - `config_path`: 配置文件路径
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 ConfigManager 实例，失败为 ConfigError
+    pub fn load(config_path: PathBuf) -> Result<Self, ConfigError> {
+        // 在读取之前记录修改时间，读取期间发生的修改会在下次检查时发现
+        let modified = Self::modified_time(&config_path);
+        let config_content = match fs::read_to_string(&config_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(source) => {
+                return Err(ConfigError::Read {
+                    path: config_path,
+                    source,
+                })
             }
-        });
+        };
 
-        let config_content = fs::read_to_string(&config_path).unwrap_or_default();
-        let config_file: ConfigFile =
-            toml::from_str(&config_content).unwrap_or_else(|_| ConfigFile {
-                global: GlobalConfig::default(),
-                servers: Vec::new(),
-            });
+        let config_file: ConfigFile = toml::from_str(&config_content).map_err(|e| {
+            let (line, column) = e
+                .span()
+                .map(|span| Self::line_column(&config_content, span.start))
+                .unwrap_or((1, 1));
+            ConfigError::Parse {
+                path: config_path.clone(),
+                line,
+                column,
+                message: e.message().trim().to_string(),
+            }
+        })?;
 
         Ok(Self {
             configs: config_file.servers,
             global_config: config_file.global,
+            config_path,
+            read_only: false,
+            modified,
         })
     }
 
+    /// 创建只读的空配置管理器，用于配置文件解析失败后继续启动
+    ///
+    /// # 参数
+    /// - `config_path`: 解析失败的配置文件路径
+    ///
+    /// # 返回
+    /// 返回不会写入磁盘的配置管理器
+    pub fn read_only(config_path: PathBuf) -> Self {
+        Self {
+            configs: Vec::new(),
+            global_config: GlobalConfig::default(),
+            modified: Self::modified_time(&config_path),
+            config_path,
+            read_only: true,
+        }
+    }
+
+    /// 获取文件的修改时间
+    ///
+    /// # 参数
+    /// - `path`: 文件路径
+    ///
+    /// # 返回
+    /// 返回修改时间，文件不存在或无法读取时为 None
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// 检查配置文件是否在外部被修改
+    ///
+    /// 先比较修改时间，变化时再重新加载并与内存中的配置比较，
+    /// 因此自己保存引起的变化不算外部修改。文件被删除时保留内存中的配置。
+    ///
+    /// # 返回
+    /// 返回 Result，文件内容与内存中不同时为 Some(重新加载的配置管理器)，
+    /// 否则为 None；文件无法读取或解析时为 Err，同一次修改只报告一次
+    pub fn check_external_change(&mut self) -> Result<Option<ConfigManager>, ConfigError> {
+        let modified = Self::modified_time(&self.config_path);
+        if modified.is_none() || modified == self.modified {
+            return Ok(None);
+        }
+        self.modified = modified;
+
+        let loaded = Self::load(self.config_path.clone())?;
+        if !self.read_only && loaded.to_toml()? == self.to_toml()? {
+            return Ok(None);
+        }
+        Ok(Some(loaded))
+    }
+
+    /// 将配置序列化为配置文件的内容
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 TOML 文本，失败为 ConfigError
+    fn to_toml(&self) -> Result<String, ConfigError> {
+        let config_file = ConfigFile {
+            global: self.global_config.clone(),
+            servers: self.configs.clone(),
+        };
+        Ok(toml::to_string_pretty(&config_file)?)
+    }
+
+    /// 检查是否为只读模式
+    ///
+    /// # 返回
+    /// 返回 true 表示只读，修改不会被保存
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// 获取当前使用的配置文件路径
+    ///
+    /// # 返回
+    /// 返回加载配置时使用的文件路径
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// 将字节偏移量转换为行号和列号（均从 1 开始）
+    ///
+    /// # 参数
+    /// - `content`: 文件内容
+    /// - `offset`: 字节偏移量
+    ///
+    /// # 返回
+    /// 返回 (行号, 列号)
+    fn line_column(content: &str, offset: usize) -> (usize, usize) {
+        let before = &content[..offset.min(content.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .map(|s| s.chars().count())
+            .unwrap_or(0)
+            + 1;
+        (line, column)
+    }
+
     /// 保存配置到文件
     ///
+    /// 写回加载时使用的配置文件，先写入临时文件再原子替换，
+    /// 并在覆盖前为旧文件保留带时间戳的备份。只读模式下拒绝写入。
+    ///
     /// # 返回
-    /// 返回 Result，成功为 Ok(())，失败为 Err
-    pub fn save_configs(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let config_path = if let Some(config_dir) = Self::get_config_dir() {
-            config_dir.join("config.toml")
-        } else {
-            PathBuf::from("config.toml")
+    /// 返回 Result，成功为 Ok(())，失败为 ConfigError
+    pub fn save_configs(&self) -> Result<(), ConfigError> {
+        self.write_configs(true)
+    }
+
+    /// 写入配置文件
+    ///
+    /// # 参数
+    /// - `backup`: 是否在覆盖前为旧文件创建备份
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 ConfigError
+    fn write_configs(&self, backup: bool) -> Result<(), ConfigError> {
+        if self.read_only {
+            return Err(ConfigError::ReadOnly {
+                path: self.config_path.clone(),
+            });
+        }
+
+        // 配置文件是符号链接时写入实际文件，避免把链接替换成普通文件
+        let config_path =
+            fs::canonicalize(&self.config_path).unwrap_or_else(|_| self.config_path.clone());
+
+        let toml_string = self.to_toml()?;
+
+        let write_error = |source| ConfigError::Write {
+            path: config_path.clone(),
+            source,
         };
 
         // 确保配置目录存在
         if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(write_error)?;
+            }
         }
 
-        let config_file = ConfigFile {
-            global: self.global_config.clone(),
-            servers: self.configs.clone(),
+        if backup {
+            Self::backup_config(&config_path, &toml_string).map_err(write_error)?;
+        }
+        Self::write_atomic(&config_path, &toml_string).map_err(write_error)?;
+
+        Ok(())
+    }
+
+    /// 为即将被覆盖的配置文件创建备份，并清理过旧的备份
+    ///
+    /// 备份文件命名为 `<文件名>.bak.<毫秒时间戳>`，最多保留 `MAX_BACKUPS` 个。
+    ///
+    /// # 参数
+    /// - `config_path`: 配置文件路径
+    /// - `new_content`: 即将写入的新内容，与旧内容相同时不创建备份
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    fn backup_config(config_path: &Path, new_content: &str) -> std::io::Result<()> {
+        let old_content = match fs::read_to_string(config_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
         };
 
-        let toml_string = toml::to_string_pretty(&config_file)?;
-        fs::write(&config_path, toml_string)?;
+        if old_content == new_content {
+            return Ok(());
+        }
+
+        let file_name = config_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "config.toml".to_string());
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let backup_path = config_path.with_file_name(format!("{file_name}.bak.{timestamp}"));
+        Self::write_private(&backup_path, &old_content)?;
+        // 备份与配置文件一样可能包含代理密码，权限与配置文件保持一致
+        if let Ok(metadata) = fs::metadata(config_path) {
+            fs::set_permissions(&backup_path, metadata.permissions())?;
+        }
+
+        // 删除超出保留数量的旧备份
+        for path in Self::backup_paths(config_path)?
+            .into_iter()
+            .skip(MAX_BACKUPS)
+        {
+            let _ = fs::remove_file(path);
+        }
+
+        Ok(())
+    }
+
+    /// 列出配置文件的备份
+    ///
+    /// # 参数
+    /// - `config_path`: 配置文件路径
+    ///
+    /// # 返回
+    /// 返回 Result，成功为按时间戳从新到旧排列的备份路径，失败为 Err
+    fn backup_paths(config_path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let file_name = config_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "config.toml".to_string());
+        let prefix = format!("{file_name}.bak.");
+        let dir = config_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let mut backups: Vec<(u128, PathBuf)> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let stamp = name.strip_prefix(&prefix)?.parse::<u128>().ok()?;
+                Some((stamp, entry.path()))
+            })
+            .collect();
+        backups.sort_by_key(|(stamp, _)| std::cmp::Reverse(*stamp));
+        Ok(backups.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// 清除备份中的明文代理密码
+    ///
+    /// 已移入密码库的密码替换为对应的引用，其他明文密码直接删除；
+    /// 无法解析的备份可能同样含有明文密码，整个删除。
+    ///
+    /// # 参数
+    /// - `sealed`: 已移入密码库的明文密码及其引用
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    fn scrub_backups(&self, sealed: &[(String, String)]) -> std::io::Result<()> {
+        let config_path =
+            fs::canonicalize(&self.config_path).unwrap_or_else(|_| self.config_path.clone());
+        for path in Self::backup_paths(&config_path)? {
+            let content = fs::read_to_string(&path)?;
+            let Ok(mut backup) = toml::from_str::<ConfigFile>(&content) else {
+                fs::remove_file(&path)?;
+                continue;
+            };
 
+            let mut changed = false;
+            let proxies = std::iter::once(&mut backup.global.proxy)
+                .chain(backup.servers.iter_mut().filter_map(|c| c.proxy.as_mut()));
+            for proxy in proxies {
+                let Some(password) = proxy.password.as_deref().filter(|p| vault::is_plaintext(p))
+                else {
+                    continue;
+                };
+                proxy.password = sealed
+                    .iter()
+                    .find(|(plaintext, _)| plaintext == password)
+                    .map(|(_, reference)| reference.clone());
+                changed = true;
+            }
+            if changed {
+                let content = toml::to_string_pretty(&backup)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                Self::write_private(&path, &content)?;
+            }
+        }
         Ok(())
     }
 
+    /// 创建只有所有者可以读写的文件并写入内容，文件已存在时覆盖
+    ///
+    /// # 参数
+    /// - `path`: 文件路径
+    /// - `content`: 要写入的内容
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    }
+
+    /// 原子写入文件：先写入同目录下的临时文件，再重命名覆盖目标文件
+    ///
+    /// 目标文件已存在时保留其权限，否则新文件只有所有者可以读写。
+    ///
+    /// # 参数
+    /// - `path`: 目标文件路径
+    /// - `content`: 要写入的内容
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    pub fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "config.toml".to_string());
+        let tmp_path = path.with_file_name(format!(".{file_name}.tmp.{}", std::process::id()));
+
+        let result = (|| {
+            Self::write_private(&tmp_path, content)?;
+
+            // 保留原文件的权限
+            if let Ok(metadata) = fs::metadata(path) {
+                fs::set_permissions(&tmp_path, metadata.permissions())?;
+            }
+
+            fs::rename(&tmp_path, path)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+
+        result
+    }
+
     /// 添加新配置
     ///
     /// # 参数
     /// - `config`: 要添加的 SSH 配置
     ///
     /// # 返回
-    /// 返回 Result，成功为 Ok(())，失败为 Err
+    /// 返回 Result，成功为 Ok(())，保存失败时撤销添加并返回 Err
     pub fn add_config(&mut self, config: SSHConfig) -> Result<(), Box<dyn std::error::Error>> {
-        // 检查是否已存在
-        if self.configs.iter().any(|c| c.alias == config.alias) {
-            return Err("主机别名已存在".into());
+        self.add_configs(vec![config])
+    }
+
+    /// 添加一批配置
+    ///
+    /// 整批配置全部加入后再校验跳板机链，因此可以引用同一批中排在后面的跳板机；
+    /// 任一配置无效或保存失败时不添加任何配置。
+    ///
+    /// # 参数
+    /// - `configs`: 要添加的 SSH 配置列表
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败时撤销添加并返回 Err
+    pub fn add_configs(
+        &mut self,
+        configs: Vec<SSHConfig>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let count = self.configs.len();
+        let result = self.push_configs(configs, count);
+        if result.is_err() {
+            self.configs.truncate(count);
         }
+        result
+    }
+
+    /// 导入一批配置
+    ///
+    /// ProxyJump 引用的跳板机不在 mssh 中时，从候选配置中一并导入，
+    /// 或按 `user@host:port` 新建跳板机配置；整批校验通过后才会保存。
+    ///
+    /// # 参数
+    /// - `configs`: 要导入的 SSH 配置列表
+    /// - `candidates`: 可一并导入的候选配置
+    ///
+    /// # 返回
+    /// 返回 Result，成功为实际导入的主机别名列表，失败时不导入任何配置并返回 Err
+    pub fn import_configs(
+        &mut self,
+        configs: Vec<SSHConfig>,
+        candidates: &[SSHConfig],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let count = self.configs.len();
+        let mut all = self.configs.clone();
+        all.extend(configs);
+        ssh_config::add_jump_hosts(&mut all, count, candidates);
+
+        let imported = all.split_off(count);
+        let aliases = imported.iter().map(|c| c.alias.clone()).collect();
+        self.add_configs(imported)?;
+        Ok(aliases)
+    }
 
-        self.configs.push(config);
+    /// 追加配置并校验、保存，供 `add_configs` 在失败时统一撤销
+    ///
+    /// # 参数
+    /// - `configs`: 要添加的 SSH 配置列表
+    /// - `count`: 添加前的配置数量
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    fn push_configs(
+        &mut self,
+        configs: Vec<SSHConfig>,
+        count: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for config in configs {
+            if self.configs.iter().any(|c| c.alias == config.alias) {
+                return Err(format!("主机别名已存在: {}", config.alias).into());
+            }
+            self.configs.push(config);
+        }
+        for config in &self.configs[count..] {
+            self.resolve_jump_chain(config)
+                .map_err(|e| format!("{}: {e}", config.alias))?;
+        }
         self.save_configs()?;
         Ok(())
     }
 
     /// 更新配置
     ///
+    /// 修改别名时，其他主机跳板机链中对该主机的引用一并改为新的别名。
+    ///
     /// # 参数
     /// - `host`: 主机别名
     /// - `config`: 新的配置
     ///
     /// # 返回
-    /// 返回 Result，成功为 Ok(())，失败为 Err
+    /// 返回 Result，成功为 Ok(())，保存失败时恢复原配置并返回 Err
     pub fn update_config(
         &mut self,
         host: &str,
         config: SSHConfig,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(index) = self.configs.iter().position(|c| c.alias == host) {
-            self.configs[index] = config;
-            self.save_configs()?;
-            Ok(())
-        } else {
-            Err("配置不存在".into())
+        let Some(index) = self.configs.iter().position(|c| c.alias == host) else {
+            return Err("配置不存在".into());
+        };
+        if config.alias != host && self.configs.iter().any(|c| c.alias == config.alias) {
+            return Err("主机别名已存在".into());
         }
+
+        let previous = self.configs.clone();
+        let alias = config.alias.clone();
+        self.configs[index] = config;
+        if alias != host {
+            for proxy in self.configs.iter_mut().filter_map(|c| c.proxy.as_mut()) {
+                for jump in proxy.jump_hosts.iter_mut().filter(|jump| *jump == host) {
+                    jump.clone_from(&alias);
+                }
+            }
+        }
+
+        if let Err(e) = self.resolve_jump_chain(&self.configs[index]) {
+            self.configs = previous;
+            return Err(e.into());
+        }
+        if let Err(e) = self.save_configs() {
+            self.configs = previous;
+            return Err(e.into());
+        }
+        Ok(())
     }
 
-    /// 删除配置
+    /// 获取跳板机链中引用了指定主机的其他主机
     ///
     /// # 参数
-    /// - `host`: 主机别名
+    /// - `alias`: 主机别名
     ///
     /// # 返回
-    /// 返回 Result，成功为 Ok(())，失败为 Err
-    pub fn remove_config(&mut self, host: &str) -> Result<(), Box<dyn std::error::Error>> {
-        self.configs.retain(|c| c.alias != host);
-        self.save_configs()?;
+    /// 返回引用该主机作为跳板机的主机别名列表
+    pub fn jump_dependents(&self, alias: &str) -> Vec<&str> {
+        self.configs
+            .iter()
+            .filter(|c| c.alias != alias && c.jump_hosts().iter().any(|jump| jump == alias))
+            .map(|c| c.alias.as_str())
+            .collect()
+    }
+
+    /// 检查所有主机的跳板机链都能展开
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，有主机引用不存在的跳板机或存在循环时为 Err
+    pub fn check_jump_chains(&self) -> Result<(), String> {
+        for config in &self.configs {
+            self.resolve_jump_chain(config)
+                .map_err(|e| format!("{}: {e}", config.alias))?;
+        }
         Ok(())
     }
 
-    /// 解析 SSH 配置文件中的 Host 配置
+    /// 交换两个配置在配置文件中的位置
     ///
     /// # 参数
-    /// - `content`: SSH 配置文件内容
-    /// - `host_line`: Host 行内容
+    /// - `a`: 配置下标
+    /// - `b`: 配置下标
     ///
     /// # 返回
-    /// 返回解析后的 SSH 配置，如果解析失败则返回 None
-    pub fn parse_ssh_host_config(&self, content: &str, host_line: &str) -> Option<SSHConfig> {
-        let host_name = host_line.split_whitespace().nth(1)?;
-        let mut config = SSHConfig {
-            alias: host_name.to_string(),
-            address: host_name.to_string(),
-            port: None,
-            user: None,
-            key: None,
-            port_forward: None,
-            proxy: None,
-            use_global_proxy: false,
-        };
+    /// 返回 Result，成功为 Ok(())，保存失败时恢复原顺序并返回 Err
+    pub fn swap_configs(&mut self, a: usize, b: usize) -> Result<(), ConfigError> {
+        self.configs.swap(a, b);
+        if let Err(e) = self.save_configs() {
+            self.configs.swap(a, b);
+            return Err(e);
+        }
+        Ok(())
+    }
 
-        // 查找该 Host 下的配置
-        let lines: Vec<&str> = content.lines().collect();
-        let mut in_host_block = false;
+    /// 获取全局代理和各主机的自定义代理
+    ///
+    /// # 返回
+    /// 返回 (主机别名, 代理) 列表，全局代理的别名为 None
+    pub fn proxies(&self) -> Vec<(Option<&str>, &ProxyConfig)> {
+        std::iter::once((None, &self.global_config.proxy))
+            .chain(self.configs.iter().filter_map(|config| {
+                config
+                    .proxy
+                    .as_ref()
+                    .map(|proxy| (Some(config.alias.as_str()), proxy))
+            }))
+            .collect()
+    }
 
-        for line in lines {
-            let line = line.trim();
+    /// 统计配置文件中以明文保存的代理密码
+    ///
+    /// # 返回
+    /// 返回明文密码的数量
+    pub fn plaintext_password_count(&self) -> usize {
+        self.proxies()
+            .iter()
+            .filter(|(_, proxy)| proxy.password.as_deref().is_some_and(vault::is_plaintext))
+            .count()
+    }
 
-            if line.starts_with("Host ") {
-                in_host_block = line == host_line;
-                continue;
+    /// 检查是否有代理密码引用了密码库
+    ///
+    /// # 返回
+    /// 返回 true 表示需要解锁密码库才能使用这些代理
+    pub fn uses_vault(&self) -> bool {
+        self.proxies().iter().any(|(_, proxy)| {
+            proxy
+                .password
+                .as_deref()
+                .and_then(vault::secret_id)
+                .is_some()
+        })
+    }
+
+    /// 检查是否有代理密码引用了指定的密码库条目
+    ///
+    /// # 参数
+    /// - `id`: 条目名称
+    ///
+    /// # 返回
+    /// 返回 true 表示全局代理或某个主机仍在使用该条目
+    pub fn references_secret(&self, id: &str) -> bool {
+        self.proxies()
+            .iter()
+            .any(|(_, proxy)| proxy.password.as_deref().and_then(vault::secret_id) == Some(id))
+    }
+
+    /// 将明文代理密码移入密码库，配置中替换为引用后保存
+    ///
+    /// 先写入密码库再保存配置，中途失败时配置中仍保留明文密码，不会丢失。
+    /// 保存时不为含有明文密码的旧配置创建备份，并清除已有备份中的明文密码。
+    ///
+    /// # 参数
+    /// - `vault`: 已解锁的密码库
+    ///
+    /// # 返回
+    /// 返回 Result，成功为移入的密码数量，失败为 Err
+    pub fn migrate_passwords(
+        &mut self,
+        vault: &mut Vault,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut migrated = self.clone();
+        let mut sealed = Vec::new();
+        let mut seal = |password: &mut Option<String>, id: String| {
+            let plaintext = password.clone();
+            if vault.seal(password, &id)? {
+                sealed.push((plaintext.unwrap_or_default(), vault::reference(&id)));
+            }
+            Ok::<_, VaultError>(())
+        };
+        seal(
+            &mut migrated.global_config.proxy.password,
+            vault::proxy_secret_id(None),
+        )?;
+        for config in &mut migrated.configs {
+            if let Some(proxy) = &mut config.proxy {
+                seal(
+                    &mut proxy.password,
+                    vault::proxy_secret_id(Some(&config.alias)),
+                )?;
             }
+        }
+        if sealed.is_empty() {
+            return Ok(0);
+        }
+        migrated.write_configs(false)?;
+        *self = migrated;
+        self.scrub_backups(&sealed)
+            .map_err(|e| format!("密码已移入密码库，但无法清除配置备份中的明文密码: {e}"))?;
+        Ok(sealed.len())
+    }
 
-            if !in_host_block {
-                continue;
+    /// 按编号或别名查找配置
+    ///
+    /// # 参数
+    /// - `target`: 从 1 开始的编号或主机别名
+    ///
+    /// # 返回
+    /// 返回配置的下标，找不到时为 None
+    pub fn find_index(&self, target: &str) -> Option<usize> {
+        if let Ok(index) = target.parse::<usize>() {
+            if index > 0 && index <= self.configs.len() {
+                return Some(index - 1);
             }
+        }
+        self.configs.iter().position(|c| c.alias == target)
+    }
 
-            // 解析各种配置项
-            if line.starts_with("HostName ") {
-                config.address = line.split_whitespace().nth(1)?.to_string();
-            } else if line.starts_with("Port ") {
-                if let Ok(port) = line.split_whitespace().nth(1)?.parse::<u16>() {
-                    config.port = Some(port);
+    /// 获取所有已使用的分组路径
+    ///
+    /// # 返回
+    /// 返回分组路径列表（包括上级分组），按首次出现的顺序排列
+    pub fn groups(&self) -> Vec<String> {
+        let mut groups: Vec<String> = Vec::new();
+        for group in self.configs.iter().filter_map(|c| c.group.as_deref()) {
+            let mut end = 0;
+            for part in group.split('/') {
+                end += part.len();
+                let path = &group[..end];
+                if !groups.iter().any(|g| g == path) {
+                    groups.push(path.to_string());
                 }
-            } else if line.starts_with("User ") {
-                config.user = Some(line.split_whitespace().nth(1)?.to_string());
-            } else if line.starts_with("IdentityFile ") {
-                config.key = Some(line.split_whitespace().nth(1)?.to_string());
-            } else if line.starts_with("LocalForward ") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 3 {
-                    let local = parts[1].to_string();
-                    let remote = parts[2].to_string();
-                    config.port_forward = Some(PortForward {
-                        enabled: true,
-                        local,
-                        remote,
-                    });
+                end += 1;
+            }
+        }
+        groups
+    }
+
+    /// 按编号、别名或关键字匹配快速连接的目标
+    ///
+    /// 编号和别名精确匹配；都不匹配时在别名、地址和用户名中查找，只有唯一匹配时才返回。
+    ///
+    /// # 参数
+    /// - `target`: 编号、别名或关键字
+    ///
+    /// # 返回
+    /// 返回 Result，成功为配置的下标，没有匹配或匹配多个时为包含错误信息的 Err
+    pub fn match_target(&self, target: &str) -> Result<usize, String> {
+        if let Ok(index) = target.parse::<usize>() {
+            if index == 0 || index > self.configs.len() {
+                return Err(format!(
+                    "编号 {} 超出范围 (1-{})",
+                    index,
+                    self.configs.len()
+                ));
+            }
+        }
+        if let Some(index) = self.find_index(target) {
+            return Ok(index);
+        }
+
+        let matches: Vec<usize> = self
+            .configs
+            .iter()
+            .enumerate()
+            .filter(|(_, config)| {
+                config.alias.contains(target)
+                    || config.address.contains(target)
+                    || config.user.as_ref().is_some_and(|u| u.contains(target))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        match matches.as_slice() {
+            [] => Err(format!("未找到匹配的配置: {target}")),
+            [index] => Ok(*index),
+            _ => {
+                let mut message = "找到多个匹配的配置:".to_string();
+                for &i in &matches {
+                    let config = &self.configs[i];
+                    message.push_str(&format!(
+                        "\n  {}: {} ({})",
+                        i + 1,
+                        config.alias,
+                        config.address
+                    ));
                 }
+                message.push_str("\n请使用更具体的编号或别名");
+                Err(message)
             }
         }
+    }
+
+    /// 展开配置的跳板机链
+    ///
+    /// 跳板机自身也经由跳板机连接时递归展开，按连接顺序返回完整的链，
+    /// 重复出现的跳板机只保留第一次。
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    ///
+    /// # 返回
+    /// 返回 Result，成功为按连接顺序排列的跳板机配置，引用不存在的别名或存在循环时为 Err
+    pub fn resolve_jump_chain(&self, config: &SSHConfig) -> Result<Vec<SSHConfig>, String> {
+        let mut chain = Vec::new();
+        let mut path = vec![config.alias.clone()];
+        for alias in config.jump_hosts() {
+            self.visit_jump_host(alias, &mut path, &mut chain)?;
+        }
+        Ok(chain)
+    }
+
+    /// 深度优先展开单个跳板机
+    ///
+    /// # 参数
+    /// - `alias`: 跳板机别名
+    /// - `path`: 当前展开路径，用于检测循环
+    /// - `chain`: 已展开的跳板机配置
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为错误信息
+    fn visit_jump_host(
+        &self,
+        alias: &str,
+        path: &mut Vec<String>,
+        chain: &mut Vec<SSHConfig>,
+    ) -> Result<(), String> {
+        if path.iter().any(|visited| visited == alias) {
+            return Err(format!(
+                "跳板机链存在循环: {} -> {alias}",
+                path.join(" -> ")
+            ));
+        }
+        let host = self
+            .configs
+            .iter()
+            .find(|c| c.alias == alias)
+            .ok_or_else(|| format!("跳板机 {alias} 不存在"))?;
+
+        path.push(alias.to_string());
+        for next in host.jump_hosts() {
+            self.visit_jump_host(next, path, chain)?;
+        }
+        path.pop();
+
+        if !chain.iter().any(|added| added.alias == alias) {
+            chain.push(host.clone());
+        }
+        Ok(())
+    }
 
-        Some(config)
+    /// 删除配置
+    ///
+    /// # 参数
+    /// - `host`: 主机别名
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，主机仍是其他主机的跳板机时为 Err，
+    /// 保存失败时恢复删除的配置并返回 Err
+    pub fn remove_config(&mut self, host: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(index) = self.configs.iter().position(|c| c.alias == host) else {
+            return Ok(());
+        };
+        let dependents = self.jump_dependents(host);
+        if !dependents.is_empty() {
+            return Err(format!(
+                "{host} 是 {} 的跳板机，请先修改这些主机的跳板机",
+                dependents.join(", ")
+            )
+            .into());
+        }
+        let removed = self.configs.remove(index);
+        if let Err(e) = self.save_configs() {
+            self.configs.insert(index, removed);
+            return Err(e.into());
+        }
+        Ok(())
     }
 }
 
@@ -262,44 +1406,395 @@ impl TryFrom<toml::Value> for SSHConfig {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        let port_forward = if let Some(pf_value) = table.get("port_forward") {
-            if let Some(pf_table) = pf_value.as_table() {
-                let enabled = pf_table
-                    .get("enabled")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
-                let local = pf_table
-                    .get("local")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let remote = pf_table
-                    .get("remote")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-
-                Some(PortForward {
-                    enabled,
-                    local,
-                    remote,
-                })
-            } else {
-                None
-            }
-        } else {
-            None
+        let port_forwards = match table
+            .get("port_forwards")
+            .or_else(|| table.get("port_forward"))
+        {
+            Some(toml::Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| item.clone().try_into().ok())
+                .collect(),
+            Some(value) => value.clone().try_into().ok().into_iter().collect(),
+            None => Vec::new(),
+        };
+
+        let group = table
+            .get("group")
+            .and_then(|v| v.as_str())
+            .and_then(normalize_group);
+
+        let tags = match table.get("tags") {
+            Some(toml::Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .flat_map(parse_tags)
+                .collect(),
+            Some(toml::Value::String(tags)) => parse_tags(tags),
+            _ => Vec::new(),
         };
 
+        let tunnel_only = table
+            .get("tunnel_only")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         Ok(SSHConfig {
             alias,
             address,
             port,
             user,
             key,
-            port_forward,
+            group,
+            tags,
+            port_forwards,
+            tunnel_only,
             proxy: None,
             use_global_proxy: false,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    /// 在临时目录中创建空的配置管理器，目录在返回的 TempDir 销毁时删除
+    fn manager(name: &str) -> (TempDir, ConfigManager) {
+        let dir = TempDir::new(name);
+        let manager = ConfigManager::load(dir.join("config.toml")).unwrap();
+        (dir, manager)
+    }
+
+    /// 列出目录中的备份文件名
+    fn backups(dir: &TempDir) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("config.toml.bak."))
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn host(alias: &str, jump_hosts: &[&str]) -> SSHConfig {
+        SSHConfig {
+            alias: alias.to_string(),
+            address: format!("{alias}.example.com"),
+            port: None,
+            user: None,
+            key: None,
+            group: None,
+            tags: Vec::new(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy: (!jump_hosts.is_empty()).then(|| ProxyConfig {
+                proxy_type: ProxyType::Jump,
+                jump_hosts: jump_hosts.iter().map(|h| h.to_string()).collect(),
+                ..ProxyConfig::default()
+            }),
+            use_global_proxy: false,
+        }
+    }
+
+    #[test]
+    fn batch_may_reference_jump_hosts_added_later() {
+        let (_dir, mut manager) = manager("batch");
+        manager
+            .add_configs(vec![host("app", &["gateway"]), host("gateway", &[])])
+            .unwrap();
+        assert_eq!(manager.configs.len(), 2);
+    }
+
+    #[test]
+    fn invalid_batch_adds_nothing() {
+        let (_dir, mut manager) = manager("invalid-batch");
+        let err = manager
+            .add_configs(vec![host("gateway", &[]), host("app", &["missing"])])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "app: 跳板机 missing 不存在");
+        assert!(manager.configs.is_empty());
+        assert!(!manager.config_path().exists());
+    }
+
+    #[test]
+    fn import_converts_raw_jump_targets() {
+        let (_dir, mut manager) = manager("import");
+        manager.add_config(host("gateway", &[])).unwrap();
+        let imported = manager
+            .import_configs(
+                vec![host("app", &["gateway.example.com", "ops@10.0.0.1:2222"])],
+                &[],
+            )
+            .unwrap();
+        assert_eq!(imported, ["app", "ops@10.0.0.1:2222"]);
+        assert_eq!(
+            manager.configs[1].jump_hosts(),
+            ["gateway", "ops@10.0.0.1:2222"]
+        );
+    }
+
+    #[test]
+    fn remove_rejects_hosts_used_as_jump_hosts() {
+        let (_dir, mut manager) = manager("remove");
+        manager
+            .add_configs(vec![host("gateway", &[]), host("app", &["gateway"])])
+            .unwrap();
+        let err = manager.remove_config("gateway").unwrap_err();
+        assert!(err.to_string().contains("app"));
+        assert_eq!(manager.configs.len(), 2);
+
+        manager.remove_config("app").unwrap();
+        manager.remove_config("gateway").unwrap();
+        assert!(manager.configs.is_empty());
+    }
+
+    #[test]
+    fn rename_rewrites_jump_host_references() {
+        let (_dir, mut manager) = manager("rename");
+        manager
+            .add_configs(vec![host("gateway", &[]), host("app", &["gateway"])])
+            .unwrap();
+        manager
+            .update_config("gateway", host("bastion", &[]))
+            .unwrap();
+        assert_eq!(manager.configs[1].jump_hosts(), ["bastion"]);
+    }
+
+    #[test]
+    fn failed_save_restores_configs() {
+        let mut manager = ConfigManager::read_only(PathBuf::from("/nonexistent/config.toml"));
+        manager.configs = vec![host("gateway", &[]), host("app", &["gateway"])];
+
+        assert!(manager.add_config(host("web", &[])).is_err());
+        assert_eq!(manager.configs.len(), 2);
+
+        assert!(manager
+            .update_config("gateway", host("bastion", &[]))
+            .is_err());
+        assert_eq!(manager.configs[0].alias, "gateway");
+        assert_eq!(manager.configs[1].jump_hosts(), ["gateway"]);
+    }
+
+    #[test]
+    fn saves_back_to_custom_path() {
+        let dir = TempDir::new("custom-path");
+        let path = dir.join("nested/custom.toml");
+        let mut manager = ConfigManager::load(path.clone()).unwrap();
+        manager.add_config(host("web", &[])).unwrap();
+
+        let reloaded = ConfigManager::load(path.clone()).unwrap();
+        assert_eq!(reloaded.configs[0].alias, "web");
+        // 临时文件已经重命名为目标文件，目录中没有残留
+        let names: Vec<_> = fs::read_dir(dir.join("nested"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["custom.toml"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_write_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("atomic");
+        let existing = dir.join("existing.toml");
+        fs::write(&existing, "old").unwrap();
+        fs::set_permissions(&existing, fs::Permissions::from_mode(0o640)).unwrap();
+        ConfigManager::write_atomic(&existing, "new").unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "new");
+        let mode = fs::metadata(&existing).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+
+        let created = dir.join("created.toml");
+        ConfigManager::write_atomic(&created, "new").unwrap();
+        let mode = fs::metadata(&created).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn rotates_backups() {
+        let (dir, mut manager) = manager("backups");
+        for i in 0..MAX_BACKUPS + 3 {
+            manager.add_config(host(&format!("host{i}"), &[])).unwrap();
+            // 备份以毫秒时间戳命名
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let names = backups(&dir);
+        assert_eq!(names.len(), MAX_BACKUPS);
+        // 最新的备份是最后一次保存之前的配置
+        let latest = fs::read_to_string(dir.join(names.last().unwrap())).unwrap();
+        assert!(latest.contains(&format!("host{}", MAX_BACKUPS + 1)));
+        assert!(!latest.contains(&format!("host{}", MAX_BACKUPS + 2)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backups_are_not_readable_by_others() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dir, mut manager) = manager("backup-mode");
+        manager.add_config(host("web", &[])).unwrap();
+        fs::set_permissions(manager.config_path(), fs::Permissions::from_mode(0o600)).unwrap();
+        manager.add_config(host("db", &[])).unwrap();
+
+        let names = backups(&dir);
+        assert_eq!(names.len(), 1);
+        let mode = fs::metadata(dir.join(&names[0]))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn finds_hosts_referencing_secret() {
+        let (_dir, mut manager) = manager("references");
+        let id = vault::proxy_secret_id(Some("web"));
+        let mut web = host("web", &[]);
+        web.proxy = Some(ProxyConfig {
+            proxy_type: ProxyType::Socks5,
+            password: Some(vault::reference(&id)),
+            ..ProxyConfig::default()
+        });
+        manager.add_config(web).unwrap();
+        assert!(manager.references_secret(&id));
+
+        manager.remove_config("web").unwrap();
+        assert!(!manager.references_secret(&id));
+    }
+
+    #[test]
+    fn migration_leaves_no_plaintext_in_backups() {
+        let (dir, mut manager) = manager("migrate");
+        let mut web = host("web", &[]);
+        web.proxy = Some(ProxyConfig {
+            proxy_type: ProxyType::Socks5,
+            password: Some("web-secret".to_string()),
+            ..ProxyConfig::default()
+        });
+        manager.add_config(web).unwrap();
+        manager.global_config.proxy.password = Some("global-secret".to_string());
+        manager.save_configs().unwrap();
+        // 备份按毫秒时间戳命名，间隔保存避免同名
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        manager.add_config(host("db", &[])).unwrap();
+        // 较早的备份中还有一个已不在配置中的旧密码
+        let names = backups(&dir);
+        fs::write(
+            dir.join(&names[0]),
+            "[[servers]]\nalias = \"old\"\naddress = \"old\"\n[servers.proxy]\nproxy_type = \"Socks5\"\npassword = \"old-secret\"\n",
+        )
+        .unwrap();
+
+        let mut vault = Vault::new(manager.config_path());
+        vault.create("master").unwrap();
+        assert_eq!(manager.migrate_passwords(&mut vault).unwrap(), 2);
+
+        // 迁移时不创建新的备份
+        assert_eq!(backups(&dir), names);
+        let files = std::iter::once(manager.config_path().to_path_buf())
+            .chain(names.iter().map(|name| dir.join(name)));
+        for path in files {
+            let content = fs::read_to_string(&path).unwrap();
+            for secret in ["web-secret", "global-secret", "old-secret"] {
+                assert!(!content.contains(secret), "{} 中有明文密码", path.display());
+            }
+        }
+        let latest = ConfigManager::load(dir.join(&names[1])).unwrap();
+        assert_eq!(
+            latest.global_config.proxy.password,
+            manager.global_config.proxy.password
+        );
+        assert!(latest.configs[0].proxy.as_ref().unwrap().password.is_some());
+    }
+
+    #[test]
+    fn jump_target_brackets_ipv6_addresses() {
+        let mut bastion = host("bastion", &[]);
+        bastion.address = "::1".to_string();
+        bastion.user = Some("user".to_string());
+        bastion.port = Some(22);
+        assert_eq!(bastion.jump_target(), "user@[::1]:22");
+
+        bastion.user = None;
+        bastion.port = None;
+        assert_eq!(bastion.jump_target(), "[::1]");
+        assert_eq!(host("gw", &[]).jump_target(), "gw.example.com");
+    }
+
+    #[test]
+    fn jump_chain_keeps_bastion_keys() {
+        let (_dir, mut manager) = manager("jump-keys");
+        let mut bastion = host("bastion", &[]);
+        bastion.key = Some("~/.ssh/bastion".to_string());
+        manager
+            .add_configs(vec![
+                bastion,
+                host("gw", &["bastion"]),
+                host("web", &["gw"]),
+            ])
+            .unwrap();
+
+        let chain = manager.resolve_jump_chain(&manager.configs[2]).unwrap();
+        let aliases: Vec<&str> = chain.iter().map(|c| c.alias.as_str()).collect();
+        assert_eq!(aliases, ["bastion", "gw"]);
+        assert_eq!(chain[0].key.as_deref(), Some("~/.ssh/bastion"));
+    }
+
+    #[test]
+    fn loads_legacy_port_forward_table() {
+        let legacy = r#"
+[[servers]]
+alias = "web"
+address = "web.example.com"
+
+[servers.port_forward]
+enabled = true
+local = "0.0.0.0:4422"
+remote = "127.0.0.1:22"
+"#;
+        let file: ConfigFile = toml::from_str(legacy).unwrap();
+        let forwards = &file.servers[0].port_forwards;
+        assert_eq!(forwards.len(), 1);
+        assert_eq!(forwards[0].kind, ForwardKind::Local);
+        assert!(forwards[0].enabled);
+        assert_eq!(forwards[0].local, "0.0.0.0:4422");
+        assert_eq!(forwards[0].remote, "127.0.0.1:22");
+
+        // 保存时改写为 port_forwards 数组，再次读取结果不变
+        let saved = toml::to_string_pretty(&file).unwrap();
+        assert!(saved.contains("[[servers.port_forwards]]"));
+        assert!(!saved.contains("[servers.port_forward]"));
+        let reloaded: ConfigFile = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.servers[0].port_forwards.len(), 1);
+        assert_eq!(reloaded.servers[0].port_forwards[0].local, "0.0.0.0:4422");
+    }
+
+    #[test]
+    fn loads_port_forwards_array() {
+        let current = r#"
+[[servers]]
+alias = "web"
+address = "web.example.com"
+
+[[servers.port_forwards]]
+enabled = true
+local = "8080"
+remote = "127.0.0.1:80"
+
+[[servers.port_forwards]]
+kind = "Dynamic"
+enabled = false
+local = "1080"
+"#;
+        let file: ConfigFile = toml::from_str(current).unwrap();
+        let forwards = &file.servers[0].port_forwards;
+        assert_eq!(forwards.len(), 2);
+        assert_eq!(forwards[1].kind, ForwardKind::Dynamic);
+        assert!(!forwards[1].enabled);
+        assert!(forwards[1].remote.is_empty());
+    }
+}
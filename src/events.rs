@@ -1,10 +1,36 @@
+use crate::agent::AgentSnapshot;
 use crate::app::App;
+use crate::health_manager::HostStatus;
+use crate::key_manager::KeyDialog;
 use crate::navigation_manager::AppMode;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+/// 后台任务推送给主循环的事件
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    /// 一个主机检测完成
+    HostProbed { alias: String, status: HostStatus },
+    /// ssh-agent 查询完成
+    AgentRefreshed(AgentSnapshot),
+}
+
 pub struct EventHandler;
 
 impl EventHandler {
+    /// 处理后台任务推送的事件
+    ///
+    /// # 参数
+    /// - `app`: 应用状态
+    /// - `event`: 后台事件
+    pub fn handle_app_event(app: &mut App, event: AppEvent) {
+        match event {
+            AppEvent::HostProbed { alias, status } => {
+                app.health_manager.set_status(&alias, status);
+            }
+            AppEvent::AgentRefreshed(snapshot) => app.agent_manager.apply_refresh(snapshot),
+        }
+    }
+
     /// 处理键盘事件
     ///
     /// # 参数
@@ -17,6 +43,29 @@ impl EventHandler {
         app: &mut App,
         key: KeyEvent,
     ) -> Result<bool, Box<dyn std::error::Error>> {
+        if matches!(*app.mode(), AppMode::ConfigError) {
+            return Ok(Self::handle_config_error_key(app, key));
+        }
+        if matches!(*app.mode(), AppMode::ConfigConflict) {
+            Self::handle_config_conflict_key(app, key);
+            return Ok(false);
+        }
+        if matches!(*app.mode(), AppMode::VaultUnlock) {
+            Self::handle_vault_key(app, key);
+            return Ok(false);
+        }
+        if matches!(*app.mode(), AppMode::AgentAdd) {
+            Self::handle_agent_key(app, key);
+            return Ok(false);
+        }
+        if matches!(*app.mode(), AppMode::Keys) {
+            return Ok(Self::handle_keys_key(app, key));
+        }
+        if matches!(*app.mode(), AppMode::KeyPermission) {
+            Self::handle_key_permission_key(app, key);
+            return Ok(false);
+        }
+
         match key {
             KeyEvent {
                 code: KeyCode::Char('q'),
@@ -68,6 +117,128 @@ impl EventHandler {
                 }
             }
 
+            KeyEvent {
+                code: KeyCode::Char('x'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    match app.export_ssh_config() {
+                        Ok(message) => app.message_manager.set_success_message(message),
+                        Err(e) => app
+                            .message_manager
+                            .set_error_message(format!("导出失败: {e}")),
+                    }
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('s'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    match app.show_sync_diff() {
+                        Ok(0) => app
+                            .message_manager
+                            .set_success_message("mssh 与 ~/.ssh/config 已同步".to_string()),
+                        Ok(_) => {}
+                        Err(e) => app
+                            .message_manager
+                            .set_error_message(format!("比较配置失败: {e}")),
+                    }
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('t'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    match app.toggle_selected_tunnel() {
+                        Ok(message) => app.message_manager.set_success_message(message),
+                        Err(e) => app.message_manager.set_error_message(e.to_string()),
+                    }
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('g'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    app.show_move_to_group();
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('b'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    match app.connect_group() {
+                        Ok(message) => app.message_manager.set_success_message(message),
+                        Err(e) => app.message_manager.set_error_message(e.to_string()),
+                    }
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    match app.probe_hosts() {
+                        Ok(message) => app.message_manager.set_success_message(message),
+                        Err(e) => app.message_manager.set_error_message(e.to_string()),
+                    }
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('v'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    if app.ssh_manager.vault.is_unlocked() {
+                        app.message_manager
+                            .set_success_message("密码库已解锁".to_string());
+                    } else {
+                        app.show_vault_prompt();
+                    }
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('k'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    if let Err(e) = app.show_keys() {
+                        app.message_manager.set_error_message(e.to_string());
+                    }
+                }
+            }
+
+            KeyEvent {
+                code: KeyCode::Char('w'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if matches!(*app.mode(), AppMode::List) {
+                    match app.cycle_sort_mode() {
+                        Ok(message) => app.message_manager.set_success_message(message),
+                        Err(e) => app.message_manager.set_error_message(e.to_string()),
+                    }
+                }
+            }
+
             KeyEvent {
                 code: KeyCode::Char('p'),
                 modifiers: KeyModifiers::CONTROL,
@@ -85,6 +256,16 @@ impl EventHandler {
                 Self::handle_enter_key(app)?;
             }
 
+            KeyEvent {
+                code: code @ (KeyCode::Up | KeyCode::Down),
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            } if matches!(*app.mode(), AppMode::List) => {
+                if let Err(e) = app.move_selected(code == KeyCode::Up) {
+                    app.message_manager.set_error_message(e.to_string());
+                }
+            }
+
             KeyEvent {
                 code: KeyCode::Up, ..
             } => {
@@ -129,10 +310,8 @@ impl EventHandler {
                 code: KeyCode::Char(ch),
                 modifiers: KeyModifiers::NONE,
                 ..
-            } => {
-                if ch.is_ascii_graphic() || ch.is_ascii_whitespace() {
-                    Self::handle_text_input(app, ch);
-                }
+            } if ch.is_ascii_graphic() || ch.is_ascii_whitespace() => {
+                Self::handle_text_input(app, ch);
             }
 
             KeyEvent {
@@ -224,11 +403,32 @@ impl EventHandler {
         }
 
         match *app.mode() {
+            AppMode::ConfigError
+            | AppMode::ConfigConflict
+            | AppMode::VaultUnlock
+            | AppMode::AgentAdd
+            | AppMode::Keys
+            | AppMode::KeyPermission => {}
+            AppMode::List if app.selected_row().is_some_and(|r| r.host_index().is_none()) => {
+                app.toggle_selected_group();
+            }
+            AppMode::List if app.get_selected_config().is_some_and(|c| c.tunnel_only) => {
+                match app.start_selected_tunnel() {
+                    Ok(message) => app.message_manager.set_success_message(message),
+                    Err(e) => app.message_manager.set_error_message(e.to_string()),
+                }
+            }
             AppMode::List => {
-                execute_and_handle_error(app, |a| a.connect_selected(), "连接成功");
+                // 密钥未加载到 ssh-agent 时先显示添加提示，在提示中确认后连接
+                if !app.offer_agent() {
+                    execute_and_handle_error(app, |a| a.connect_selected(), "连接成功");
+                }
             }
             AppMode::AddForm | AppMode::EditForm => {
-                execute_and_handle_error(app, |a| a.save_config(), "配置保存成功");
+                // 私钥权限过宽时先显示修复提示，在提示中确认后保存
+                if !app.offer_key_permission_fix() {
+                    execute_and_handle_error(app, |a| a.save_config(), "配置保存成功");
+                }
             }
             AppMode::DeleteDialog => {
                 execute_and_handle_error(app, |a| a.delete_config(), "配置删除成功");
@@ -239,19 +439,262 @@ impl EventHandler {
             AppMode::ProxyConfig => {
                 execute_and_handle_error(app, |a| a.save_proxy_config(), "代理配置保存成功");
             }
+            AppMode::Search => app.finish_search(),
+            AppMode::MoveToGroup => match app.confirm_move_to_group() {
+                Ok(message) => app.message_manager.set_success_message(message),
+                Err(e) => app
+                    .message_manager
+                    .set_error_message(format!("移动失败: {e}")),
+            },
+            AppMode::SyncDiff => match app.confirm_sync() {
+                Ok(message) => app.message_manager.set_success_message(message),
+                Err(e) => app
+                    .message_manager
+                    .set_error_message(format!("同步失败: {e}")),
+            },
         }
 
         Ok(())
     }
+    /// 处理配置文件错误界面的按键
+    ///
+    /// # 参数
+    /// - `app`: 应用状态
+    /// - `key`: 键盘事件
+    ///
+    /// # 返回
+    /// 返回 true 表示退出，false 表示继续
+    fn handle_config_error_key(app: &mut App, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                match app.open_config_in_editor() {
+                    Ok(_) => app
+                        .message_manager
+                        .set_success_message("配置已重新加载".to_string()),
+                    Err(e) => app
+                        .message_manager
+                        .set_error_message(format!("重新加载失败: {e}")),
+                }
+                false
+            }
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                app.start_read_only();
+                false
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => true,
+            _ => false,
+        }
+    }
+
+    /// 处理配置冲突提示的按键
+    ///
+    /// # 参数
+    /// - `app`: 应用状态
+    /// - `key`: 键盘事件
+    fn handle_config_conflict_key(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                app.resolve_config_conflict(true);
+                app.message_manager
+                    .set_success_message("已放弃修改并重新加载配置".to_string());
+            }
+            KeyCode::Char('k') | KeyCode::Char('K') | KeyCode::Esc => {
+                app.resolve_config_conflict(false);
+                app.message_manager
+                    .set_success_message("已载入外部修改，保存时将基于新的配置".to_string());
+            }
+            _ => {}
+        }
+    }
+
+    /// 处理修复私钥权限提示的按键
+    ///
+    /// # 参数
+    /// - `app`: 应用状态
+    /// - `key`: 键盘事件
+    fn handle_key_permission_key(app: &mut App, key: KeyEvent) {
+        let fix = match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => true,
+            KeyCode::Char('n') | KeyCode::Char('N') => false,
+            KeyCode::Esc => {
+                app.cancel_key_permission_prompt();
+                return;
+            }
+            _ => return,
+        };
+        match app.resolve_key_permission(fix) {
+            Ok(message) => app.message_manager.set_success_message(message),
+            Err(e) => app
+                .message_manager
+                .set_error_message(format!("操作失败: {e}")),
+        }
+    }
+
+    /// 处理主密码提示的按键
+    ///
+    /// 主密码中可能有大写字母和符号，因此不经过通用的文本输入处理。
+    ///
+    /// # 参数
+    /// - `app`: 应用状态
+    /// - `key`: 键盘事件
+    fn handle_vault_key(app: &mut App, key: KeyEvent) {
+        let Some(prompt) = &mut app.vault_prompt else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => match app.submit_vault_password() {
+                Ok(message) => app.message_manager.set_success_message(message),
+                Err(e) => app.message_manager.set_error_message(e.to_string()),
+            },
+            KeyCode::Esc => {
+                app.cancel_vault_prompt();
+                app.message_manager
+                    .set_error_message("密码库未解锁，需要时按 Ctrl+V 解锁".to_string());
+            }
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.input.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// 处理添加密钥到 ssh-agent 提示的按键
+    ///
+    /// 口令中可能有大写字母和符号，因此不经过通用的文本输入处理。
+    ///
+    /// # 参数
+    /// - `app`: 应用状态
+    /// - `key`: 键盘事件
+    fn handle_agent_key(app: &mut App, key: KeyEvent) {
+        let Some(prompt) = &mut app.agent_prompt else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => match app.submit_agent_prompt() {
+                Ok(message) => app.message_manager.set_success_message(message),
+                Err(e) => app.message_manager.set_error_message(e.to_string()),
+            },
+            KeyCode::Esc => app.cancel_agent_prompt(),
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                match app.skip_agent_prompt() {
+                    Ok(()) => app
+                        .message_manager
+                        .set_success_message("连接成功".to_string()),
+                    Err(e) => app
+                        .message_manager
+                        .set_error_message(format!("操作失败: {e}")),
+                }
+            }
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                prompt.editing_passphrase = prompt.needs_passphrase && !prompt.editing_passphrase;
+            }
+            KeyCode::Backspace if prompt.editing_passphrase => {
+                prompt.passphrase.pop();
+            }
+            KeyCode::Backspace => {
+                prompt.minutes.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if prompt.editing_passphrase {
+                    prompt.passphrase.push(c);
+                } else if c.is_ascii_digit() {
+                    prompt.minutes.push(c);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 处理密钥管理界面的按键
+    ///
+    /// 生成密钥时的文件名和注释中可能有大写字母和符号，因此不经过通用的文本输入处理。
+    ///
+    /// # 参数
+    /// - `app`: 应用状态
+    /// - `key`: 键盘事件
+    ///
+    /// # 返回
+    /// 返回 true 表示退出，false 表示继续
+    fn handle_keys_key(app: &mut App, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('q') {
+            return true;
+        }
+        let host_count = app.config_manager.configs.len();
+        let key_manager = &mut app.navigation.key_manager;
+
+        if let Some(KeyDialog::Generate {
+            name,
+            comment,
+            editing_comment,
+        }) = &mut key_manager.dialog
+        {
+            let input = if *editing_comment { comment } else { name };
+            match key.code {
+                KeyCode::Enter => match app.confirm_generate_key() {
+                    Ok(message) => app.message_manager.set_success_message(message),
+                    Err(e) => app
+                        .message_manager
+                        .set_error_message(format!("生成失败: {e}")),
+                },
+                KeyCode::Esc => app.cancel_keys(),
+                KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                    *editing_comment = !*editing_comment;
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char('u') if ctrl => input.clear(),
+                KeyCode::Char(c) if !ctrl => input.push(c),
+                _ => {}
+            }
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Up => key_manager.move_selection(false, host_count),
+            KeyCode::Down => key_manager.move_selection(true, host_count),
+            KeyCode::Esc => app.cancel_keys(),
+            KeyCode::Enter if key_manager.dialog.is_some() => match app.confirm_deploy_key() {
+                Ok(message) => app.message_manager.set_success_message(message),
+                Err(e) => app
+                    .message_manager
+                    .set_error_message(format!("部署失败: {e}")),
+            },
+            KeyCode::Enter => {
+                if let Err(e) = app.show_deploy_key() {
+                    app.message_manager.set_error_message(e.to_string());
+                }
+            }
+            KeyCode::Char('n') if ctrl => {
+                if let Err(e) = app.show_generate_key() {
+                    app.message_manager.set_error_message(e.to_string());
+                }
+            }
+            KeyCode::Char('r') if ctrl => match app.rescan_keys() {
+                Ok(count) => app
+                    .message_manager
+                    .set_success_message(format!("找到 {count} 个密钥")),
+                Err(e) => app.message_manager.set_error_message(e.to_string()),
+            },
+            _ => {}
+        }
+        false
+    }
+
     /// 处理上箭头键事件
     ///
     /// # 参数
     /// - `app`: 应用状态
     fn handle_up_key(app: &mut App) {
         match *app.mode() {
-            AppMode::List => app.previous(),
+            AppMode::List | AppMode::Search => app.previous(),
             AppMode::AddForm | AppMode::EditForm => app.previous_field(),
             AppMode::SelectImport => app.import_previous(),
+            AppMode::SyncDiff => app.navigation.sync_manager.previous_item(),
             AppMode::ProxyConfig => app.previous_field(),
             _ => {}
         }
@@ -263,9 +706,10 @@ impl EventHandler {
     /// - `app`: 应用状态
     fn handle_down_key(app: &mut App) {
         match *app.mode() {
-            AppMode::List => app.next(),
+            AppMode::List | AppMode::Search => app.next(),
             AppMode::AddForm | AppMode::EditForm => app.next_field(),
             AppMode::SelectImport => app.import_next(),
+            AppMode::SyncDiff => app.navigation.sync_manager.next_item(),
             AppMode::ProxyConfig => app.next_field(),
             _ => {}
         }
@@ -278,7 +722,11 @@ impl EventHandler {
     fn handle_tab_key(app: &mut App) {
         match *app.mode() {
             AppMode::List => app.toggle_focus(),
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 4 => {
+                app.complete_key_path()
+            }
             AppMode::AddForm | AppMode::EditForm | AppMode::ProxyConfig => app.next_field(),
+            AppMode::MoveToGroup => app.cycle_group_input(),
             _ => {}
         }
     }
@@ -301,6 +749,9 @@ impl EventHandler {
     fn handle_escape_key(app: &mut App) {
         match *app.mode() {
             AppMode::SelectImport => app.cancel_import(),
+            AppMode::SyncDiff => app.cancel_sync(),
+            AppMode::Search => app.clear_search(),
+            AppMode::List if app.navigation.search_manager.is_filtering() => app.clear_search(),
             _ => app.cancel_action(),
         }
     }
@@ -311,32 +762,36 @@ impl EventHandler {
     /// - `app`: 应用状态
     fn handle_space_key(app: &mut App) {
         match *app.mode() {
+            AppMode::Search => app.search_input(' '),
+            AppMode::MoveToGroup => app.navigation.group_manager.input.push(' '),
             AppMode::SelectImport => app.toggle_import_selection(),
+            AppMode::SyncDiff => app.navigation.sync_manager.cycle_current(true),
             AppMode::AddForm | AppMode::EditForm => {
                 match app.current_field() {
-                    5 => app.toggle_checkbox(),     // 端口转发
-                    8 => app.toggle_proxy_option(), // 代理选项
+                    7 => app.toggle_checkbox(),      // 端口转发列表
+                    8 => app.cycle_forward_kind(),   // 转发类型
+                    11 => app.toggle_checkbox(),     // 仅隧道
+                    12 => app.toggle_proxy_option(), // 代理选项
+                    17 => app.push_jump_candidate(), // 跳板机链
                     _ => {}
                 }
             }
-            AppMode::ProxyConfig => {
-                if app.current_field() == 0 {
-                    // 代理类型字段
-                    // 通过 FormManager 切换代理类型
-                    let current_type = app
-                        .form_data()
-                        .get("global_proxy_type")
-                        .map(|t| match t.as_str() {
-                            "Socks5" => "Http",
-                            "Http" => "None",
-                            _ => "Socks5",
-                        })
-                        .unwrap_or("Socks5");
-                    app.form_manager
-                        .form_data
-                        .data
-                        .insert("global_proxy_type".to_string(), current_type.to_string());
-                }
+            AppMode::ProxyConfig if app.current_field() == 0 => {
+                // 代理类型字段
+                // 通过 FormManager 切换代理类型
+                let current_type = app
+                    .form_data()
+                    .get("global_proxy_type")
+                    .map(|t| match t.as_str() {
+                        "Socks5" => "Http",
+                        "Http" => "None",
+                        _ => "Socks5",
+                    })
+                    .unwrap_or("Socks5");
+                app.form_manager
+                    .form_data
+                    .data
+                    .insert("global_proxy_type".to_string(), current_type.to_string());
             }
             _ => {}
         }
@@ -349,8 +804,21 @@ impl EventHandler {
     /// - `ch`: 输入的字符
     fn handle_text_input(app: &mut App, ch: char) {
         match *app.mode() {
+            // 端口转发列表中字母键作为操作键
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 7 => match ch {
+                'a' | '+' => {
+                    if let Err(e) = app.add_draft_forward() {
+                        app.message_manager.set_error_message(e.to_string());
+                    }
+                }
+                'e' => app.edit_selected_forward(),
+                _ => {}
+            },
             AppMode::AddForm | AppMode::EditForm => app.insert_char(ch),
             AppMode::ProxyConfig => app.insert_char(ch),
+            AppMode::List if ch == '/' => app.start_search(),
+            AppMode::Search => app.search_input(ch),
+            AppMode::MoveToGroup => app.navigation.group_manager.input.push(ch),
             _ => {}
         }
     }
@@ -361,8 +829,18 @@ impl EventHandler {
     /// - `app`: 应用状态
     fn handle_backspace(app: &mut App) {
         match *app.mode() {
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 7 => {
+                app.remove_selected_forward()
+            }
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 17 => {
+                app.pop_jump_host()
+            }
             AppMode::AddForm | AppMode::EditForm => app.delete_char(),
             AppMode::ProxyConfig => app.delete_char(),
+            AppMode::Search => app.search_backspace(),
+            AppMode::MoveToGroup => {
+                app.navigation.group_manager.input.pop();
+            }
             _ => {}
         }
     }
@@ -373,6 +851,9 @@ impl EventHandler {
     /// - `app`: 应用状态
     fn handle_delete(app: &mut App) {
         match *app.mode() {
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 7 => {
+                app.remove_selected_forward()
+            }
             AppMode::AddForm | AppMode::EditForm => app.delete_char_forward(),
             AppMode::ProxyConfig => app.delete_char_forward(),
             _ => {}
@@ -385,6 +866,14 @@ impl EventHandler {
     /// - `app`: 应用状态
     fn handle_left_key(app: &mut App) {
         match *app.mode() {
+            AppMode::List => app.collapse_selected(),
+            AppMode::SyncDiff => app.navigation.sync_manager.cycle_current(false),
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 7 => {
+                app.select_forward(false)
+            }
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 17 => {
+                app.cycle_jump_candidate(false)
+            }
             AppMode::AddForm | AppMode::EditForm => app.move_cursor_left(),
             AppMode::ProxyConfig => app.move_cursor_left(),
             _ => {}
@@ -397,6 +886,14 @@ impl EventHandler {
     /// - `app`: 应用状态
     fn handle_right_key(app: &mut App) {
         match *app.mode() {
+            AppMode::List => app.expand_selected(),
+            AppMode::SyncDiff => app.navigation.sync_manager.cycle_current(true),
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 7 => {
+                app.select_forward(true)
+            }
+            AppMode::AddForm | AppMode::EditForm if app.current_field() == 17 => {
+                app.cycle_jump_candidate(true)
+            }
             AppMode::AddForm | AppMode::EditForm => app.move_cursor_right(),
             AppMode::ProxyConfig => app.move_cursor_right(),
             _ => {}
@@ -435,6 +932,7 @@ impl EventHandler {
         match *app.mode() {
             AppMode::AddForm | AppMode::EditForm => app.clear_current_field(),
             AppMode::ProxyConfig => app.clear_current_field(),
+            AppMode::MoveToGroup => app.navigation.group_manager.input.clear(),
             _ => {}
         }
     }
@@ -157,6 +157,7 @@ impl FormManager {
             port,
             username,
             password,
+            jump_hosts: Vec::new(),
         })
     }
 
@@ -230,6 +231,75 @@ impl FormManager {
         self.form_data.toggle_proxy_option();
     }
 
+    /// 切换编辑中的转发类型
+    pub fn cycle_forward_kind(&mut self) {
+        self.form_data.cycle_forward_kind();
+    }
+
+    /// 将编辑中的端口转发添加到列表
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为包含错误信息的 Err
+    pub fn add_draft_forward(&mut self) -> Result<(), String> {
+        self.form_data.add_draft_forward()
+    }
+
+    /// 将选中的端口转发移回编辑字段
+    pub fn edit_selected_forward(&mut self) {
+        self.form_data.edit_selected_forward();
+    }
+
+    /// 删除选中的端口转发
+    pub fn remove_selected_forward(&mut self) {
+        self.form_data.remove_selected_forward();
+    }
+
+    /// 在端口转发列表中移动选择
+    ///
+    /// # 参数
+    /// - `forward`: true 为向后移动，false 为向前移动
+    pub fn select_forward(&mut self, forward: bool) {
+        self.form_data.select_forward(forward);
+    }
+
+    /// 切换待添加的跳板机
+    ///
+    /// # 参数
+    /// - `candidates`: 可选的主机别名
+    /// - `forward`: true 为向后切换，false 为向前切换
+    pub fn cycle_jump_candidate(&mut self, candidates: &[String], forward: bool) {
+        if candidates.is_empty() {
+            self.form_data.data.remove("jump_candidate");
+            return;
+        }
+
+        let current = self
+            .form_data
+            .data
+            .get("jump_candidate")
+            .and_then(|alias| candidates.iter().position(|c| c == alias));
+        let next = match current {
+            Some(index) if forward => (index + 1) % candidates.len(),
+            Some(index) => (index + candidates.len() - 1) % candidates.len(),
+            None => 0,
+        };
+        self.form_data
+            .data
+            .insert("jump_candidate".to_string(), candidates[next].clone());
+    }
+
+    /// 将待添加的跳板机追加到跳板机链
+    pub fn push_jump_candidate(&mut self) {
+        if let Some(alias) = self.form_data.data.remove("jump_candidate") {
+            self.form_data.push_jump_host(&alias);
+        }
+    }
+
+    /// 移除跳板机链中的最后一个跳板机
+    pub fn pop_jump_host(&mut self) {
+        self.form_data.pop_jump_host();
+    }
+
     // 访问器方法
     /// 获取当前字段索引
     ///
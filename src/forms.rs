@@ -1,5 +1,7 @@
-use crate::config::{PortForward, SSHConfig};
+use crate::config::{normalize_group, parse_tags, ForwardKind, PortForward, SSHConfig};
+use crate::key_manager::check_private_key;
 use crate::proxy::{ProxyConfig, ProxyType};
+use crate::secret::SecretRef;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -9,14 +11,19 @@ pub enum FormField {
     Port,
     User,
     Key,
-    PortForwardEnabled,
+    Group,
+    Tags,
+    PortForwards,
+    PortForwardKind,
     PortForwardLocal,
     PortForwardRemote,
+    TunnelOnly,
     UseGlobalProxy,
     ProxyHost,
     ProxyPort,
     ProxyUsername,
     ProxyPassword,
+    JumpHosts,
     // 全局代理配置字段
     GlobalProxyType,
     GlobalProxyHost,
@@ -37,14 +44,19 @@ impl FormField {
             FormField::Port => "port",
             FormField::User => "user",
             FormField::Key => "key",
-            FormField::PortForwardEnabled => "pf_enabled",
+            FormField::Group => "group",
+            FormField::Tags => "tags",
+            FormField::PortForwards => "port_forwards",
+            FormField::PortForwardKind => "pf_kind",
             FormField::PortForwardLocal => "pf_local",
             FormField::PortForwardRemote => "pf_remote",
+            FormField::TunnelOnly => "tunnel_only",
             FormField::UseGlobalProxy => "use_global_proxy",
             FormField::ProxyHost => "proxy_host",
             FormField::ProxyPort => "proxy_port",
             FormField::ProxyUsername => "proxy_username",
             FormField::ProxyPassword => "proxy_password",
+            FormField::JumpHosts => "jump_hosts",
             FormField::GlobalProxyType => "global_proxy_type",
             FormField::GlobalProxyHost => "global_proxy_host",
             FormField::GlobalProxyPort => "global_proxy_port",
@@ -60,7 +72,12 @@ impl FormField {
     pub fn is_text_input(&self) -> bool {
         !matches!(
             self,
-            FormField::PortForwardEnabled | FormField::UseGlobalProxy | FormField::GlobalProxyType
+            FormField::PortForwards
+                | FormField::PortForwardKind
+                | FormField::TunnelOnly
+                | FormField::UseGlobalProxy
+                | FormField::JumpHosts
+                | FormField::GlobalProxyType
         )
     }
 
@@ -75,14 +92,19 @@ impl FormField {
             FormField::Port,
             FormField::User,
             FormField::Key,
-            FormField::PortForwardEnabled,
+            FormField::Group,
+            FormField::Tags,
+            FormField::PortForwards,
+            FormField::PortForwardKind,
             FormField::PortForwardLocal,
             FormField::PortForwardRemote,
+            FormField::TunnelOnly,
             FormField::UseGlobalProxy,
             FormField::ProxyHost,
             FormField::ProxyPort,
             FormField::ProxyUsername,
             FormField::ProxyPassword,
+            FormField::JumpHosts,
         ]
     }
 
@@ -106,6 +128,12 @@ pub struct FormData {
     pub data: HashMap<String, String>,
     pub current_field: usize,
     pub cursor_position: usize,
+    /// 已添加的端口转发
+    pub port_forwards: Vec<PortForward>,
+    /// 端口转发列表中选中的项
+    pub forward_index: usize,
+    /// 编辑前的私钥路径，私钥未修改时不检查文件，本机没有该私钥也可以修改其他字段
+    pub original_key: Option<String>,
 }
 
 impl FormData {
@@ -115,7 +143,8 @@ impl FormData {
     /// 返回初始化的表单数据
     pub fn new() -> Self {
         let mut data = HashMap::new();
-        data.insert("pf_enabled".to_string(), "false".to_string());
+        data.insert("pf_kind".to_string(), "Local".to_string());
+        data.insert("tunnel_only".to_string(), "false".to_string());
         data.insert("use_global_proxy".to_string(), "true".to_string());
         data.insert("proxy_enabled".to_string(), "false".to_string());
 
@@ -123,6 +152,9 @@ impl FormData {
             data,
             current_field: 0,
             cursor_position: 0,
+            port_forwards: Vec::new(),
+            forward_index: 0,
+            original_key: None,
         };
 
         // 确保字段索引在有效范围内
@@ -159,19 +191,23 @@ impl FormData {
         if let Some(key) = &config.key {
             form_data.data.insert("key".to_string(), key.clone());
         }
+        form_data.original_key = config.key.clone();
 
-        if let Some(pf) = &config.port_forward {
-            form_data
-                .data
-                .insert("pf_enabled".to_string(), pf.enabled.to_string());
-            form_data
-                .data
-                .insert("pf_local".to_string(), pf.local.clone());
+        if let Some(group) = &config.group {
+            form_data.data.insert("group".to_string(), group.clone());
+        }
+
+        if !config.tags.is_empty() {
             form_data
                 .data
-                .insert("pf_remote".to_string(), pf.remote.clone());
+                .insert("tags".to_string(), config.tags.join(", "));
         }
 
+        form_data.port_forwards = config.port_forwards.clone();
+        form_data
+            .data
+            .insert("tunnel_only".to_string(), config.tunnel_only.to_string());
+
         form_data.data.insert(
             "use_global_proxy".to_string(),
             config.use_global_proxy.to_string(),
@@ -205,6 +241,12 @@ impl FormData {
                     .data
                     .insert("proxy_password".to_string(), password.clone());
             }
+
+            if !proxy.jump_hosts.is_empty() {
+                form_data
+                    .data
+                    .insert("jump_hosts".to_string(), proxy.jump_hosts.join(","));
+            }
         }
 
         // 确保字段索引在有效范围内
@@ -371,13 +413,129 @@ impl FormData {
         }
     }
 
-    /// 切换复选框状态（端口转发启用/禁用）
+    /// 切换复选框状态（选中的端口转发启用/禁用、仅隧道）
     pub fn toggle_checkbox(&mut self) {
-        let field = self.get_current_field();
-        if field == FormField::PortForwardEnabled {
-            let current = self.get(&field).to_lowercase() == "true";
-            self.set(&field, (!current).to_string());
+        match self.get_current_field() {
+            FormField::PortForwards => {
+                if let Some(pf) = self.port_forwards.get_mut(self.forward_index) {
+                    pf.enabled = !pf.enabled;
+                }
+            }
+            FormField::TunnelOnly => {
+                let tunnel_only = self.is_tunnel_only();
+                self.set(&FormField::TunnelOnly, (!tunnel_only).to_string());
+            }
+            _ => {}
+        }
+    }
+
+    /// 检查是否勾选了仅隧道
+    ///
+    /// # 返回
+    /// 返回 true 表示仅隧道
+    pub fn is_tunnel_only(&self) -> bool {
+        self.get(&FormField::TunnelOnly) == "true"
+    }
+
+    /// 获取编辑中的转发类型
+    ///
+    /// # 返回
+    /// 返回转发类型
+    pub fn forward_kind(&self) -> ForwardKind {
+        match self.get(&FormField::PortForwardKind).as_str() {
+            "Remote" => ForwardKind::Remote,
+            "Dynamic" => ForwardKind::Dynamic,
+            _ => ForwardKind::Local,
+        }
+    }
+
+    /// 切换编辑中的转发类型（本地 -> 远程 -> 动态）
+    pub fn cycle_forward_kind(&mut self) {
+        let next = match self.forward_kind() {
+            ForwardKind::Local => ForwardKind::Remote,
+            ForwardKind::Remote => ForwardKind::Dynamic,
+            ForwardKind::Dynamic => ForwardKind::Local,
+        };
+        if next == ForwardKind::Dynamic {
+            self.set(&FormField::PortForwardRemote, String::new());
+        }
+        self.set(&FormField::PortForwardKind, format!("{next:?}"));
+    }
+
+    /// 根据编辑中的字段生成端口转发
+    ///
+    /// # 返回
+    /// 没有填写任何字段时返回 None，否则返回验证结果
+    fn draft_forward(&self) -> Option<Result<PortForward, String>> {
+        let local = self.get(&FormField::PortForwardLocal);
+        let remote = self.get(&FormField::PortForwardRemote);
+        if local.is_empty() && remote.is_empty() {
+            return None;
+        }
+
+        let forward = PortForward {
+            kind: self.forward_kind(),
+            enabled: true,
+            local,
+            remote,
+        };
+        Some(forward.validate().map(|_| forward))
+    }
+
+    /// 将编辑中的端口转发添加到列表
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为包含错误信息的 Err
+    pub fn add_draft_forward(&mut self) -> Result<(), String> {
+        let forward = self.draft_forward().ok_or("请先填写监听地址和目标地址")??;
+        if self.port_forwards.contains(&forward) {
+            return Err("该端口转发已存在".to_string());
+        }
+
+        self.port_forwards.push(forward);
+        self.forward_index = self.port_forwards.len() - 1;
+        self.set(&FormField::PortForwardLocal, String::new());
+        self.set(&FormField::PortForwardRemote, String::new());
+        Ok(())
+    }
+
+    /// 将选中的端口转发移回编辑字段
+    pub fn edit_selected_forward(&mut self) {
+        if self.forward_index >= self.port_forwards.len() {
+            return;
+        }
+
+        let forward = self.port_forwards.remove(self.forward_index);
+        self.set(&FormField::PortForwardKind, format!("{:?}", forward.kind));
+        self.set(&FormField::PortForwardLocal, forward.local);
+        self.set(&FormField::PortForwardRemote, forward.remote);
+        self.forward_index = self.forward_index.saturating_sub(1);
+    }
+
+    /// 删除选中的端口转发
+    pub fn remove_selected_forward(&mut self) {
+        if self.forward_index < self.port_forwards.len() {
+            self.port_forwards.remove(self.forward_index);
+            self.forward_index = self
+                .forward_index
+                .min(self.port_forwards.len().saturating_sub(1));
+        }
+    }
+
+    /// 在端口转发列表中移动选择
+    ///
+    /// # 参数
+    /// - `forward`: true 为向后移动，false 为向前移动
+    pub fn select_forward(&mut self, forward: bool) {
+        let len = self.port_forwards.len();
+        if len == 0 {
+            return;
         }
+        self.forward_index = if forward {
+            (self.forward_index + 1) % len
+        } else {
+            (self.forward_index + len - 1) % len
+        };
     }
 
     /// 切换代理选项（全局代理/不使用代理/SOCKS5/HTTP）
@@ -396,7 +554,7 @@ impl FormData {
                 .map(|t| t.as_str())
                 .unwrap_or("None");
 
-            // 状态循环: 全局代理 -> 不使用代理 -> SOCKS5代理 -> HTTP代理 -> 全局代理
+            // 状态循环: 全局代理 -> 不使用代理 -> SOCKS5代理 -> HTTP代理 -> 跳板机 -> 全局代理
             if use_global_proxy {
                 // 全局代理 -> 不使用代理
                 self.set(&FormField::UseGlobalProxy, "false".to_string());
@@ -415,14 +573,11 @@ impl FormData {
                 self.data
                     .insert("proxy_type".to_string(), "Http".to_string());
             } else if proxy_type == "Http" {
-                // HTTP代理 -> 全局代理
-                self.set(&FormField::UseGlobalProxy, "true".to_string());
+                // HTTP代理 -> 跳板机
                 self.data
-                    .insert("proxy_enabled".to_string(), "false".to_string());
-                self.data
-                    .insert("proxy_type".to_string(), "None".to_string());
+                    .insert("proxy_type".to_string(), "Jump".to_string());
             } else {
-                // 默认回到全局代理
+                // 跳板机或其他状态 -> 全局代理
                 self.set(&FormField::UseGlobalProxy, "true".to_string());
                 self.data
                     .insert("proxy_enabled".to_string(), "false".to_string());
@@ -432,6 +587,36 @@ impl FormData {
         }
     }
 
+    /// 获取已选择的跳板机链
+    ///
+    /// # 返回
+    /// 返回按连接顺序排列的跳板机别名
+    pub fn jump_hosts(&self) -> Vec<String> {
+        self.get(&FormField::JumpHosts)
+            .split(',')
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 将跳板机追加到链的末尾
+    ///
+    /// # 参数
+    /// - `alias`: 跳板机别名
+    pub fn push_jump_host(&mut self, alias: &str) {
+        let mut hosts = self.jump_hosts();
+        hosts.push(alias.to_string());
+        self.set(&FormField::JumpHosts, hosts.join(","));
+    }
+
+    /// 移除链中最后一个跳板机
+    pub fn pop_jump_host(&mut self) {
+        let mut hosts = self.jump_hosts();
+        hosts.pop();
+        self.set(&FormField::JumpHosts, hosts.join(","));
+    }
+
     /// 验证表单数据
     ///
     /// # 返回
@@ -459,19 +644,22 @@ impl FormData {
             }
         }
 
-        // 端口转发验证
-        let pf_enabled = self.get(&FormField::PortForwardEnabled).to_lowercase() == "true";
-        if pf_enabled {
-            let local = self.get(&FormField::PortForwardLocal);
-            let remote = self.get(&FormField::PortForwardRemote);
-
-            if local.is_empty() {
-                return Err("启用端口转发时，本地端口不能为空".to_string());
-            }
+        let key = self.get(&FormField::Key);
+        if !key.is_empty() && self.original_key.as_deref() != Some(key.as_str()) {
+            check_private_key(&key)?;
+        }
 
-            if remote.is_empty() {
-                return Err("启用端口转发时，远程端口不能为空".to_string());
-            }
+        // 端口转发验证，未添加到列表的编辑中转发也会一并保存
+        for pf in self.port_forwards.iter().filter(|pf| pf.enabled) {
+            pf.validate()
+                .map_err(|e| format!("端口转发 {}: {e}", pf.summary()))?;
+        }
+        let draft = self.draft_forward().transpose()?;
+        if self.is_tunnel_only()
+            && draft.is_none()
+            && !self.port_forwards.iter().any(|pf| pf.enabled)
+        {
+            return Err("仅隧道模式需要至少一条启用的端口转发".to_string());
         }
 
         // 代理配置验证
@@ -482,7 +670,16 @@ impl FormData {
                 .get("proxy_enabled")
                 .map(|v| v.to_lowercase() == "true")
                 .unwrap_or(false);
-            if proxy_enabled {
+            let is_jump = self.data.get("proxy_type").map(String::as_str) == Some("Jump");
+            if proxy_enabled && is_jump {
+                if self.jump_hosts().is_empty() {
+                    return Err("请至少选择一个跳板机".to_string());
+                }
+                let alias = self.get(&FormField::Alias);
+                if self.jump_hosts().contains(&alias) {
+                    return Err("跳板机不能是主机自身".to_string());
+                }
+            } else if proxy_enabled {
                 let proxy_host = self.get(&FormField::ProxyHost);
                 let proxy_port_str = self.get(&FormField::ProxyPort);
 
@@ -500,6 +697,8 @@ impl FormData {
                 } else {
                     return Err("代理端口不能为空".to_string());
                 }
+
+                validate_password_ref(&self.get(&FormField::ProxyPassword))?;
             }
         }
 
@@ -536,6 +735,8 @@ impl FormData {
             if proxy_port == 0 {
                 return Err("代理端口必须是1-65535之间的有效数字".to_string());
             }
+
+            validate_password_ref(&self.get(&FormField::GlobalProxyPassword))?;
         }
 
         Ok(())
@@ -583,22 +784,15 @@ impl FormData {
             Some(self.get(&FormField::Key))
         };
 
-        let pf_enabled = self.get(&FormField::PortForwardEnabled).to_lowercase() == "true";
-        let port_forward = if pf_enabled {
-            let local = self.get(&FormField::PortForwardLocal);
-            let remote = self.get(&FormField::PortForwardRemote);
-            if !local.is_empty() && !remote.is_empty() {
-                Some(PortForward {
-                    enabled: true,
-                    local,
-                    remote,
-                })
-            } else {
-                None
+        let group = normalize_group(&self.get(&FormField::Group));
+        let tags = parse_tags(&self.get(&FormField::Tags));
+
+        let mut port_forwards = self.port_forwards.clone();
+        if let Some(Ok(draft)) = self.draft_forward() {
+            if !port_forwards.contains(&draft) {
+                port_forwards.push(draft);
             }
-        } else {
-            None
-        };
+        }
 
         let use_global_proxy = self.get(&FormField::UseGlobalProxy).to_lowercase() == "true";
         let proxy = if !use_global_proxy {
@@ -615,11 +809,18 @@ impl FormData {
                     .map(|t| match t.as_str() {
                         "Socks5" => ProxyType::Socks5,
                         "Http" => ProxyType::Http,
+                        "Jump" => ProxyType::Jump,
                         _ => ProxyType::None,
                     })
                     .unwrap_or(ProxyType::None);
 
-                if proxy_type != ProxyType::None {
+                if proxy_type == ProxyType::Jump {
+                    Some(ProxyConfig {
+                        proxy_type,
+                        jump_hosts: self.jump_hosts(),
+                        ..ProxyConfig::default()
+                    })
+                } else if proxy_type != ProxyType::None {
                     let host = self.get(&FormField::ProxyHost);
                     let port = self.get(&FormField::ProxyPort).parse::<u16>().ok();
                     let username = if self.get(&FormField::ProxyUsername).is_empty() {
@@ -639,6 +840,7 @@ impl FormData {
                         port,
                         username,
                         password,
+                        jump_hosts: Vec::new(),
                     })
                 } else {
                     None
@@ -656,9 +858,91 @@ impl FormData {
             port,
             user,
             key,
-            port_forward,
+            group,
+            tags,
+            port_forwards,
+            tunnel_only: self.is_tunnel_only(),
             proxy,
             use_global_proxy,
         })
     }
 }
+
+/// 检查代理密码中引用的格式
+///
+/// # 参数
+/// - `password`: 表单中的代理密码
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，引用格式无效时为包含错误信息的 Err
+fn validate_password_ref(password: &str) -> Result<(), String> {
+    SecretRef::parse(password)
+        .validate()
+        .map_err(|e| format!("代理密码: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture_key() -> String {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/agent/id_ed25519")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn host(key: &str) -> SSHConfig {
+        SSHConfig {
+            alias: "web".to_string(),
+            address: "web.example.com".to_string(),
+            port: None,
+            user: None,
+            key: Some(key.to_string()),
+            group: None,
+            tags: Vec::new(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy: None,
+            use_global_proxy: false,
+        }
+    }
+
+    #[test]
+    fn unchanged_key_is_not_checked() {
+        // 私钥只在其他机器上存在时，也可以修改主机的其他字段
+        let mut form = FormData::from_config(&host("/nonexistent/id_ed25519"));
+        form.set(&FormField::User, "deploy".to_string());
+        assert_eq!(form.validate(), Ok(()));
+    }
+
+    #[test]
+    fn changed_key_must_exist() {
+        let mut form = FormData::from_config(&host("/nonexistent/id_ed25519"));
+        form.set(&FormField::Key, "/nonexistent/id_rsa".to_string());
+        let error = form.validate().unwrap_err();
+        assert!(error.contains("不存在"), "{error}");
+
+        form.set(&FormField::Key, fixture_key());
+        assert_eq!(form.validate(), Ok(()));
+    }
+
+    #[test]
+    fn new_host_key_is_checked() {
+        let mut form = FormData::new();
+        form.set(&FormField::Alias, "web".to_string());
+        form.set(&FormField::Address, "web.example.com".to_string());
+        assert_eq!(form.validate(), Ok(()));
+
+        form.set(&FormField::Key, "/nonexistent/id_ed25519".to_string());
+        assert!(form.validate().is_err());
+
+        form.set(&FormField::Key, format!("{}.pub", fixture_key()));
+        let error = form.validate().unwrap_err();
+        assert!(error.contains("是公钥"), "{error}");
+
+        form.set(&FormField::Key, fixture_key());
+        assert_eq!(form.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,424 @@
+use crate::config::SSHConfig;
+use crate::search_manager::SearchMatch;
+use std::collections::HashSet;
+
+/// 列表中的一行
+#[derive(Debug, Clone)]
+pub enum ListRow {
+    /// 分组
+    Group {
+        /// 完整路径，如 `prod/db`
+        path: String,
+        /// 层级，顶层分组为 0
+        depth: usize,
+        /// 分组及其子分组中的主机数量
+        count: usize,
+        collapsed: bool,
+    },
+    /// 列表顶部的“最近连接”
+    Recent { count: usize, collapsed: bool },
+    /// 主机，`matched.index` 为主机在配置列表中的下标；
+    /// `recent` 表示该行位于“最近连接”中
+    Host {
+        matched: SearchMatch,
+        depth: usize,
+        recent: bool,
+    },
+}
+
+impl ListRow {
+    /// 获取行对应的主机下标
+    ///
+    /// # 返回
+    /// 返回主机在配置列表中的下标，分组行为 None
+    pub fn host_index(&self) -> Option<usize> {
+        match self {
+            ListRow::Host { matched, .. } => Some(matched.index),
+            ListRow::Group { .. } | ListRow::Recent { .. } => None,
+        }
+    }
+
+    /// 获取分组行的路径
+    ///
+    /// # 返回
+    /// 返回分组路径，主机行为 None
+    pub fn group_path(&self) -> Option<&str> {
+        match self {
+            ListRow::Group { path, .. } => Some(path),
+            ListRow::Recent { .. } | ListRow::Host { .. } => None,
+        }
+    }
+}
+
+/// 分组树中的一个节点
+#[derive(Debug, Default)]
+struct GroupNode {
+    path: String,
+    children: Vec<GroupNode>,
+    hosts: Vec<usize>,
+}
+
+impl GroupNode {
+    /// 将主机放入路径对应的子节点，缺少的中间节点按出现顺序创建
+    ///
+    /// # 参数
+    /// - `parts`: 剩余的路径片段
+    /// - `index`: 主机下标
+    fn insert(&mut self, parts: &[&str], index: usize) {
+        let Some((first, rest)) = parts.split_first() else {
+            self.hosts.push(index);
+            return;
+        };
+        let path = if self.path.is_empty() {
+            first.to_string()
+        } else {
+            format!("{}/{first}", self.path)
+        };
+        let position = match self.children.iter().position(|c| c.path == path) {
+            Some(position) => position,
+            None => {
+                self.children.push(GroupNode {
+                    path,
+                    ..GroupNode::default()
+                });
+                self.children.len() - 1
+            }
+        };
+        self.children[position].insert(rest, index);
+    }
+
+    /// 统计节点及其子节点中的主机数量
+    ///
+    /// # 返回
+    /// 返回主机数量
+    fn count(&self) -> usize {
+        self.hosts.len() + self.children.iter().map(GroupNode::count).sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupManager {
+    /// 已折叠的分组路径
+    pub collapsed: HashSet<String>,
+    /// “最近连接”是否已折叠
+    pub recent_collapsed: bool,
+    /// 移动主机时输入的目标分组
+    pub input: String,
+}
+
+impl GroupManager {
+    /// 创建新的分组管理器
+    ///
+    /// # 返回
+    /// 返回初始化的分组管理器，所有分组默认展开
+    pub fn new() -> Self {
+        Self {
+            collapsed: HashSet::new(),
+            recent_collapsed: false,
+            input: String::new(),
+        }
+    }
+
+    /// 展开或折叠分组
+    ///
+    /// # 参数
+    /// - `path`: 分组路径
+    pub fn toggle(&mut self, path: &str) {
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.to_string());
+        }
+    }
+
+    /// 设置分组的折叠状态
+    ///
+    /// # 参数
+    /// - `path`: 分组路径
+    /// - `collapsed`: true 为折叠，false 为展开
+    pub fn set_collapsed(&mut self, path: &str, collapsed: bool) {
+        if collapsed {
+            self.collapsed.insert(path.to_string());
+        } else {
+            self.collapsed.remove(path);
+        }
+    }
+
+    /// 展开分组及其所有上级分组
+    ///
+    /// # 参数
+    /// - `path`: 分组路径
+    pub fn expand(&mut self, path: &str) {
+        let mut current = Some(path);
+        while let Some(path) = current {
+            self.collapsed.remove(path);
+            current = parent_group(path);
+        }
+    }
+
+    /// 将输入切换为下一个已有分组
+    ///
+    /// # 参数
+    /// - `groups`: 已有的分组路径
+    pub fn cycle_input(&mut self, groups: &[String]) {
+        let next = groups
+            .iter()
+            .position(|g| *g == self.input)
+            .map_or(0, |i| (i + 1) % groups.len());
+        if let Some(group) = groups.get(next) {
+            self.input = group.clone();
+        }
+    }
+
+    /// 生成树形列表
+    ///
+    /// 分组排在同级主机之前，分组和主机都保持在 `order` 中首次出现的顺序；
+    /// 折叠的分组只显示分组行。`recent` 不为空时在顶部显示“最近连接”。
+    ///
+    /// # 参数
+    /// - `configs`: SSH 配置列表
+    /// - `order`: 按排序方式排列的主机下标
+    /// - `recent`: 最近连接过的主机下标，最近的在前
+    ///
+    /// # 返回
+    /// 返回按显示顺序排列的行
+    pub fn build_rows(
+        &self,
+        configs: &[SSHConfig],
+        order: &[usize],
+        recent: &[usize],
+    ) -> Vec<ListRow> {
+        let mut root = GroupNode::default();
+        for &index in order {
+            let config = &configs[index];
+            let parts: Vec<&str> = config
+                .group
+                .as_deref()
+                .map(|group| group.split('/').collect())
+                .unwrap_or_default();
+            root.insert(&parts, index);
+        }
+
+        let mut rows = Vec::new();
+        if !recent.is_empty() {
+            rows.push(ListRow::Recent {
+                count: recent.len(),
+                collapsed: self.recent_collapsed,
+            });
+            if !self.recent_collapsed {
+                rows.extend(recent.iter().map(|&index| ListRow::Host {
+                    matched: SearchMatch {
+                        index,
+                        ..SearchMatch::default()
+                    },
+                    depth: 1,
+                    recent: true,
+                }));
+            }
+        }
+        self.flatten(&root, 0, &mut rows);
+        rows
+    }
+
+    /// 按显示顺序展开节点
+    ///
+    /// # 参数
+    /// - `node`: 分组节点
+    /// - `depth`: 子项的层级
+    /// - `rows`: 输出的行
+    fn flatten(&self, node: &GroupNode, depth: usize, rows: &mut Vec<ListRow>) {
+        for child in &node.children {
+            let collapsed = self.collapsed.contains(&child.path);
+            rows.push(ListRow::Group {
+                path: child.path.clone(),
+                depth,
+                count: child.count(),
+                collapsed,
+            });
+            if !collapsed {
+                self.flatten(child, depth + 1, rows);
+            }
+        }
+        rows.extend(node.hosts.iter().map(|&index| ListRow::Host {
+            matched: SearchMatch {
+                index,
+                ..SearchMatch::default()
+            },
+            depth,
+            recent: false,
+        }));
+    }
+}
+
+/// 获取分组路径的最后一级名称
+///
+/// # 参数
+/// - `path`: 分组路径
+///
+/// # 返回
+/// 返回最后一级名称，如 `prod/db` 返回 `db`
+pub fn group_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// 获取分组路径的上一级
+///
+/// # 参数
+/// - `path`: 分组路径
+///
+/// # 返回
+/// 返回上一级路径，顶层分组返回 None
+pub fn parent_group(path: &str) -> Option<&str> {
+    path.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(alias: &str, group: Option<&str>) -> SSHConfig {
+        SSHConfig {
+            alias: alias.to_string(),
+            address: format!("{alias}.example.com"),
+            port: None,
+            user: None,
+            key: None,
+            group: group.map(str::to_string),
+            tags: Vec::new(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy: None,
+            use_global_proxy: false,
+        }
+    }
+
+    /// 将行转换为便于比较的文本：分组为 `路径/层级/数量`，折叠的分组末尾加 `+`，
+    /// 主机为 `别名@层级`
+    fn describe(configs: &[SSHConfig], rows: &[ListRow]) -> Vec<String> {
+        rows.iter()
+            .map(|row| match row {
+                ListRow::Group {
+                    path,
+                    depth,
+                    count,
+                    collapsed,
+                } => format!(
+                    "{path}/{depth}/{count}{}",
+                    if *collapsed { "+" } else { "" }
+                ),
+                ListRow::Recent { count, collapsed } => {
+                    format!("recent/{count}{}", if *collapsed { "+" } else { "" })
+                }
+                ListRow::Host {
+                    matched,
+                    depth,
+                    recent,
+                } => format!(
+                    "{}@{depth}{}",
+                    configs[matched.index].alias,
+                    if *recent { "*" } else { "" }
+                ),
+            })
+            .collect()
+    }
+
+    fn configs() -> Vec<SSHConfig> {
+        vec![
+            host("laptop", None),
+            host("db1", Some("prod/db")),
+            host("web1", Some("prod")),
+            host("dev1", Some("dev")),
+            host("db2", Some("prod/db")),
+        ]
+    }
+
+    #[test]
+    fn groups_come_before_ungrouped_hosts() {
+        let configs = configs();
+        let rows = GroupManager::new().build_rows(&configs, &[0, 1, 2, 3, 4], &[]);
+        assert_eq!(
+            describe(&configs, &rows),
+            [
+                "prod/0/3",
+                "prod/db/1/2",
+                "db1@2",
+                "db2@2",
+                "web1@1",
+                "dev/0/1",
+                "dev1@1",
+                "laptop@0",
+            ]
+        );
+    }
+
+    #[test]
+    fn hosts_follow_the_given_order() {
+        let configs = configs();
+        let rows = GroupManager::new().build_rows(&configs, &[4, 3, 2, 1, 0], &[]);
+        assert_eq!(
+            describe(&configs, &rows),
+            [
+                "prod/0/3",
+                "prod/db/1/2",
+                "db2@2",
+                "db1@2",
+                "web1@1",
+                "dev/0/1",
+                "dev1@1",
+                "laptop@0",
+            ]
+        );
+    }
+
+    #[test]
+    fn collapsed_group_hides_its_children() {
+        let configs = configs();
+        let mut groups = GroupManager::new();
+        groups.toggle("prod/db");
+        let rows = groups.build_rows(&configs, &[0, 1, 2, 3, 4], &[]);
+        assert_eq!(
+            describe(&configs, &rows),
+            [
+                "prod/0/3",
+                "prod/db/1/2+",
+                "web1@1",
+                "dev/0/1",
+                "dev1@1",
+                "laptop@0"
+            ]
+        );
+
+        groups.toggle("prod");
+        let rows = groups.build_rows(&configs, &[0, 1, 2, 3, 4], &[]);
+        assert_eq!(
+            describe(&configs, &rows),
+            ["prod/0/3+", "dev/0/1", "dev1@1", "laptop@0"]
+        );
+
+        groups.expand("prod/db");
+        assert!(groups.collapsed.is_empty());
+    }
+
+    #[test]
+    fn recent_section_is_listed_first() {
+        let configs = configs();
+        let mut groups = GroupManager::new();
+        let rows = groups.build_rows(&configs, &[0, 3], &[3, 0]);
+        assert_eq!(
+            describe(&configs, &rows),
+            [
+                "recent/2",
+                "dev1@1*",
+                "laptop@1*",
+                "dev/0/1",
+                "dev1@1",
+                "laptop@0"
+            ]
+        );
+
+        groups.recent_collapsed = true;
+        let rows = groups.build_rows(&configs, &[0, 3], &[3, 0]);
+        assert_eq!(
+            describe(&configs, &rows),
+            ["recent/2+", "dev/0/1", "dev1@1", "laptop@0"]
+        );
+    }
+}
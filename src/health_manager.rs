@@ -0,0 +1,338 @@
+use crate::config::SSHConfig;
+use crate::events::AppEvent;
+use crate::proxy::{GlobalConfig, ProxyConfig, ProxyType};
+use crate::proxy_connect::connect_via_proxy;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+
+/// 同时进行的检测数
+const MAX_CONCURRENT: usize = 16;
+/// 单个主机的检测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 主机的可达状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostStatus {
+    /// 正在检测
+    Checking,
+    /// 可以建立 TCP 连接，附带连接耗时
+    Up(Duration),
+    /// 无法连接，附带错误信息
+    Down(String),
+    /// 经跳板机连接的主机无法直接检测
+    Skipped,
+}
+
+/// 一个待检测的主机
+#[derive(Debug, Clone)]
+pub struct ProbeTarget {
+    pub alias: String,
+    pub host: String,
+    pub port: u16,
+    /// 实际生效的代理，经代理检测时连接耗时包含代理握手
+    pub proxy: Option<ProxyConfig>,
+}
+
+impl ProbeTarget {
+    /// 根据主机配置生成检测目标
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    /// - `global_config`: 全局配置
+    ///
+    /// # 返回
+    /// 返回检测目标
+    pub fn from_config(config: &SSHConfig, global_config: &GlobalConfig) -> Self {
+        Self {
+            alias: config.alias.clone(),
+            host: config.address.clone(),
+            port: config.port.unwrap_or(22),
+            proxy: config.effective_proxy(global_config).cloned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthManager {
+    /// 按别名保存的检测结果
+    statuses: HashMap<String, HostStatus>,
+    /// 上次开始检测的时间
+    last_started: Option<Instant>,
+}
+
+impl HealthManager {
+    /// 创建新的检测管理器
+    ///
+    /// # 返回
+    /// 返回没有检测结果的管理器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取主机的检测结果
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回检测结果，尚未检测时为 None
+    pub fn status(&self, alias: &str) -> Option<&HostStatus> {
+        self.statuses.get(alias)
+    }
+
+    /// 保存主机的检测结果
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    /// - `status`: 检测结果
+    pub fn set_status(&mut self, alias: &str, status: HostStatus) {
+        // 主机在检测期间被删除或改名时丢弃结果
+        if let Some(current) = self.statuses.get_mut(alias) {
+            *current = status;
+        }
+    }
+
+    /// 检查是否有正在进行的检测
+    ///
+    /// # 返回
+    /// 返回 true 表示仍有主机在检测中
+    pub fn is_running(&self) -> bool {
+        self.statuses.values().any(|s| *s == HostStatus::Checking)
+    }
+
+    /// 检查是否到了定时检测的时间
+    ///
+    /// # 参数
+    /// - `interval`: 检测间隔
+    ///
+    /// # 返回
+    /// 返回 true 表示从未检测过或距上次检测已超过间隔
+    pub fn is_due(&self, interval: Duration) -> bool {
+        self.last_started
+            .map_or(true, |started| started.elapsed() >= interval)
+    }
+
+    /// 在后台线程中检测主机，每个主机检测完成时推送 `AppEvent::HostProbed`
+    ///
+    /// # 参数
+    /// - `targets`: 待检测的主机
+    /// - `skipped`: 本次不检测的主机，保留上次的结果
+    /// - `sender`: 主循环的事件通道
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，无法启动后台线程时为 Err
+    pub fn start(
+        &mut self,
+        targets: Vec<ProbeTarget>,
+        skipped: &[String],
+        sender: Sender<AppEvent>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        self.last_started = Some(Instant::now());
+        let kept: Vec<(String, HostStatus)> = skipped
+            .iter()
+            .filter_map(|alias| Some((alias.clone(), self.statuses.get(alias)?.clone())))
+            .collect();
+        self.statuses = targets
+            .iter()
+            .map(|target| (target.alias.clone(), HostStatus::Checking))
+            .chain(kept)
+            .collect();
+
+        std::thread::Builder::new()
+            .name("mssh-probe".to_string())
+            .spawn(move || {
+                runtime.block_on(probe_all(
+                    targets,
+                    MAX_CONCURRENT,
+                    PROBE_TIMEOUT,
+                    move |alias, status| {
+                        // 主循环已退出时忽略发送失败
+                        let _ = sender.send(AppEvent::HostProbed {
+                            alias: alias.to_string(),
+                            status,
+                        });
+                    },
+                ))
+            })?;
+        Ok(())
+    }
+}
+
+/// 以有限的并发检测多个主机
+///
+/// # 参数
+/// - `targets`: 待检测的主机
+/// - `concurrency`: 同时进行的检测数
+/// - `timeout`: 单个主机的超时
+/// - `on_result`: 每个主机检测完成时调用
+pub async fn probe_all<F>(
+    targets: Vec<ProbeTarget>,
+    concurrency: usize,
+    timeout: Duration,
+    on_result: F,
+) where
+    F: Fn(&str, HostStatus) + Clone + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let handles: Vec<_> = targets
+        .into_iter()
+        .map(|target| {
+            let semaphore = Arc::clone(&semaphore);
+            let on_result = on_result.clone();
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire().await;
+                let status = probe(&target, timeout).await;
+                on_result(&target.alias, status);
+            })
+        })
+        .collect();
+    for handle in handles {
+        let _ = handle.await;
+    }
+}
+
+/// 检测单个主机能否建立 TCP 连接
+///
+/// # 参数
+/// - `target`: 待检测的主机
+/// - `timeout`: 超时
+///
+/// # 返回
+/// 返回检测结果
+pub async fn probe(target: &ProbeTarget, timeout: Duration) -> HostStatus {
+    let proxy = target.proxy.as_ref();
+    if proxy.is_some_and(|p| p.proxy_type == ProxyType::Jump) {
+        return HostStatus::Skipped;
+    }
+
+    let started = Instant::now();
+    let connect = async {
+        match proxy {
+            Some(proxy) => connect_via_proxy(proxy, &target.host, target.port).await,
+            None => TcpStream::connect((target.host.as_str(), target.port)).await,
+        }
+    };
+    match tokio::time::timeout(timeout, connect).await {
+        Ok(Ok(_)) => HostStatus::Up(started.elapsed()),
+        Ok(Err(e)) => HostStatus::Down(e.to_string()),
+        Err(_) => HostStatus::Down(format!("{} 秒内未响应", timeout.as_secs())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    fn target(alias: &str, port: u16, proxy: Option<ProxyConfig>) -> ProbeTarget {
+        ProbeTarget {
+            alias: alias.to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            proxy,
+        }
+    }
+
+    /// 获取一个当前没有监听的本地端口
+    async fn closed_port() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn listening_port_is_up() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let status = probe(&target("up", port, None), Duration::from_secs(1)).await;
+        assert!(matches!(status, HostStatus::Up(_)), "{status:?}");
+    }
+
+    #[tokio::test]
+    async fn closed_port_is_down() {
+        let port = closed_port().await;
+        let status = probe(&target("down", port, None), Duration::from_secs(1)).await;
+        assert!(matches!(status, HostStatus::Down(_)), "{status:?}");
+    }
+
+    #[tokio::test]
+    async fn unresponsive_proxy_times_out() {
+        // 代理接受连接但不回应握手
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = ProxyConfig {
+            proxy_type: ProxyType::Socks5,
+            host: "127.0.0.1".to_string(),
+            port: Some(listener.local_addr().unwrap().port()),
+            ..ProxyConfig::default()
+        };
+        let started = Instant::now();
+        let status = probe(&target("slow", 22, Some(proxy)), Duration::from_millis(200)).await;
+        assert!(
+            matches!(&status, HostStatus::Down(e) if e.contains("未响应")),
+            "{status:?}"
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn jump_hosts_are_skipped() {
+        let proxy = ProxyConfig {
+            proxy_type: ProxyType::Jump,
+            jump_hosts: vec!["bastion".to_string()],
+            ..ProxyConfig::default()
+        };
+        let status = probe(&target("jump", 22, Some(proxy)), Duration::from_secs(1)).await;
+        assert_eq!(status, HostStatus::Skipped);
+    }
+
+    #[tokio::test]
+    async fn probe_all_reports_every_target() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let up = listener.local_addr().unwrap().port();
+        let down = closed_port().await;
+        let results = Arc::new(Mutex::new(HashMap::new()));
+        let collected = Arc::clone(&results);
+
+        probe_all(
+            vec![target("up", up, None), target("down", down, None)],
+            1,
+            Duration::from_secs(1),
+            move |alias, status| {
+                collected.lock().unwrap().insert(alias.to_string(), status);
+            },
+        )
+        .await;
+
+        let results = results.lock().unwrap();
+        assert!(matches!(results["up"], HostStatus::Up(_)));
+        assert!(matches!(results["down"], HostStatus::Down(_)));
+    }
+
+    #[test]
+    fn skipped_hosts_keep_previous_status() {
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        let mut manager = HealthManager::new();
+        manager
+            .start(vec![target("web", 1, None)], &[], sender.clone())
+            .unwrap();
+        let up = HostStatus::Up(Duration::from_millis(5));
+        manager.set_status("web", up.clone());
+
+        manager
+            .start(Vec::new(), &["web".to_string()], sender.clone())
+            .unwrap();
+        assert_eq!(manager.status("web"), Some(&up));
+        assert!(!manager.is_running());
+
+        manager.start(Vec::new(), &[], sender).unwrap();
+        assert_eq!(manager.status("web"), None);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::config::ConfigManager;
+use crate::tunnel_manager::{format_duration, unix_now};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 最多保留的连接记录数，超出时丢弃最早的记录
+const MAX_ENTRIES: usize = 1000;
+
+/// 一次连接记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub alias: String,
+    /// 连接时间（Unix 时间戳，秒）
+    pub started_at: u64,
+    /// 连接持续的秒数
+    #[serde(default)]
+    pub duration_secs: u64,
+    /// ssh 的退出码，ssh 无法启动或被信号终止时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    entries: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryManager {
+    /// 按时间顺序排列的连接记录
+    pub entries: Vec<HistoryEntry>,
+    path: PathBuf,
+}
+
+impl HistoryManager {
+    /// 载入与配置文件位于同一目录的 history.toml
+    ///
+    /// 文件不存在或无法解析时从空记录开始。
+    ///
+    /// # 参数
+    /// - `config_path`: 配置文件路径
+    ///
+    /// # 返回
+    /// 返回历史记录管理器
+    pub fn load(config_path: &Path) -> Self {
+        let path = config_path.with_file_name("history.toml");
+        let entries = Self::read(&path);
+        Self { entries, path }
+    }
+
+    /// 读取历史记录文件
+    ///
+    /// # 参数
+    /// - `path`: 历史记录文件路径
+    ///
+    /// # 返回
+    /// 返回连接记录，文件不存在或无法解析时为空
+    fn read(path: &Path) -> Vec<HistoryEntry> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| toml::from_str::<HistoryFile>(&content).ok())
+            .map(|file| file.entries)
+            .unwrap_or_default()
+    }
+
+    /// 记录一次已结束的连接
+    ///
+    /// 写入前重新读取文件，避免覆盖其他 mssh 进程（如 tmux 窗口中的连接）写入的记录。
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    /// - `started_at`: 连接开始的时间戳
+    /// - `exit_code`: ssh 的退出码
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，写入失败时为 Err
+    pub fn record(
+        &mut self,
+        alias: &str,
+        started_at: u64,
+        exit_code: Option<i32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.entries = Self::read(&self.path);
+        self.entries.push(HistoryEntry {
+            alias: alias.to_string(),
+            started_at,
+            duration_secs: unix_now().saturating_sub(started_at),
+            exit_code,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.save()
+    }
+
+    /// 主机改名后更新历史记录中的别名
+    ///
+    /// # 参数
+    /// - `old`: 原别名
+    /// - `new`: 新别名
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，写入失败时为 Err
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.entries = Self::read(&self.path);
+        if old == new || !self.entries.iter().any(|e| e.alias == old) {
+            return Ok(());
+        }
+        for entry in self.entries.iter_mut().filter(|e| e.alias == old) {
+            entry.alias = new.to_string();
+        }
+        self.save()
+    }
+
+    /// 获取主机最近一次连接的记录
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回连接记录，从未连接过时为 None
+    pub fn last_entry(&self, alias: &str) -> Option<&HistoryEntry> {
+        self.entries.iter().rev().find(|e| e.alias == alias)
+    }
+
+    /// 获取主机最近一次连接的时间
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回 Unix 时间戳，从未连接过时为 None
+    pub fn last_used(&self, alias: &str) -> Option<u64> {
+        self.last_entry(alias).map(|e| e.started_at)
+    }
+
+    /// 获取主机的连接次数
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回保留的记录中该主机的连接次数
+    pub fn use_count(&self, alias: &str) -> usize {
+        self.entries.iter().filter(|e| e.alias == alias).count()
+    }
+
+    /// 获取连接过的主机，每个主机只取最近一次记录
+    ///
+    /// # 返回
+    /// 返回连接记录，最近的在前
+    pub fn recent(&self) -> Vec<&HistoryEntry> {
+        let mut recent: Vec<&HistoryEntry> = Vec::new();
+        for entry in self.entries.iter().rev() {
+            if !recent.iter().any(|e| e.alias == entry.alias) {
+                recent.push(entry);
+            }
+        }
+        recent
+    }
+
+    /// 保存历史记录
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let file = HistoryFile {
+            entries: self.entries.clone(),
+        };
+        ConfigManager::write_atomic(&self.path, &toml::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+}
+
+impl HistoryEntry {
+    /// 格式化连接结果
+    ///
+    /// # 返回
+    /// 返回形如 `时长 3 分钟, 退出码 0` 的说明
+    pub fn summary(&self) -> String {
+        let result = match self.exit_code {
+            Some(code) => format!("退出码 {code}"),
+            None => "未正常退出".to_string(),
+        };
+        format!("时长 {}, {result}", format_duration(self.duration_secs))
+    }
+}
+
+/// 将时间戳格式化为本地时间
+///
+/// # 参数
+/// - `timestamp`: Unix 时间戳（秒）
+///
+/// # 返回
+/// 返回形如 `2025-07-28 14:03` 的文本
+pub fn format_time(timestamp: u64) -> String {
+    Local
+        .timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    /// 在临时目录中创建历史记录管理器，目录在返回的 TempDir 销毁时删除
+    fn history(name: &str) -> (TempDir, HistoryManager) {
+        let dir = TempDir::new(name);
+        let history = HistoryManager::load(&dir.join("config.toml"));
+        (dir, history)
+    }
+
+    fn recent_aliases(history: &HistoryManager) -> Vec<&str> {
+        history.recent().iter().map(|e| e.alias.as_str()).collect()
+    }
+
+    #[test]
+    fn recent_lists_each_host_once_newest_first() {
+        let (_dir, mut history) = history("recent");
+        history.record("web", 100, Some(0)).unwrap();
+        history.record("db", 200, Some(0)).unwrap();
+        history.record("web", 300, Some(255)).unwrap();
+
+        assert_eq!(recent_aliases(&history), ["web", "db"]);
+        assert_eq!(history.recent()[0].exit_code, Some(255));
+        assert_eq!(history.last_used("web"), Some(300));
+        assert_eq!(history.use_count("web"), 2);
+        assert_eq!(history.last_used("cache"), None);
+    }
+
+    #[test]
+    fn record_keeps_entries_from_other_processes() {
+        let (dir, mut history) = history("shared");
+        let mut other = HistoryManager::load(&dir.join("config.toml"));
+        history.record("web", 100, Some(0)).unwrap();
+        other.record("db", 200, Some(0)).unwrap();
+        history.record("cache", 300, Some(0)).unwrap();
+
+        let reloaded = HistoryManager::load(&dir.join("config.toml"));
+        assert_eq!(recent_aliases(&reloaded), ["cache", "db", "web"]);
+    }
+
+    #[test]
+    fn record_drops_oldest_entries_over_limit() {
+        let (dir, mut history) = history("limit");
+        let file = HistoryFile {
+            entries: (0..MAX_ENTRIES as u64)
+                .map(|i| HistoryEntry {
+                    alias: format!("host{i}"),
+                    started_at: i,
+                    duration_secs: 0,
+                    exit_code: Some(0),
+                })
+                .collect(),
+        };
+        fs::write(
+            dir.join("history.toml"),
+            toml::to_string_pretty(&file).unwrap(),
+        )
+        .unwrap();
+
+        history.record("web", 5000, Some(0)).unwrap();
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        assert_eq!(history.entries[0].alias, "host1");
+        assert_eq!(history.entries.last().unwrap().alias, "web");
+    }
+
+    #[test]
+    fn rename_carries_entries_over() {
+        let (dir, mut history) = history("rename");
+        history.record("web", 100, Some(0)).unwrap();
+        history.record("db", 200, Some(0)).unwrap();
+        history.record("web", 300, Some(0)).unwrap();
+
+        history.rename("web", "frontend").unwrap();
+        assert_eq!(recent_aliases(&history), ["frontend", "db"]);
+        assert_eq!(history.use_count("frontend"), 2);
+        assert_eq!(history.use_count("web"), 0);
+
+        let reloaded = HistoryManager::load(&dir.join("config.toml"));
+        assert_eq!(reloaded.use_count("frontend"), 2);
+        // 没有记录的主机改名时不写入文件
+        history.rename("cache", "redis").unwrap();
+        assert_eq!(recent_aliases(&history), ["frontend", "db"]);
+    }
+}
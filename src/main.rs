@@ -1,75 +1,64 @@
+mod agent;
 mod app;
+mod cli;
 mod config;
 mod events;
 mod form_manager;
 mod forms;
+mod group_manager;
+mod health_manager;
+mod history_manager;
+mod key_manager;
 mod message_manager;
 mod navigation_manager;
 mod proxy;
+mod proxy_connect;
+mod search_manager;
+mod secret;
 mod ssh;
+mod ssh_config;
+mod sync_manager;
+#[cfg(test)]
+mod test_support;
+mod tunnel_manager;
 mod ui;
+mod vault;
 
 use app::App;
+use clap::Parser;
+use cli::Cli;
 use crossterm::{
-    event::{self, Event},
+    event::{self, Event, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use events::EventHandler;
+use events::{AppEvent, EventHandler};
 use ratatui::{prelude::CrosstermBackend, Terminal};
 use std::io;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// 没有按键时重新绘制界面的间隔
+const TICK_RATE: Duration = Duration::from_millis(250);
 
 /// SSH Manager 主程序入口
 ///
 /// # 返回
 /// 返回 Result，成功为 Ok(())，失败为 Err
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // 解析命令行参数
+    // 内置代理助手，由 ssh 作为 ProxyCommand 调用，不进入 TUI
     let args: Vec<String> = std::env::args().collect();
-    let mut config_path = None;
-    let mut import_ssh = false;
-    let mut quick_connect = None;
-
-    for (i, arg) in args.iter().enumerate() {
-        if arg == "--config" || arg == "-c" {
-            if i + 1 < args.len() {
-                config_path = Some(args[i + 1].clone());
-            } else {
-                eprintln!("错误: --config 参数需要指定配置文件路径");
-                std::process::exit(1);
-            }
-        } else if arg == "--import-ssh" {
-            import_ssh = true;
-        } else if arg == "-C" {
-            if i + 1 < args.len() {
-                quick_connect = Some(args[i + 1].clone());
-            } else {
-                eprintln!("错误: -C 参数需要指定编号或 Host 别名");
-                std::process::exit(1);
-            }
-        } else if arg == "--help" || arg == "-h" {
-            println!("SSH Manager - SSH 配置管理工具");
-            println!();
-            println!("用法: mssh [选项] [编号或别名]");
-            println!();
-            println!("选项:");
-            println!("  -c, --config <路径>     指定配置文件路径");
-            println!("  --import-ssh           显示 SSH 配置导入界面");
-            println!("  -C, <目标>              快速连接到指定配置");
-            println!("  -h, --help             显示帮助信息");
-            println!();
-            println!("快速连接示例:");
-            println!("  mssh 1                    # 连接到编号为 1 的配置");
-            println!("  mssh test-server          # 连接到别名为 test-server 的配置");
-            println!("  mssh -C 1                 # 连接到编号为 1 的配置");
-            println!("  mssh -C test-server       # 连接到别名为 test-server 的配置");
-            println!();
-            println!("其他示例:");
-            println!("  mssh                      # 启动 TUI 界面");
-            println!("  mssh -c ~/my-config.toml  # 使用指定配置文件");
-            println!("  mssh --import-ssh         # 启动时导入 SSH 配置");
-            std::process::exit(0);
-        }
+    if args.get(1).map(String::as_str) == Some("proxy-connect") {
+        std::process::exit(proxy_connect::run(&args[2..]));
+    }
+
+    // 解析命令行参数，子命令和快速连接都不进入 TUI
+    let cli = Cli::parse();
+    if let Some(command) = cli.command {
+        std::process::exit(cli::run(command, cli.config));
+    }
+    if let Some(target) = cli.quick_connect.or(cli.target) {
+        std::process::exit(cli::connect(cli.config, Some(&target), &[]));
     }
 
     // 设置终端
@@ -84,28 +73,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }));
 
     // 创建应用
-    let mut app = App::new(config_path)?;
-
-    // 处理快速连接
-    if let Some(target) = quick_connect {
-        if let Err(e) = app.quick_connect(&target) {
-            eprintln!("连接失败: {e}");
-            std::process::exit(1);
-        }
-        return Ok(());
-    }
+    let (event_sender, event_receiver) = mpsc::channel();
+    let mut app = App::new(cli.config, event_sender)?;
 
-    // 处理无参数快速连接（第一个参数作为目标）
-    if args.len() > 1 && !args[1].starts_with('-') {
-        let target = &args[1];
-        if let Err(e) = app.quick_connect(target) {
-            eprintln!("连接失败: {e}");
-            std::process::exit(1);
-        }
-        return Ok(());
-    }
-
-    if import_ssh {
+    if cli.import_ssh {
         if let Err(e) = app.show_import_selection() {
             eprintln!("显示导入选择失败: {e}");
             std::process::exit(1);
@@ -116,7 +87,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    let res = run_app(&mut terminal, &mut app);
+    let res = run_app(&mut terminal, &mut app, &event_receiver);
 
     // 恢复终端
     disable_raw_mode()?;
@@ -132,26 +103,46 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 /// 运行应用程序主循环
 ///
+/// 按键在主线程中以带超时的 `event::poll` 读取，连接 SSH 时不会有其他线程抢读终端输入；
+/// 后台任务通过事件通道推送结果，没有按键时每隔 `TICK_RATE` 处理一次并重新绘制。
+///
 /// # 参数
 /// - `terminal`: 终端实例
 /// - `app`: 应用状态
+/// - `events`: 后台任务的事件通道
 ///
 /// # 返回
 /// 返回 io::Result，成功为 Ok(())，失败为 Err
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
+    events: &Receiver<AppEvent>,
 ) -> io::Result<()> {
     loop {
+        while let Ok(event) = events.try_recv() {
+            EventHandler::handle_app_event(app, event);
+        }
+
         // 检查并清理过期消息
         app.check_message();
+        app.refresh_tunnels();
+        app.refresh_agent();
+        app.refresh_health();
+        app.check_config_file();
 
         terminal.draw(|f| ui::ui(f, app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if EventHandler::handle_key_event(app, key).unwrap_or(false) {
-                return Ok(());
-            }
+        if !event::poll(TICK_RATE)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        // Windows 上松开按键也会产生事件
+        if key.kind != KeyEventKind::Release
+            && EventHandler::handle_key_event(app, key).unwrap_or(false)
+        {
+            return Ok(());
         }
     }
 }
@@ -1,4 +1,8 @@
 use crate::config::SSHConfig;
+use crate::group_manager::GroupManager;
+use crate::key_manager::KeyManager;
+use crate::search_manager::SearchManager;
+use crate::sync_manager::{SyncEntry, SyncManager};
 use crate::ui::ScrollManager;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -9,6 +13,22 @@ pub enum AppMode {
     DeleteDialog,
     SelectImport,
     ProxyConfig,
+    ConfigError,
+    SyncDiff,
+    /// 在列表中输入搜索内容
+    Search,
+    /// 输入要移动到的分组
+    MoveToGroup,
+    /// 配置文件在外部被修改，且有尚未保存的修改
+    ConfigConflict,
+    /// 输入密码库主密码
+    VaultUnlock,
+    /// 连接前将密钥添加到 ssh-agent
+    AgentAdd,
+    /// 管理 ~/.ssh 中的密钥
+    Keys,
+    /// 保存主机前修复私钥的权限
+    KeyPermission,
 }
 
 #[derive(Debug, Clone)]
@@ -105,6 +125,14 @@ pub struct NavigationManager {
     pub scroll_manager: ScrollManager,
 
     pub import_manager: ImportManager,
+
+    pub sync_manager: SyncManager,
+
+    pub search_manager: SearchManager,
+
+    pub group_manager: GroupManager,
+
+    pub key_manager: KeyManager,
 }
 
 impl NavigationManager {
@@ -119,6 +147,10 @@ impl NavigationManager {
             focus: 0,
             scroll_manager: ScrollManager::new(),
             import_manager: ImportManager::new(),
+            sync_manager: SyncManager::new(),
+            search_manager: SearchManager::new(),
+            group_manager: GroupManager::new(),
+            key_manager: KeyManager::new(),
         }
     }
 
@@ -130,18 +162,20 @@ impl NavigationManager {
         self.mode = mode;
     }
 
-    /// 检查是否为列表模式
+    /// 检查是否为列表模式（包括正在输入搜索内容）
     ///
     /// # 返回
     /// 返回 true 表示是列表模式，false 表示不是
     pub fn is_list_mode(&self) -> bool {
-        matches!(self.mode, AppMode::List)
+        matches!(self.mode, AppMode::List | AppMode::Search)
     }
 
     /// 返回到列表模式
     pub fn return_to_list(&mut self) {
         self.mode = AppMode::List;
         self.import_manager.clear();
+        self.sync_manager.clear();
+        self.key_manager.close_dialog();
     }
 
     /// 移动到下一个项目
@@ -241,4 +275,13 @@ impl NavigationManager {
         self.import_manager.clear();
         self.mode = AppMode::List;
     }
+
+    /// 开始同步流程
+    ///
+    /// # 参数
+    /// - `entries`: 同步差异列表
+    pub fn start_sync(&mut self, entries: Vec<SyncEntry>) {
+        self.sync_manager.set_entries(entries);
+        self.mode = AppMode::SyncDiff;
+    }
 }
@@ -1,10 +1,14 @@
+use crate::config::SortMode;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ProxyType {
     None,
     Socks5,
     Http,
+    /// 经由其他 SSH 配置组成的跳板机链连接（ProxyJump）
+    Jump,
 }
 
 impl Default for ProxyType {
@@ -29,12 +33,24 @@ pub struct ProxyConfig {
     pub username: Option<String>,
     #[serde(default)]
     pub password: Option<String>,
+    /// 跳板机链，按连接顺序引用其他 SSH 配置的别名
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jump_hosts: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GlobalConfig {
     #[serde(default)]
     pub proxy: ProxyConfig,
+    /// 主机列表的排序方式
+    #[serde(default, skip_serializing_if = "SortMode::is_manual")]
+    pub sort_mode: SortMode,
+    /// 定时检测主机可达性的间隔（秒），不设置时只在按 Ctrl+R 时检测
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe_interval_secs: Option<u64>,
+    /// 连接前将密钥添加到 ssh-agent 时默认的有效期（分钟）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_lifetime_mins: Option<u64>,
 }
 
 impl ProxyConfig {
@@ -43,34 +59,112 @@ impl ProxyConfig {
     /// # 返回
     /// 返回 true 表示代理已启用，false 表示未启用
     pub fn is_enabled(&self) -> bool {
-        self.proxy_type != ProxyType::None && !self.host.is_empty()
+        match self.proxy_type {
+            ProxyType::None => false,
+            ProxyType::Jump => !self.jump_hosts.is_empty(),
+            ProxyType::Socks5 | ProxyType::Http => !self.host.is_empty(),
+        }
+    }
+
+    /// 获取代理端口，未设置时使用代理类型的默认端口
+    ///
+    /// # 返回
+    /// 返回代理端口，跳板机或未启用代理时返回 None
+    pub fn effective_port(&self) -> Option<u16> {
+        match self.proxy_type {
+            ProxyType::Socks5 => Some(self.port.unwrap_or(1080)),
+            ProxyType::Http => Some(self.port.unwrap_or(8080)),
+            ProxyType::None | ProxyType::Jump => None,
+        }
+    }
+
+    /// 检查代理是否需要认证
+    ///
+    /// # 返回
+    /// 返回 true 表示配置了用户名
+    pub fn has_credentials(&self) -> bool {
+        self.username
+            .as_deref()
+            .is_some_and(|user| !user.is_empty())
     }
 
     /// 获取 SSH 代理命令
     ///
+    /// 代理握手由内置的 `mssh proxy-connect` 完成，不依赖特定版本的 nc。
+    /// 密码通过环境变量传递，不会出现在命令行中；从导出的 OpenSSH 配置直接连接时，
+    /// proxy-connect 按 `-c` 指定的配置文件查找密码。
+    ///
+    /// # 参数
+    /// - `config_path`: 代理所在的 mssh 配置文件
+    ///
     /// # 返回
-    /// 返回代理命令字符串，如果代理未启用则返回 None
-    pub fn get_ssh_proxy_command(&self) -> Option<String> {
+    /// 返回代理命令字符串，如果代理未启用或为跳板机则返回 None
+    pub fn get_ssh_proxy_command(&self, config_path: &Path) -> Option<String> {
         if !self.is_enabled() {
             return None;
         }
 
-        let port = self.port.unwrap_or(match self.proxy_type {
-            ProxyType::Socks5 => 1080,
-            ProxyType::Http => 8080,
-            ProxyType::None => return None,
-        });
+        let port = self.effective_port()?;
+        let proxy_type = match self.proxy_type {
+            ProxyType::Socks5 => "socks5",
+            ProxyType::Http => "http",
+            ProxyType::None | ProxyType::Jump => return None,
+        };
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
 
-        match self.proxy_type {
-            ProxyType::Socks5 => {
-                // 使用 nc 通过 SOCKS5 代理连接
-                Some(format!("nc -X 5 -x {}:{} %h %p", self.host, port))
-            }
-            ProxyType::Http => {
-                // 使用 nc 通过 HTTP 代理连接
-                Some(format!("nc -X connect -x {}:{} %h %p", self.host, port))
-            }
-            ProxyType::None => None,
+        // 指向当前运行的 mssh，获取失败时依赖 PATH 中的 mssh
+        let exe = std::env::current_exe()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|_| "mssh".to_string());
+
+        // ssh 可能在其他目录中执行代理命令，相对路径需要转换为绝对路径
+        let config_path = if config_path.is_relative() {
+            std::env::current_dir()
+                .map(|dir| dir.join(config_path))
+                .unwrap_or_else(|_| config_path.to_path_buf())
+        } else {
+            config_path.to_path_buf()
+        };
+
+        let mut command = format!(
+            "{} proxy-connect -c {} --type {proxy_type} --proxy {}",
+            shell_quote(&exe),
+            shell_quote(&config_path.to_string_lossy()),
+            shell_quote(&format!("{host}:{port}"))
+        );
+        if let Some(user) = self.username.as_deref().filter(|user| !user.is_empty()) {
+            command.push_str(&format!(" --user {}", shell_quote(user)));
         }
+        command.push_str(" %h %p");
+        Some(command)
+    }
+}
+
+/// 为代理命令中的参数添加引号
+///
+/// OpenSSH 通过 shell 执行 ProxyCommand，包含特殊字符的参数需要加引号；
+/// `%` 会被 OpenSSH 当作占位符展开，需要转义为 `%%`。
+///
+/// # 参数
+/// - `value`: 参数值
+///
+/// # 返回
+/// 返回可安全放入命令行的参数
+pub(crate) fn shell_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@[]+=,".contains(c));
+    let value = value.replace('%', "%%");
+    if safe {
+        value
+    } else if cfg!(windows) {
+        format!("\"{value}\"")
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
     }
 }
@@ -0,0 +1,849 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::io;
+use std::net::IpAddr;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::config::ConfigManager;
+use crate::proxy::{ProxyConfig, ProxyType};
+use crate::secret::SecretRef;
+use crate::vault::{self, Vault};
+
+/// 传递代理密码的环境变量，避免密码出现在进程参数中
+pub const PASSWORD_ENV: &str = "MSSH_PROXY_PASSWORD";
+
+/// HTTP 代理响应头的最大长度
+const MAX_HTTP_HEADER: usize = 16 * 1024;
+
+/// proxy-connect 子命令的参数
+#[derive(Debug, Clone)]
+struct ProxyConnectArgs {
+    proxy_type: ProxyType,
+    proxy_host: String,
+    proxy_port: u16,
+    username: Option<String>,
+    target_host: String,
+    target_port: u16,
+    /// 查找代理密码的 mssh 配置文件，未指定时使用默认路径
+    config_path: Option<String>,
+}
+
+/// 执行 proxy-connect 子命令
+///
+/// 作为 OpenSSH 的 ProxyCommand 使用：经代理连接到目标主机后，
+/// 在标准输入输出与连接之间双向转发数据。
+///
+/// # 参数
+/// - `args`: 子命令之后的命令行参数
+///
+/// # 返回
+/// 返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("mssh proxy-connect: {e}");
+            eprintln!(
+                "用法: mssh proxy-connect [-c <配置文件>] --type socks5|http --proxy <主机:端口> [--user <用户名>] <目标主机> <目标端口>"
+            );
+            return 2;
+        }
+    };
+
+    let password = std::env::var(PASSWORD_ENV).ok().or_else(|| {
+        args.username.as_deref().and_then(|user| {
+            lookup_password(
+                args.config_path.clone(),
+                &args.proxy_host,
+                args.proxy_port,
+                user,
+            )
+        })
+    });
+
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("mssh proxy-connect: 无法创建运行时: {e}");
+            return 1;
+        }
+    };
+
+    let result = runtime.block_on(async {
+        let stream = connect(&args, password.as_deref()).await?;
+        pipe_stdio(stream).await
+    });
+    // 标准输入的读取在阻塞线程中进行，不等待它结束
+    runtime.shutdown_background();
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("mssh proxy-connect: 代理连接失败: {e}");
+            1
+        }
+    }
+}
+
+/// 解析 proxy-connect 的命令行参数
+///
+/// # 参数
+/// - `args`: 子命令之后的命令行参数
+///
+/// # 返回
+/// 返回解析结果，参数不合法时为错误信息
+fn parse_args(args: &[String]) -> Result<ProxyConnectArgs, String> {
+    let mut proxy_type = None;
+    let mut proxy = None;
+    let mut username = None;
+    let mut config_path = None;
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--type" => {
+                let value = iter.next().ok_or("--type 需要指定代理类型")?;
+                proxy_type = Some(match value.to_lowercase().as_str() {
+                    "socks5" => ProxyType::Socks5,
+                    "http" => ProxyType::Http,
+                    other => return Err(format!("不支持的代理类型: {other}")),
+                });
+            }
+            "--proxy" => proxy = Some(iter.next().ok_or("--proxy 需要指定代理地址")?),
+            "--user" => username = Some(iter.next().ok_or("--user 需要指定用户名")?.clone()),
+            "-c" | "--config" => {
+                config_path = Some(iter.next().ok_or("-c 需要指定配置文件")?.clone())
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let proxy_type = proxy_type.ok_or("缺少 --type 参数")?;
+    let (proxy_host, proxy_port) = split_host_port(proxy.ok_or("缺少 --proxy 参数")?)?;
+    let [target_host, target_port] = positional.as_slice() else {
+        return Err("需要指定目标主机和端口".to_string());
+    };
+    let target_port = target_port
+        .parse()
+        .map_err(|_| format!("无效的目标端口: {target_port}"))?;
+
+    Ok(ProxyConnectArgs {
+        proxy_type,
+        proxy_host,
+        proxy_port,
+        username,
+        target_host: target_host.to_string(),
+        target_port,
+        config_path,
+    })
+}
+
+/// 拆分 `主机:端口` 形式的地址，支持 `[IPv6]:端口`
+///
+/// # 参数
+/// - `value`: 地址字符串
+///
+/// # 返回
+/// 返回主机和端口
+pub fn split_host_port(value: &str) -> Result<(String, u16), String> {
+    let (host, port) = value
+        .rsplit_once(':')
+        .ok_or_else(|| format!("代理地址缺少端口: {value}"))?;
+    let port = port
+        .parse()
+        .map_err(|_| format!("无效的代理端口: {port}"))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(format!("代理地址缺少主机: {value}"));
+    }
+    Ok((host.to_string(), port))
+}
+
+/// 从 mssh 配置中查找代理密码
+///
+/// 用于从导出的 OpenSSH 配置直接连接的场景，此时 ssh 不会传递密码环境变量。
+///
+/// # 参数
+/// - `config_path`: 代理命令中 `-c` 指定的配置文件，可选
+/// - `host`: 代理主机
+/// - `port`: 代理端口
+/// - `username`: 代理用户名
+///
+/// # 返回
+/// 返回匹配代理的密码，找不到时为 None
+fn lookup_password(
+    config_path: Option<String>,
+    host: &str,
+    port: u16,
+    username: &str,
+) -> Option<String> {
+    let config_manager = ConfigManager::load(ConfigManager::resolve_path(config_path)).ok()?;
+    let matches = |proxy: &ProxyConfig| {
+        proxy.host == host
+            && proxy.effective_port() == Some(port)
+            && proxy.username.as_deref() == Some(username)
+    };
+
+    std::iter::once(&config_manager.global_config.proxy)
+        .chain(
+            config_manager
+                .configs
+                .iter()
+                .filter_map(|config| config.proxy.as_ref()),
+        )
+        .find(|proxy| matches(proxy))
+        .and_then(|proxy| proxy.password.as_deref())
+        .and_then(|password| {
+            // 密码保存在密码库中时在终端中输入主密码
+            let mut vault = Vault::new(config_manager.config_path());
+            if vault::secret_id(password).is_some() {
+                if let Err(e) = vault.unlock_from_terminal() {
+                    eprintln!("mssh proxy-connect: 无法解锁密码库: {e}");
+                    return None;
+                }
+            }
+            SecretRef::parse(password)
+                .resolve(&vault)
+                .map_err(|e| eprintln!("mssh proxy-connect: 无法读取代理密码: {e}"))
+                .ok()
+        })
+}
+
+/// 经代理连接到目标主机，供主机检测使用
+///
+/// # 参数
+/// - `proxy`: 代理配置，需为 SOCKS5 或 HTTP 代理
+/// - `host`: 目标主机
+/// - `port`: 目标端口
+///
+/// # 返回
+/// 返回已连接到目标主机的数据流
+pub async fn connect_via_proxy(
+    proxy: &ProxyConfig,
+    host: &str,
+    port: u16,
+) -> io::Result<TcpStream> {
+    let proxy_port = proxy
+        .effective_port()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "未指定代理类型"))?;
+    let args = ProxyConnectArgs {
+        proxy_type: proxy.proxy_type.clone(),
+        proxy_host: proxy.host.clone(),
+        proxy_port,
+        username: proxy.username.clone().filter(|user| !user.is_empty()),
+        target_host: host.to_string(),
+        target_port: port,
+        config_path: None,
+    };
+    connect(&args, proxy.password.as_deref()).await
+}
+
+/// 连接代理并完成握手
+///
+/// # 参数
+/// - `args`: 子命令参数
+/// - `password`: 代理密码，可选
+///
+/// # 返回
+/// 返回已连接到目标主机的数据流
+async fn connect(args: &ProxyConnectArgs, password: Option<&str>) -> io::Result<TcpStream> {
+    let mut stream = TcpStream::connect((args.proxy_host.as_str(), args.proxy_port))
+        .await
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("无法连接代理 {}:{}: {e}", args.proxy_host, args.proxy_port),
+            )
+        })?;
+    stream.set_nodelay(true)?;
+
+    let credentials = args
+        .username
+        .as_deref()
+        .map(|user| (user, password.unwrap_or_default()));
+
+    match args.proxy_type {
+        ProxyType::Socks5 => {
+            socks5_handshake(
+                &mut stream,
+                &args.target_host,
+                args.target_port,
+                credentials,
+            )
+            .await?
+        }
+        ProxyType::Http => {
+            http_connect_handshake(
+                &mut stream,
+                &args.target_host,
+                args.target_port,
+                credentials,
+            )
+            .await?
+        }
+        ProxyType::None | ProxyType::Jump => {
+            return Err(io::Error::new(io::ErrorKind::Other, "未指定代理类型"))
+        }
+    }
+
+    Ok(stream)
+}
+
+/// 执行 SOCKS5 握手（RFC 1928），支持用户名密码认证（RFC 1929）
+///
+/// # 参数
+/// - `stream`: 到代理服务器的连接
+/// - `host`: 目标主机
+/// - `port`: 目标端口
+/// - `credentials`: 用户名和密码，可选
+///
+/// # 返回
+/// 返回 io::Result，握手成功为 Ok(())
+pub async fn socks5_handshake<S>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    credentials: Option<(&str, &str)>,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let greeting: &[u8] = if credentials.is_some() {
+        &[0x05, 0x02, 0x00, 0x02]
+    } else {
+        &[0x05, 0x01, 0x00]
+    };
+    stream.write_all(greeting).await?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != 0x05 {
+        return Err(protocol_error("代理服务器不是 SOCKS5 协议"));
+    }
+
+    match (reply[1], credentials) {
+        (0x00, _) => {}
+        (0x02, Some((user, pass))) => {
+            if user.len() > 255 || pass.len() > 255 {
+                return Err(protocol_error("SOCKS5 用户名或密码过长"));
+            }
+            let mut request = vec![0x01, user.len() as u8];
+            request.extend_from_slice(user.as_bytes());
+            request.push(pass.len() as u8);
+            request.extend_from_slice(pass.as_bytes());
+            stream.write_all(&request).await?;
+
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await?;
+            if status[1] != 0x00 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "SOCKS5 认证失败，请检查用户名和密码",
+                ));
+            }
+        }
+        (0x02 | 0xFF, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "SOCKS5 代理要求认证，请配置用户名和密码",
+            ))
+        }
+        _ => return Err(protocol_error("SOCKS5 代理不支持可用的认证方式")),
+    }
+
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(protocol_error("目标主机名过长"));
+            }
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await?;
+    if header[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("SOCKS5 代理拒绝连接: {}", socks5_reply_message(header[1])),
+        ));
+    }
+
+    // 读取并丢弃代理返回的绑定地址
+    let address_len = match header[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            len[0] as usize
+        }
+        _ => return Err(protocol_error("SOCKS5 响应地址类型无效")),
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound).await?;
+
+    Ok(())
+}
+
+/// 执行 HTTP CONNECT 握手，支持 Basic 认证
+///
+/// # 参数
+/// - `stream`: 到代理服务器的连接
+/// - `host`: 目标主机
+/// - `port`: 目标端口
+/// - `credentials`: 用户名和密码，可选
+///
+/// # 返回
+/// 返回 io::Result，握手成功为 Ok(())
+pub async fn http_connect_handshake<S>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    credentials: Option<(&str, &str)>,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let authority = if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    };
+
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some((user, pass)) = credentials {
+        let token = STANDARD.encode(format!("{user}:{pass}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // 逐字节读取响应头，避免读走隧道中的数据
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_HTTP_HEADER {
+            return Err(protocol_error("HTTP 代理响应头过长"));
+        }
+        let mut byte = [0u8; 1];
+        if stream.read(&mut byte).await? == 0 {
+            return Err(protocol_error("HTTP 代理提前关闭了连接"));
+        }
+        response.push(byte[0]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    let is_success =
+        status.len() == 3 && status.starts_with('2') && status.bytes().all(|b| b.is_ascii_digit());
+    match status {
+        _ if is_success => Ok(()),
+        "407" => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            if credentials.is_some() {
+                "HTTP 代理认证失败，请检查用户名和密码"
+            } else {
+                "HTTP 代理要求认证，请配置用户名和密码"
+            },
+        )),
+        _ => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("HTTP 代理拒绝连接: {status_line}"),
+        )),
+    }
+}
+
+/// 在标准输入输出与连接之间双向转发数据
+///
+/// # 参数
+/// - `stream`: 已建立的隧道连接
+///
+/// # 返回
+/// 返回 io::Result，任一方向结束后返回
+async fn pipe_stdio(stream: TcpStream) -> io::Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut stdin = tokio::io::stdin();
+    let mut stdout = tokio::io::stdout();
+
+    let upload = async {
+        tokio::io::copy(&mut stdin, &mut writer).await?;
+        writer.shutdown().await
+    };
+    let download = async {
+        tokio::io::copy(&mut reader, &mut stdout).await?;
+        stdout.flush().await
+    };
+
+    // 远端关闭后立即结束；标准输入结束时只关闭写方向，继续接收剩余数据
+    tokio::pin!(upload, download);
+    tokio::select! {
+        result = &mut download => result,
+        result = &mut upload => {
+            result?;
+            download.await
+        }
+    }
+}
+
+/// 构造协议错误
+///
+/// # 参数
+/// - `message`: 错误信息
+///
+/// # 返回
+/// 返回 io::Error
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// 获取 SOCKS5 响应码的说明
+///
+/// # 参数
+/// - `code`: 响应码
+///
+/// # 返回
+/// 返回响应码对应的说明
+fn socks5_reply_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "代理服务器内部错误",
+        0x02 => "规则不允许该连接",
+        0x03 => "网络不可达",
+        0x04 => "主机不可达",
+        0x05 => "连接被拒绝",
+        0x06 => "TTL 过期",
+        0x07 => "不支持的命令",
+        0x08 => "不支持的地址类型",
+        _ => "未知错误",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tokio::io::{duplex, DuplexStream};
+
+    /// 代理存根握手完成后发送的数据，用于确认握手没有读走隧道中的内容
+    const BANNER: &[u8] = b"SSH-2.0-stub\r\n";
+
+    async fn read_vec(stream: &mut DuplexStream, len: usize) -> Vec<u8> {
+        let mut buf = vec![0u8; len];
+        stream.read_exact(&mut buf).await.unwrap();
+        buf
+    }
+
+    async fn read_banner(stream: &mut DuplexStream) {
+        assert_eq!(read_vec(stream, BANNER.len()).await, BANNER);
+    }
+
+    /// 读取 HTTP 请求头，返回以 `\r\n` 分隔的各行
+    async fn read_http_request(stream: &mut DuplexStream) -> Vec<String> {
+        let mut request = Vec::new();
+        while !request.ends_with(b"\r\n\r\n") {
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).await.unwrap();
+            request.push(byte[0]);
+        }
+        String::from_utf8(request)
+            .unwrap()
+            .split("\r\n")
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn socks5_connects_to_domain_without_auth() {
+        let (mut client, mut server) = duplex(1024);
+        let stub = tokio::spawn(async move {
+            assert_eq!(read_vec(&mut server, 3).await, [0x05, 0x01, 0x00]);
+            server.write_all(&[0x05, 0x00]).await.unwrap();
+
+            let mut expected = vec![0x05, 0x01, 0x00, 0x03, 11];
+            expected.extend_from_slice(b"example.com");
+            expected.extend_from_slice(&22u16.to_be_bytes());
+            assert_eq!(read_vec(&mut server, expected.len()).await, expected);
+
+            server
+                .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0x1F, 0x90])
+                .await
+                .unwrap();
+            server.write_all(BANNER).await.unwrap();
+        });
+
+        socks5_handshake(&mut client, "example.com", 22, None)
+            .await
+            .unwrap();
+        read_banner(&mut client).await;
+        stub.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn socks5_authenticates_with_username_and_password() {
+        let (mut client, mut server) = duplex(1024);
+        let stub = tokio::spawn(async move {
+            assert_eq!(read_vec(&mut server, 4).await, [0x05, 0x02, 0x00, 0x02]);
+            server.write_all(&[0x05, 0x02]).await.unwrap();
+
+            let mut auth = vec![0x01, 5];
+            auth.extend_from_slice(b"alice");
+            auth.push(6);
+            auth.extend_from_slice(b"secret");
+            assert_eq!(read_vec(&mut server, auth.len()).await, auth);
+            server.write_all(&[0x01, 0x00]).await.unwrap();
+
+            assert_eq!(
+                read_vec(&mut server, 10).await,
+                [0x05, 0x01, 0x00, 0x01, 10, 0, 0, 1, 0, 22]
+            );
+            // 绑定地址为域名形式
+            server
+                .write_all(&[0x05, 0x00, 0x00, 0x03, 4, b'h', b'o', b's', b't', 0, 80])
+                .await
+                .unwrap();
+            server.write_all(BANNER).await.unwrap();
+        });
+
+        socks5_handshake(&mut client, "10.0.0.1", 22, Some(("alice", "secret")))
+            .await
+            .unwrap();
+        read_banner(&mut client).await;
+        stub.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn socks5_sends_ipv6_targets_as_addresses() {
+        let (mut client, mut server) = duplex(1024);
+        let stub = tokio::spawn(async move {
+            read_vec(&mut server, 3).await;
+            server.write_all(&[0x05, 0x00]).await.unwrap();
+
+            let request = read_vec(&mut server, 22).await;
+            assert_eq!(request[..4], [0x05, 0x01, 0x00, 0x04]);
+            assert_eq!(
+                request[4..20],
+                "::1".parse::<std::net::Ipv6Addr>().unwrap().octets()
+            );
+            assert_eq!(request[20..], 2222u16.to_be_bytes());
+            server
+                .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+        });
+
+        socks5_handshake(&mut client, "::1", 2222, None)
+            .await
+            .unwrap();
+        stub.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn socks5_reports_rejected_credentials() {
+        let (mut client, mut server) = duplex(1024);
+        tokio::spawn(async move {
+            read_vec(&mut server, 4).await;
+            server.write_all(&[0x05, 0x02]).await.unwrap();
+            read_vec(&mut server, 2 + 5 + 1 + 5).await;
+            server.write_all(&[0x01, 0x01]).await.unwrap();
+        });
+
+        let err = socks5_handshake(&mut client, "example.com", 22, Some(("alice", "wrong")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn socks5_requires_credentials_when_proxy_asks_for_them() {
+        let (mut client, mut server) = duplex(1024);
+        tokio::spawn(async move {
+            read_vec(&mut server, 3).await;
+            server.write_all(&[0x05, 0xFF]).await.unwrap();
+        });
+
+        let err = socks5_handshake(&mut client, "example.com", 22, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn socks5_reports_refused_connections() {
+        let (mut client, mut server) = duplex(1024);
+        tokio::spawn(async move {
+            read_vec(&mut server, 3).await;
+            server.write_all(&[0x05, 0x00]).await.unwrap();
+            read_vec(&mut server, 5 + 11 + 2).await;
+            server.write_all(&[0x05, 0x05, 0x00, 0x01]).await.unwrap();
+        });
+
+        let err = socks5_handshake(&mut client, "example.com", 22, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+        assert!(err.to_string().contains("连接被拒绝"));
+    }
+
+    #[tokio::test]
+    async fn http_connect_sends_basic_auth_and_keeps_tunnel_data() {
+        let (mut client, mut server) = duplex(1024);
+        let stub = tokio::spawn(async move {
+            let request = read_http_request(&mut server).await;
+            assert_eq!(
+                request,
+                [
+                    "CONNECT example.com:22 HTTP/1.1",
+                    "Host: example.com:22",
+                    "Proxy-Authorization: Basic YWxpY2U6c2VjcmV0",
+                ]
+            );
+            server
+                .write_all(b"HTTP/1.1 200 Connection established\r\nProxy-Agent: stub\r\n\r\n")
+                .await
+                .unwrap();
+            server.write_all(BANNER).await.unwrap();
+        });
+
+        http_connect_handshake(&mut client, "example.com", 22, Some(("alice", "secret")))
+            .await
+            .unwrap();
+        read_banner(&mut client).await;
+        stub.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn http_connect_accepts_any_2xx_status() {
+        let (mut client, mut server) = duplex(1024);
+        let stub = tokio::spawn(async move {
+            let request = read_http_request(&mut server).await;
+            assert_eq!(request[0], "CONNECT [::1]:22 HTTP/1.1");
+            server
+                .write_all(b"HTTP/1.0 204 No Content\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        http_connect_handshake(&mut client, "::1", 22, None)
+            .await
+            .unwrap();
+        stub.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn http_connect_reports_auth_and_refusal() {
+        for (response, kind) in [
+            (
+                "HTTP/1.1 407 Proxy Authentication Required\r\n\r\n",
+                io::ErrorKind::PermissionDenied,
+            ),
+            (
+                "HTTP/1.1 403 Forbidden\r\n\r\n",
+                io::ErrorKind::ConnectionRefused,
+            ),
+            (
+                "HTTP/1.1 2000 Odd\r\n\r\n",
+                io::ErrorKind::ConnectionRefused,
+            ),
+        ] {
+            let (mut client, mut server) = duplex(1024);
+            tokio::spawn(async move {
+                read_http_request(&mut server).await;
+                server.write_all(response.as_bytes()).await.unwrap();
+            });
+
+            let err = http_connect_handshake(&mut client, "example.com", 22, None)
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), kind, "{response}");
+        }
+    }
+
+    #[tokio::test]
+    async fn http_connect_fails_when_proxy_closes_early() {
+        let (mut client, server) = duplex(1024);
+        drop(server);
+
+        let err = http_connect_handshake(&mut client, "example.com", 22, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            io::ErrorKind::InvalidData | io::ErrorKind::BrokenPipe
+        ));
+    }
+
+    #[test]
+    fn parses_config_path_and_targets() {
+        let args: Vec<String> = [
+            "-c",
+            "/tmp/other.toml",
+            "--type",
+            "http",
+            "--proxy",
+            "[::1]:3128",
+            "--user",
+            "alice",
+            "example.com",
+            "22",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let parsed = parse_args(&args).unwrap();
+        assert_eq!(parsed.config_path.as_deref(), Some("/tmp/other.toml"));
+        assert_eq!(parsed.proxy_type, ProxyType::Http);
+        assert_eq!(parsed.proxy_host, "::1");
+        assert_eq!(parsed.proxy_port, 3128);
+        assert_eq!(parsed.username.as_deref(), Some("alice"));
+        assert_eq!(parsed.target_host, "example.com");
+        assert_eq!(parsed.target_port, 22);
+    }
+
+    #[test]
+    fn proxy_command_passes_the_config_path() {
+        let proxy = ProxyConfig {
+            proxy_type: ProxyType::Socks5,
+            host: "proxy.local".to_string(),
+            port: Some(1080),
+            ..ProxyConfig::default()
+        };
+
+        let command = proxy
+            .get_ssh_proxy_command(Path::new("/etc/mssh/other.toml"))
+            .unwrap();
+        assert!(
+            command.contains(" proxy-connect -c /etc/mssh/other.toml --type socks5 "),
+            "{command}"
+        );
+
+        // 相对路径转换为绝对路径，ssh 可能在其他目录中执行代理命令
+        let relative = proxy
+            .get_ssh_proxy_command(Path::new("other.toml"))
+            .unwrap();
+        let expected = std::env::current_dir().unwrap().join("other.toml");
+        assert!(
+            relative.contains(&*expected.to_string_lossy()),
+            "{relative}"
+        );
+    }
+}
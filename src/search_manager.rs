@@ -0,0 +1,369 @@
+use crate::config::{tag_matches, SSHConfig};
+use std::cmp::Reverse;
+
+/// 每个匹配字符的基础得分
+const SCORE_MATCH: i64 = 16;
+/// 与上一个匹配字符相邻时的加分
+const BONUS_CONSECUTIVE: i64 = 24;
+/// 匹配字符位于单词开头（字段开头或分隔符之后）时的加分
+const BONUS_BOUNDARY: i64 = 12;
+/// 别名匹配的额外加分，别名命中的主机排在地址、用户命中的主机之前
+const BONUS_ALIAS: i64 = 20;
+/// 视为单词分隔符的字符
+const SEPARATORS: &[char] = &['-', '_', '.', ' ', '@', '/', ':'];
+
+/// 一个主机的搜索结果
+#[derive(Debug, Clone, Default)]
+pub struct SearchMatch {
+    /// 主机在配置列表中的下标
+    pub index: usize,
+    pub score: i64,
+    /// 别名中命中的字符位置
+    pub alias: Vec<usize>,
+    /// 地址中命中的字符位置
+    pub address: Vec<usize>,
+    /// 用户名中命中的字符位置
+    pub user: Vec<usize>,
+    /// 命中的标签下标
+    pub tags: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchManager {
+    pub query: String,
+}
+
+impl SearchManager {
+    /// 创建新的搜索管理器
+    ///
+    /// # 返回
+    /// 返回初始化的搜索管理器
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+        }
+    }
+
+    /// 检查是否正在过滤列表
+    ///
+    /// # 返回
+    /// 返回 true 表示搜索内容不为空
+    pub fn is_filtering(&self) -> bool {
+        !self.query.trim().is_empty()
+    }
+
+    /// 追加搜索字符
+    ///
+    /// # 参数
+    /// - `c`: 输入的字符
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+    }
+
+    /// 删除最后一个搜索字符
+    pub fn pop(&mut self) {
+        self.query.pop();
+    }
+
+    /// 清空搜索内容
+    pub fn clear(&mut self) {
+        self.query.clear();
+    }
+
+    /// 按搜索内容过滤并排序主机
+    ///
+    /// 搜索内容按空格分为多个关键字，每个关键字都需要模糊匹配别名、地址、用户名或标签之一；
+    /// 以 `#` 开头的关键字只按标签过滤，参见 `tag_matches`。搜索内容为空时按原顺序返回所有主机。
+    ///
+    /// # 参数
+    /// - `configs`: SSH 配置列表
+    ///
+    /// # 返回
+    /// 返回按得分从高到低排列的搜索结果
+    pub fn filter(&self, configs: &[SSHConfig]) -> Vec<SearchMatch> {
+        let terms: Vec<Vec<char>> = self
+            .query
+            .split_whitespace()
+            .map(|term| term.to_lowercase().chars().collect())
+            .collect();
+
+        let mut matches: Vec<SearchMatch> = configs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, config)| match_config(index, config, &terms))
+            .collect();
+        // 稳定排序，得分相同的主机保持原顺序
+        matches.sort_by_key(|m| Reverse(m.score));
+        matches
+    }
+}
+
+/// 用所有关键字匹配一个主机
+///
+/// # 参数
+/// - `index`: 主机在配置列表中的下标
+/// - `config`: SSH 配置
+/// - `terms`: 小写的关键字列表
+///
+/// # 返回
+/// 所有关键字都命中时返回搜索结果，否则为 None
+fn match_config(index: usize, config: &SSHConfig, terms: &[Vec<char>]) -> Option<SearchMatch> {
+    let mut result = SearchMatch {
+        index,
+        ..SearchMatch::default()
+    };
+
+    for term in terms {
+        if term.first() == Some(&'#') {
+            // 只输入 `#` 时匹配所有带标签的主机
+            let filter: String = term[1..].iter().collect();
+            let matched: Vec<usize> = (0..config.tags.len())
+                .filter(|&i| filter.is_empty() || tag_matches(&config.tags[i], &filter))
+                .collect();
+            if matched.is_empty() {
+                return None;
+            }
+            result.tags.extend(matched);
+            continue;
+        }
+
+        let alias = fuzzy_match(term, &config.alias).map(|(score, pos)| (score + BONUS_ALIAS, pos));
+        let address = fuzzy_match(term, &config.address);
+        let user = config
+            .user
+            .as_deref()
+            .and_then(|user| fuzzy_match(term, user));
+
+        // 标签不显示在列表中，只记录命中的标签，不记录字符位置
+        let tag = config
+            .tags
+            .iter()
+            .enumerate()
+            .filter_map(|(i, tag)| fuzzy_match(term, tag).map(|(score, _)| (score, vec![i])))
+            .max_by_key(|(score, _)| *score);
+
+        let best = [&alias, &address, &user, &tag]
+            .into_iter()
+            .enumerate()
+            .filter_map(|(field, m)| m.as_ref().map(|(score, _)| (field, *score)))
+            .max_by_key(|(_, score)| *score)?;
+
+        result.score += best.1;
+        let (target, positions) = match best.0 {
+            0 => (&mut result.alias, alias),
+            1 => (&mut result.address, address),
+            2 => (&mut result.user, user),
+            _ => (&mut result.tags, tag),
+        };
+        if let Some((_, positions)) = positions {
+            target.extend(positions);
+        }
+    }
+
+    for positions in [
+        &mut result.alias,
+        &mut result.address,
+        &mut result.user,
+        &mut result.tags,
+    ] {
+        positions.sort_unstable();
+        positions.dedup();
+    }
+    Some(result)
+}
+
+/// 模糊匹配：关键字的字符按顺序出现在文本中即视为命中，不区分大小写
+///
+/// 从每个可能的起点贪心匹配，取得分最高的一组位置。连续命中、命中单词开头的字符得分更高，
+/// 字符之间的间隔和靠后的起点会扣分。
+///
+/// # 参数
+/// - `pattern`: 小写的关键字字符
+/// - `text`: 被匹配的文本
+///
+/// # 返回
+/// 命中时返回得分和命中的字符位置，否则为 None
+pub fn fuzzy_match(pattern: &[char], text: &str) -> Option<(i64, Vec<usize>)> {
+    let first = *pattern.first()?;
+    let chars: Vec<char> = text
+        .chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect();
+
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in (0..chars.len()).filter(|&i| chars[i] == first) {
+        let mut positions = vec![start];
+        let mut next = start + 1;
+        for &c in &pattern[1..] {
+            let offset = chars[next..].iter().position(|&t| t == c);
+            match offset {
+                Some(offset) => {
+                    positions.push(next + offset);
+                    next += offset + 1;
+                }
+                None => break,
+            }
+        }
+        if positions.len() < pattern.len() {
+            // 后面的起点可用的字符更少，同样无法命中
+            break;
+        }
+
+        let score = score_positions(&chars, &positions);
+        if best.as_ref().map_or(true, |(s, _)| score > *s) {
+            best = Some((score, positions));
+        }
+    }
+    best
+}
+
+/// 计算一组命中位置的得分
+///
+/// # 参数
+/// - `chars`: 被匹配文本的小写字符
+/// - `positions`: 命中的字符位置
+///
+/// # 返回
+/// 返回得分
+fn score_positions(chars: &[char], positions: &[usize]) -> i64 {
+    let mut score = -(positions[0].min(10) as i64);
+    for (i, &pos) in positions.iter().enumerate() {
+        score += SCORE_MATCH;
+        if pos == 0 || SEPARATORS.contains(&chars[pos - 1]) {
+            score += BONUS_BOUNDARY;
+        }
+        if i > 0 {
+            let gap = pos - positions[i - 1] - 1;
+            if gap == 0 {
+                score += BONUS_CONSECUTIVE;
+            } else {
+                score -= gap.min(8) as i64;
+            }
+        }
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(alias: &str, address: &str, user: Option<&str>, tags: &[&str]) -> SSHConfig {
+        SSHConfig {
+            alias: alias.to_string(),
+            address: address.to_string(),
+            port: None,
+            user: user.map(str::to_string),
+            key: None,
+            group: None,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy: None,
+            use_global_proxy: false,
+        }
+    }
+
+    fn pattern(term: &str) -> Vec<char> {
+        term.chars().collect()
+    }
+
+    fn search(query: &str, configs: &[SSHConfig]) -> Vec<SearchMatch> {
+        let mut manager = SearchManager::new();
+        query.chars().for_each(|c| manager.push(c));
+        manager.filter(configs)
+    }
+
+    fn aliases<'a>(matches: &[SearchMatch], configs: &'a [SSHConfig]) -> Vec<&'a str> {
+        matches
+            .iter()
+            .map(|m| configs[m.index].alias.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn fuzzy_match_prefers_consecutive_and_boundary_hits() {
+        let (consecutive, _) = fuzzy_match(&pattern("db"), "db-main").unwrap();
+        let (scattered, _) = fuzzy_match(&pattern("db"), "dashboard").unwrap();
+        assert!(consecutive > scattered);
+
+        let (boundary, _) = fuzzy_match(&pattern("web"), "prod-web").unwrap();
+        let (inner, _) = fuzzy_match(&pattern("web"), "prodweb").unwrap();
+        assert!(boundary > inner);
+
+        assert!(fuzzy_match(&pattern("xyz"), "prod-web").is_none());
+        assert!(fuzzy_match(&pattern(""), "prod-web").is_none());
+    }
+
+    #[test]
+    fn fuzzy_match_returns_best_positions() {
+        // 第一个 a 之后没有相邻的 b，选择后面连续命中的一组
+        assert_eq!(fuzzy_match(&pattern("ab"), "axb ab").unwrap().1, [4, 5]);
+        assert_eq!(
+            fuzzy_match(&pattern("web"), "PROD-WEB-01").unwrap().1,
+            [5, 6, 7]
+        );
+        // 位置按字符计算，不是字节
+        assert_eq!(fuzzy_match(&pattern("db"), "数据库-db").unwrap().1, [4, 5]);
+    }
+
+    #[test]
+    fn alias_matches_rank_before_address_matches() {
+        let configs = [
+            host("gateway", "db.example.com", None, &[]),
+            host("db-main", "10.0.0.2", None, &[]),
+            host("cache", "10.0.0.3", None, &[]),
+        ];
+        let matches = search("db", &configs);
+        assert_eq!(aliases(&matches, &configs), ["db-main", "gateway"]);
+        assert_eq!(matches[0].alias, [0, 1]);
+        assert!(matches[1].alias.is_empty());
+        assert_eq!(matches[1].address, [0, 1]);
+    }
+
+    #[test]
+    fn every_term_must_match() {
+        let configs = [
+            host("prod-web", "10.0.0.1", Some("deploy"), &[]),
+            host("prod-db", "10.0.0.2", Some("postgres"), &[]),
+            host("stage-web", "10.0.1.1", Some("deploy"), &[]),
+        ];
+        let matches = search("prod web", &configs);
+        assert_eq!(aliases(&matches, &configs), ["prod-web"]);
+        // 两个关键字的命中位置合并后用于高亮
+        assert_eq!(matches[0].alias, [0, 1, 2, 3, 5, 6, 7]);
+
+        let matches = search("web deploy", &configs);
+        assert_eq!(aliases(&matches, &configs), ["prod-web", "stage-web"]);
+        assert_eq!(matches[0].user, [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn hash_terms_filter_by_tag() {
+        let configs = [
+            host("web", "10.0.0.1", None, &["env:prod", "team:web"]),
+            host("db", "10.0.0.2", None, &["env:stage"]),
+            host("cache", "10.0.0.3", None, &[]),
+        ];
+        assert_eq!(aliases(&search("#env", &configs), &configs), ["web", "db"]);
+        assert_eq!(aliases(&search("#ENV:prod", &configs), &configs), ["web"]);
+        assert_eq!(aliases(&search("#", &configs), &configs), ["web", "db"]);
+        assert!(search("#prod", &configs).is_empty());
+
+        let matches = search("#team w", &configs);
+        assert_eq!(aliases(&matches, &configs), ["web"]);
+        assert_eq!(matches[0].tags, [1]);
+        assert_eq!(matches[0].alias, [0]);
+    }
+
+    #[test]
+    fn empty_query_keeps_original_order() {
+        let configs = [
+            host("b", "10.0.0.1", None, &[]),
+            host("a", "10.0.0.2", None, &[]),
+        ];
+        let manager = SearchManager::new();
+        assert!(!manager.is_filtering());
+        assert_eq!(aliases(&manager.filter(&configs), &configs), ["b", "a"]);
+    }
+}
@@ -0,0 +1,347 @@
+use crate::vault::{self, Vault, VaultError};
+use std::io;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// 从系统密钥环读取的引用前缀，如 `keyring:corp-proxy/alice`
+pub const KEYRING_PREFIX: &str = "keyring:";
+/// 从 `pass` 读取的引用前缀，如 `pass:work/proxy`
+pub const PASS_PREFIX: &str = "pass:";
+/// 从环境变量读取的引用前缀，如 `env:PROXY_PASSWORD`
+pub const ENV_PREFIX: &str = "env:";
+/// 执行命令读取的引用前缀，如 `cmd:op read op://work/proxy/password`
+pub const CMD_PREFIX: &str = "cmd:";
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error(transparent)]
+    Vault(#[from] VaultError),
+    #[error("密码引用 {0} 缺少名称")]
+    Empty(String),
+    #[error("keyring 引用的格式应为 keyring:服务/账户: {0}")]
+    InvalidKeyring(String),
+    #[error("当前系统不支持 keyring 引用，可改用 cmd: 调用其他工具")]
+    KeyringUnsupported,
+    #[error("环境变量 {0} 未设置")]
+    EnvNotSet(String),
+    #[error("无法运行 {program}: {source}")]
+    Spawn {
+        program: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{source_name} 执行失败: {message}")]
+    Command {
+        source_name: String,
+        message: String,
+    },
+    #[error("{0} 没有输出密码")]
+    NoOutput(String),
+}
+
+/// 配置中的密码，可以是明文或指向外部来源的引用
+///
+/// 引用在需要密码时才读取，配置文件中只保存引用本身。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretRef<'a> {
+    /// 明文密码
+    Plain(&'a str),
+    /// 密码库条目，`vault:<条目>`
+    Vault(&'a str),
+    /// 系统密钥环，`keyring:<服务>/<账户>`
+    Keyring(&'a str),
+    /// `pass` 中的条目，`pass:<路径>`
+    Pass(&'a str),
+    /// 环境变量，`env:<变量名>`
+    Env(&'a str),
+    /// 命令的标准输出，`cmd:<命令>`
+    Cmd(&'a str),
+}
+
+impl<'a> SecretRef<'a> {
+    /// 解析配置中的密码
+    ///
+    /// # 参数
+    /// - `value`: 配置中的密码
+    ///
+    /// # 返回
+    /// 返回对应的引用，没有可识别的前缀时为 `Plain`
+    pub fn parse(value: &'a str) -> Self {
+        if let Some(id) = vault::secret_id(value) {
+            Self::Vault(id)
+        } else if let Some(path) = value.strip_prefix(KEYRING_PREFIX) {
+            Self::Keyring(path)
+        } else if let Some(path) = value.strip_prefix(PASS_PREFIX) {
+            Self::Pass(path)
+        } else if let Some(name) = value.strip_prefix(ENV_PREFIX) {
+            Self::Env(name)
+        } else if let Some(command) = value.strip_prefix(CMD_PREFIX) {
+            Self::Cmd(command)
+        } else {
+            Self::Plain(value)
+        }
+    }
+
+    /// 检查是否为引用
+    ///
+    /// # 返回
+    /// 返回 true 表示密码需要从其他来源读取
+    pub fn is_reference(&self) -> bool {
+        !matches!(self, Self::Plain(_))
+    }
+
+    /// 检查读取密码时是否需要运行外部命令
+    ///
+    /// # 返回
+    /// 返回 true 表示为 `keyring`、`pass` 或 `cmd` 引用，读取可能较慢或需要用户操作
+    pub fn is_external(&self) -> bool {
+        matches!(self, Self::Keyring(_) | Self::Pass(_) | Self::Cmd(_))
+    }
+
+    /// 检查引用的格式，不读取密码
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，引用缺少名称或格式无效时为 Err
+    pub fn validate(&self) -> Result<(), SecretError> {
+        let target = match self {
+            Self::Plain(_) => return Ok(()),
+            Self::Keyring(path) => {
+                split_keyring(path)?;
+                return Ok(());
+            }
+            Self::Vault(target) | Self::Pass(target) | Self::Env(target) | Self::Cmd(target) => {
+                target
+            }
+        };
+        if target.trim().is_empty() {
+            return Err(SecretError::Empty(self.describe()));
+        }
+        Ok(())
+    }
+
+    /// 读取密码
+    ///
+    /// `pass`、`keyring` 和 `cmd` 引用会运行外部命令，
+    /// 命令不读取标准输入，错误输出作为错误信息返回。
+    ///
+    /// # 参数
+    /// - `vault`: 读取 `vault:` 引用使用的密码库
+    ///
+    /// # 返回
+    /// 返回 Result，成功为密码明文，来源不可用或没有输出时为 Err
+    pub fn resolve(&self, vault: &Vault) -> Result<String, SecretError> {
+        self.validate()?;
+        match *self {
+            Self::Plain(value) => Ok(value.to_string()),
+            Self::Vault(id) => Ok(vault.get(id)?.to_string()),
+            Self::Env(name) => match std::env::var(name) {
+                Ok(value) if !value.is_empty() => Ok(value),
+                _ => Err(SecretError::EnvNotSet(name.to_string())),
+            },
+            Self::Pass(path) => {
+                let mut cmd = Command::new("pass");
+                cmd.arg("show").arg(path);
+                // pass 的第一行是密码，其余行为附加信息
+                let output = run(cmd, &self.describe())?;
+                Ok(output.lines().next().unwrap_or_default().to_string())
+            }
+            Self::Keyring(path) => {
+                let (service, account) = split_keyring(path)?;
+                run(keyring_command(service, account)?, &self.describe())
+            }
+            Self::Cmd(command) => run(shell_command(command), &self.describe()),
+        }
+        .and_then(|secret| {
+            if secret.is_empty() {
+                Err(SecretError::NoOutput(self.describe()))
+            } else {
+                Ok(secret)
+            }
+        })
+    }
+
+    /// 生成用于提示信息的描述，不包含密码明文
+    ///
+    /// # 返回
+    /// 返回引用本身，明文密码为「明文密码」
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Plain(_) => "明文密码".to_string(),
+            Self::Vault(id) => vault::reference(id),
+            Self::Keyring(path) => format!("{KEYRING_PREFIX}{path}"),
+            Self::Pass(path) => format!("{PASS_PREFIX}{path}"),
+            Self::Env(name) => format!("{ENV_PREFIX}{name}"),
+            Self::Cmd(command) => format!("{CMD_PREFIX}{command}"),
+        }
+    }
+}
+
+/// 拆分 keyring 引用中的服务和账户
+///
+/// # 参数
+/// - `path`: 去掉前缀后的引用，如 `corp-proxy/alice`
+///
+/// # 返回
+/// 返回 Result，成功为 (服务, 账户)，缺少任意一部分时为 Err
+fn split_keyring(path: &str) -> Result<(&str, &str), SecretError> {
+    match path.split_once('/') {
+        Some((service, account)) if !service.is_empty() && !account.is_empty() => {
+            Ok((service, account))
+        }
+        _ => Err(SecretError::InvalidKeyring(format!(
+            "{KEYRING_PREFIX}{path}"
+        ))),
+    }
+}
+
+/// 生成从系统密钥环读取密码的命令
+///
+/// macOS 使用钥匙串的 `security`，其他 Unix 系统使用 libsecret 的 `secret-tool`，
+/// 按 `service` 和 `username` 属性查找，与常见的 keyring 库保存的格式一致。
+///
+/// # 参数
+/// - `service`: 服务名
+/// - `account`: 账户名
+///
+/// # 返回
+/// 返回 Result，成功为尚未运行的命令，当前系统不支持时为 Err
+fn keyring_command(service: &str, account: &str) -> Result<Command, SecretError> {
+    if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("security");
+        cmd.args(["find-generic-password", "-s", service, "-a", account, "-w"]);
+        Ok(cmd)
+    } else if cfg!(unix) {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["lookup", "service", service, "username", account]);
+        Ok(cmd)
+    } else {
+        Err(SecretError::KeyringUnsupported)
+    }
+}
+
+/// 生成在系统 shell 中运行的命令
+///
+/// # 参数
+/// - `command`: 命令行
+///
+/// # 返回
+/// 返回尚未运行的命令
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// 运行命令并读取标准输出
+///
+/// # 参数
+/// - `cmd`: 要运行的命令
+/// - `source_name`: 错误信息中显示的引用
+///
+/// # 返回
+/// 返回 Result，成功为去掉结尾换行的标准输出，无法运行或退出码非零时为 Err
+fn run(mut cmd: Command, source_name: &str) -> Result<String, SecretError> {
+    let output = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|source| SecretError::Spawn {
+            program: cmd.get_program().to_string_lossy().to_string(),
+            source,
+        })?;
+    let stdout = Zeroizing::new(output.stdout);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .unwrap_or_else(|| output.status.to_string());
+        return Err(SecretError::Command {
+            source_name: source_name.to_string(),
+            message,
+        });
+    }
+
+    let text = String::from_utf8_lossy(&stdout);
+    Ok(text.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn resolve(value: &str) -> Result<String, SecretError> {
+        SecretRef::parse(value).resolve(&Vault::new(Path::new("/nonexistent/config.toml")))
+    }
+
+    #[test]
+    fn parses_prefixes() {
+        assert_eq!(SecretRef::parse("env:TOKEN"), SecretRef::Env("TOKEN"));
+        assert_eq!(SecretRef::parse("cmd:echo hi"), SecretRef::Cmd("echo hi"));
+        assert_eq!(SecretRef::parse("hunter2"), SecretRef::Plain("hunter2"));
+        assert!(SecretRef::parse("pass:work/proxy").is_external());
+        assert!(!SecretRef::parse("env:TOKEN").is_external());
+        assert!(!SecretRef::parse("vault:proxy/global").is_external());
+        assert!(matches!(
+            SecretRef::parse("cmd: ").validate(),
+            Err(SecretError::Empty(_))
+        ));
+    }
+
+    #[test]
+    fn reads_environment_variable() {
+        std::env::set_var("MSSH_TEST_SECRET_SET", "s3cret");
+        assert_eq!(resolve("env:MSSH_TEST_SECRET_SET").unwrap(), "s3cret");
+    }
+
+    #[test]
+    fn unset_or_empty_environment_variable_is_an_error() {
+        std::env::remove_var("MSSH_TEST_SECRET_UNSET");
+        assert!(matches!(
+            resolve("env:MSSH_TEST_SECRET_UNSET"),
+            Err(SecretError::EnvNotSet(name)) if name == "MSSH_TEST_SECRET_UNSET"
+        ));
+        std::env::set_var("MSSH_TEST_SECRET_EMPTY", "");
+        assert!(matches!(
+            resolve("env:MSSH_TEST_SECRET_EMPTY"),
+            Err(SecretError::EnvNotSet(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_command_output_without_trailing_newline() {
+        assert_eq!(resolve("cmd:printf 'p@ss word\\n'").unwrap(), "p@ss word");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_command_reports_last_stderr_line() {
+        let err = resolve("cmd:echo first >&2; echo 'access denied' >&2; exit 3").unwrap_err();
+        assert!(
+            matches!(&err, SecretError::Command { message, .. } if message == "access denied"),
+            "{err:?}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn empty_command_output_is_an_error() {
+        assert!(matches!(
+            resolve("cmd:printf '\\n'"),
+            Err(SecretError::NoOutput(source)) if source == "cmd:printf '\\n'"
+        ));
+    }
+}
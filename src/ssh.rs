@@ -4,14 +4,22 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use std::io::Write;
-use std::process::Command;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
 
 use crate::config::SSHConfig;
-use crate::proxy::GlobalConfig;
+use crate::proxy::{shell_quote, GlobalConfig, ProxyConfig};
+use crate::proxy_connect::PASSWORD_ENV;
+use crate::secret::{SecretError, SecretRef};
+use crate::vault::{self, Vault};
 
 #[derive(Debug, Clone)]
 pub struct SSHManager {
     pub global_config: GlobalConfig,
+    /// 保存代理密码的密码库
+    pub vault: Vault,
+    /// mssh 配置文件，写入代理命令供 proxy-connect 查找密码
+    pub config_path: PathBuf,
 }
 
 impl SSHManager {
@@ -19,65 +27,211 @@ impl SSHManager {
     ///
     /// # 参数
     /// - `global_config`: 全局配置
+    /// - `vault`: 密码库
+    /// - `config_path`: mssh 配置文件路径
     ///
     /// # 返回
     /// 返回初始化的 SSH 管理器
-    pub fn new(global_config: GlobalConfig) -> Self {
-        Self { global_config }
+    pub fn new(global_config: GlobalConfig, vault: Vault, config_path: PathBuf) -> Self {
+        Self {
+            global_config,
+            vault,
+            config_path,
+        }
+    }
+
+    /// 检查连接配置前是否需要先解锁密码库
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    ///
+    /// # 返回
+    /// 返回 true 表示实际生效的代理密码保存在密码库中，且密码库尚未解锁
+    pub fn needs_vault(&self, config: &SSHConfig) -> bool {
+        !self.vault.is_unlocked()
+            && config
+                .effective_proxy(&self.global_config)
+                .and_then(|proxy| proxy.password.as_deref())
+                .and_then(vault::secret_id)
+                .is_some()
+    }
+
+    /// 获取代理密码的明文
+    ///
+    /// # 参数
+    /// - `proxy`: 代理配置
+    ///
+    /// # 返回
+    /// 返回 Result，成功为密码明文（未设置密码时为 None），引用的来源无法读取时为 Err
+    pub fn proxy_password(&self, proxy: &ProxyConfig) -> Result<Option<String>, SecretError> {
+        proxy
+            .password
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(|p| SecretRef::parse(p).resolve(&self.vault))
+            .transpose()
     }
 
     /// 建立 SSH 连接
     ///
     /// # 参数
     /// - `config`: SSH 配置
+    /// - `jump_chain`: 展开后的跳板机链，参见 `ConfigManager::resolve_jump_chain`
     ///
     /// # 返回
-    /// 返回 Result，成功为 Ok(()), 失败为 Err
-    pub fn connect(&self, config: &SSHConfig) -> Result<(), Box<dyn std::error::Error>> {
+    /// 返回 Result，成功为 ssh 的退出状态，无法启动 ssh 或切换终端状态失败时为 Err
+    pub fn connect(
+        &self,
+        config: &SSHConfig,
+        jump_chain: &[SSHConfig],
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
         // 恢复终端设置，退出TUI模式
         disable_raw_mode()?;
         execute!(std::io::stdout(), LeaveAlternateScreen, Show)?;
         std::io::stdout().flush()?;
 
+        let status = self.run(config, jump_chain);
+
+        // SSH 进程结束后，重新进入 TUI 模式
+        execute!(std::io::stdout(), EnterAlternateScreen)?;
+        enable_raw_mode()?;
+
+        status
+    }
+
+    /// 在当前终端中运行 ssh 并等待其退出，不处理 TUI 的终端状态
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    /// - `jump_chain`: 展开后的跳板机链
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 ssh 的退出状态，无法启动 ssh 时为 Err
+    pub fn run(
+        &self,
+        config: &SSHConfig,
+        jump_chain: &[SSHConfig],
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
         println!("\x1b[33m正在连接: {}\x1b[0m", config.address);
 
-        if config.use_global_proxy && self.global_config.proxy.is_enabled() {
+        let proxy = config.effective_proxy(&self.global_config);
+        if !jump_chain.is_empty() {
+            let targets: Vec<String> = jump_chain.iter().map(SSHConfig::jump_target).collect();
+            println!("\x1b[33m跳板机:\x1b[0m {}", targets.join(" -> "));
+        } else if let Some(proxy) = proxy {
+            let label = if config.use_global_proxy {
+                "全局代理"
+            } else {
+                "自定义代理"
+            };
             println!(
-                "\x1b[33m全局代理:\x1b[0m {:?} {}:{}",
-                self.global_config.proxy.proxy_type,
-                self.global_config.proxy.host,
-                self.global_config.proxy.port.unwrap_or(
-                    match self.global_config.proxy.proxy_type {
-                        crate::proxy::ProxyType::Socks5 => 1080,
-                        crate::proxy::ProxyType::Http => 8080,
-                        crate::proxy::ProxyType::None => 0,
-                    }
-                )
+                "\x1b[33m{label}:\x1b[0m {:?} {}:{}{}",
+                proxy.proxy_type,
+                proxy.host,
+                proxy.effective_port().unwrap_or(0),
+                if proxy.has_credentials() {
+                    " (认证)"
+                } else {
+                    ""
+                }
             );
-        } else if let Some(proxy) = &config.proxy {
-            if proxy.is_enabled() {
-                println!(
-                    "\x1b[33m自定义代理:\x1b[0m {:?} {}:{}",
-                    proxy.proxy_type,
-                    proxy.host,
-                    proxy.port.unwrap_or(match proxy.proxy_type {
-                        crate::proxy::ProxyType::Socks5 => 1080,
-                        crate::proxy::ProxyType::Http => 8080,
-                        crate::proxy::ProxyType::None => 0,
-                    })
-                );
-            }
         }
 
-        if let Some(pf) = &config.port_forward {
-            if pf.enabled {
-                println!("\x1b[33m端口转发:\x1b[0m  {} <->  {}", pf.local, pf.remote);
-            }
+        for pf in config.enabled_forwards() {
+            println!("\x1b[33m端口转发:\x1b[0m  {}", pf.summary());
         }
 
         std::io::stdout().flush()?;
 
+        let mut cmd = self.build_command(config, jump_chain, &[])?;
+
+        let cmd_str = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|s| s.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        println!("\x1b[33m最终执行命令:\x1b[0m {cmd_str}");
+        println!("按 Ctrl+C 取消连接\n");
+
+        cmd.stdin(std::process::Stdio::inherit());
+        cmd.stdout(std::process::Stdio::inherit());
+        cmd.stderr(std::process::Stdio::inherit());
+
+        // 使用 spawn 和 wait 替代 exec，以实现跨平台
+        Ok(cmd.spawn()?.wait()?)
+    }
+
+    /// 生成后台隧道使用的 ssh 命令
+    ///
+    /// 只建立端口转发不打开 shell（`-N`）；转发失败时立即退出，
+    /// 且不会向终端询问密码，因此隧道需要使用密钥或 ssh-agent 认证。
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    /// - `jump_chain`: 展开后的跳板机链
+    ///
+    /// # 返回
+    /// 返回 Result，成功为尚未启动的 ssh 命令，代理密码无法读取时为 Err
+    pub fn tunnel_command(
+        &self,
+        config: &SSHConfig,
+        jump_chain: &[SSHConfig],
+    ) -> Result<Command, SecretError> {
+        self.build_command(
+            config,
+            jump_chain,
+            &[
+                "-N",
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "-o",
+                "BatchMode=yes",
+            ],
+        )
+    }
+
+    /// 生成在主机上执行远程命令的 ssh 命令，不建立端口转发
+    ///
+    /// 用于部署公钥等一次性操作，认证方式与连接时相同，可以在终端中输入密码。
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    /// - `jump_chain`: 展开后的跳板机链
+    /// - `remote_command`: 在远程主机上执行的命令
+    ///
+    /// # 返回
+    /// 返回 Result，成功为尚未启动的 ssh 命令，代理密码无法读取时为 Err
+    pub fn remote_command(
+        &self,
+        config: &SSHConfig,
+        jump_chain: &[SSHConfig],
+        remote_command: &str,
+    ) -> Result<Command, SecretError> {
+        let mut config = config.clone();
+        config.port_forwards.clear();
+        let mut cmd = self.build_command(&config, jump_chain, &[])?;
+        cmd.arg(remote_command);
+        Ok(cmd)
+    }
+
+    /// 生成 ssh 命令，包括端口、密钥、代理和端口转发参数
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    /// - `jump_chain`: 展开后的跳板机链
+    /// - `options`: 放在目标主机之前的额外参数
+    ///
+    /// # 返回
+    /// 返回 Result，成功为尚未启动的 ssh 命令，代理密码无法读取时为 Err
+    fn build_command(
+        &self,
+        config: &SSHConfig,
+        jump_chain: &[SSHConfig],
+        options: &[&str],
+    ) -> Result<Command, SecretError> {
         let mut cmd = Command::new("ssh");
+        let proxy = config.effective_proxy(&self.global_config);
 
         if let Some(port) = config.port {
             cmd.arg("-p").arg(port.to_string());
@@ -88,53 +242,154 @@ impl SSHManager {
             cmd.arg("-i").arg(expanded_key);
         }
 
-        if config.use_global_proxy {
-            if let Some(proxy_cmd) = self.global_config.proxy.get_ssh_proxy_command() {
+        if jump_chain.iter().any(|host| host.key.is_some()) {
+            // -J 不会把跳板机的密钥传给连接跳板机的 ssh，改用逐级嵌套的 ProxyCommand
+            cmd.arg("-o")
+                .arg(format!("ProxyCommand={}", jump_proxy_command(jump_chain)));
+        } else if !jump_chain.is_empty() {
+            let targets: Vec<String> = jump_chain.iter().map(SSHConfig::jump_target).collect();
+            cmd.arg("-J").arg(targets.join(","));
+        } else if let Some(proxy) = proxy {
+            if let Some(proxy_cmd) = proxy.get_ssh_proxy_command(&self.config_path) {
                 cmd.arg("-o").arg(format!("ProxyCommand={proxy_cmd}"));
             }
-        } else if let Some(proxy) = &config.proxy {
-            if let Some(proxy_cmd) = proxy.get_ssh_proxy_command() {
-                cmd.arg("-o").arg(format!("ProxyCommand={proxy_cmd}"));
+            // 代理密码在生成命令时才从引用的来源读取，
+            // 通过环境变量传给 proxy-connect，不出现在命令行中
+            if let Some(password) = self.proxy_password(proxy)? {
+                cmd.env(PASSWORD_ENV, password);
             }
         }
 
-        if let Some(pf) = &config.port_forward {
-            if pf.enabled {
-                cmd.arg("-L").arg(format!("{}:{}", pf.local, pf.remote));
-            }
+        for pf in config.enabled_forwards() {
+            cmd.arg(pf.kind.flag()).arg(pf.spec());
         }
 
+        cmd.args(options);
+
         let connection_string = if let Some(user) = &config.user {
             format!("{}@{}", user, config.address)
         } else {
             config.address.clone()
         };
         cmd.arg(connection_string);
+        Ok(cmd)
+    }
+}
 
-        let cmd_str = std::iter::once(cmd.get_program())
-            .chain(cmd.get_args())
-            .map(|s| s.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join(" ");
+/// 生成经跳板机链连接的 ProxyCommand
+///
+/// 每一级跳板机由 `ssh -W` 转发到下一级，并以上一级的命令作为自己的 ProxyCommand，
+/// 跳板机配置的端口和密钥都会传给连接它的 ssh。
+///
+/// # 参数
+/// - `jump_chain`: 按连接顺序排列的跳板机
+///
+/// # 返回
+/// 返回 ProxyCommand 的值，其中的 `%h` 和 `%p` 由 ssh 展开为下一级的地址和端口
+fn jump_proxy_command(jump_chain: &[SSHConfig]) -> String {
+    let mut command: Option<String> = None;
+    for host in jump_chain {
+        let mut args = vec!["ssh".to_string()];
+        if let Some(port) = host.port {
+            args.push(format!("-p {port}"));
+        }
+        if let Some(key) = &host.key {
+            args.push(format!("-i {}", shell_quote(&shellexpand::tilde(key))));
+        }
+        if let Some(inner) = command.take() {
+            // shell_quote 将内层命令中的 % 转义，外层 ssh 展开后还原为内层 ssh 的占位符
+            args.push(format!(
+                "-o {}",
+                shell_quote(&format!("ProxyCommand={inner}"))
+            ));
+        }
+        // 与 OpenSSH 的 ProxyJump 一样加方括号，支持 IPv6 地址
+        args.push("-W '[%h]:%p'".to_string());
+        let destination = match &host.user {
+            Some(user) => format!("{user}@{}", host.address),
+            None => host.address.clone(),
+        };
+        args.push(shell_quote(&destination));
+        command = Some(args.join(" "));
+    }
+    command.unwrap_or_default()
+}
 
-        println!("\x1b[33m最终执行命令:\x1b[0m {cmd_str}");
-        println!("按 Ctrl+C 取消连接\n");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
 
-        cmd.stdin(std::process::Stdio::inherit());
-        cmd.stdout(std::process::Stdio::inherit());
-        cmd.stderr(std::process::Stdio::inherit());
+    fn host(alias: &str, address: &str, key: Option<&str>) -> SSHConfig {
+        SSHConfig {
+            alias: alias.to_string(),
+            address: address.to_string(),
+            port: None,
+            user: None,
+            key: key.map(str::to_string),
+            group: None,
+            tags: Vec::new(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy: None,
+            use_global_proxy: false,
+        }
+    }
 
-        // 使用 spawn 和 wait 替代 exec，以实现跨平台
-        let status = cmd.spawn()?.wait()?;
+    fn args(jump_chain: &[SSHConfig]) -> Vec<String> {
+        let config_path = Path::new("/nonexistent/config.toml");
+        let manager = SSHManager::new(
+            GlobalConfig::default(),
+            Vault::new(config_path),
+            config_path.to_path_buf(),
+        );
+        let command = manager
+            .build_command(&host("web", "10.0.0.5", None), jump_chain, &[])
+            .unwrap();
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
 
-        // SSH 进程结束后，重新进入 TUI 模式
-        execute!(std::io::stdout(), EnterAlternateScreen)?;
-        enable_raw_mode()?;
+    #[test]
+    fn jump_hosts_without_keys_use_proxy_jump() {
+        let mut bastion = host("bastion", "::1", None);
+        bastion.user = Some("root".to_string());
+        bastion.port = Some(2222);
+        let gateway = host("gw", "gw.example.com", None);
 
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("SSH 连接失败，退出码: {:?}", status.code()).into())
-        }
+        assert_eq!(
+            args(&[bastion, gateway]),
+            ["-J", "root@[::1]:2222,gw.example.com", "10.0.0.5"]
+        );
+    }
+
+    #[test]
+    fn jump_host_keys_are_passed_through_proxy_command() {
+        let mut bastion = host("bastion", "bastion.example.com", Some("/keys/bastion"));
+        bastion.user = Some("ops".to_string());
+        bastion.port = Some(2222);
+        let gateway = host("gw", "::1", Some("/keys/my gw"));
+
+        assert_eq!(
+            args(&[bastion.clone()]),
+            [
+                "-o",
+                "ProxyCommand=ssh -p 2222 -i /keys/bastion -W '[%h]:%p' ops@bastion.example.com",
+                "10.0.0.5"
+            ]
+        );
+        // 内层命令的占位符转义为 %%，由连接网关的 ssh 展开为网关的地址
+        assert_eq!(
+            args(&[bastion, gateway]),
+            [
+                "-o",
+                "ProxyCommand=ssh -i '/keys/my gw' \
+                 -o 'ProxyCommand=ssh -p 2222 -i /keys/bastion -W '\\''[%%h]:%%p'\\'' ops@bastion.example.com' \
+                 -W '[%h]:%p' ::1",
+                "10.0.0.5"
+            ]
+        );
     }
 }
@@ -0,0 +1,997 @@
+use crate::config::{ConfigManager, ForwardKind, PortForward, SSHConfig};
+use crate::proxy::{GlobalConfig, ProxyConfig, ProxyType};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Include 的最大嵌套深度，与 OpenSSH 保持一致
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// mssh 生成的 OpenSSH 配置片段的首行标记
+pub const GENERATED_MARKER: &str = "# Generated by mssh";
+
+/// 可以出现多次并累加的配置项，其余配置项均以第一次出现的值为准
+const MULTI_VALUE_KEYWORDS: &[&str] = &[
+    "identityfile",
+    "certificatefile",
+    "localforward",
+    "remoteforward",
+    "dynamicforward",
+    "sendenv",
+    "setenv",
+];
+
+/// 参数为整行命令的配置项，不拆分参数，也不在导入时展开 `%h` 等标记
+const COMMAND_KEYWORDS: &[&str] = &[
+    "proxycommand",
+    "localcommand",
+    "remotecommand",
+    "knownhostscommand",
+];
+
+/// 配置块的生效条件
+#[derive(Debug, Clone)]
+enum Condition {
+    /// `Host` 模式列表
+    Host(Vec<String>),
+    /// `Match` 条件列表，每项为 (是否取反, 条件名, 参数)
+    Match(Vec<(bool, String, Option<String>)>),
+}
+
+/// 一个配置块：所有条件同时满足时其中的配置项生效
+#[derive(Debug, Clone)]
+struct Block {
+    conditions: Vec<Condition>,
+    options: Vec<(String, Vec<String>)>,
+}
+
+/// OpenSSH 客户端配置文件 (`~/.ssh/config`)
+#[derive(Debug, Clone, Default)]
+pub struct OpenSshConfig {
+    blocks: Vec<Block>,
+    aliases: Vec<String>,
+    included_files: Vec<PathBuf>,
+    ssh_dir: PathBuf,
+}
+
+/// 某个主机最终生效的配置项
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedHost {
+    pub alias: String,
+    options: HashMap<String, Vec<String>>,
+}
+
+impl ResolvedHost {
+    /// 获取单值配置项
+    ///
+    /// # 参数
+    /// - `keyword`: 配置项名称（不区分大小写）
+    ///
+    /// # 返回
+    /// 返回配置项的第一个参数，未设置时为 None
+    pub fn get(&self, keyword: &str) -> Option<&str> {
+        self.options
+            .get(&keyword.to_lowercase())
+            .and_then(|values| values.first())
+            .map(|s| s.as_str())
+    }
+
+    /// 获取可多次出现的配置项
+    ///
+    /// # 参数
+    /// - `keyword`: 配置项名称（不区分大小写）
+    ///
+    /// # 返回
+    /// 返回按出现顺序排列的所有值
+    pub fn get_all(&self, keyword: &str) -> &[String] {
+        self.options
+            .get(&keyword.to_lowercase())
+            .map(|values| values.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// 转换为 mssh 的 SSH 配置
+    ///
+    /// # 返回
+    /// 返回对应的 SSH 配置
+    pub fn to_ssh_config(&self) -> SSHConfig {
+        let mut port_forwards = Vec::new();
+        for (keyword, kind) in [
+            ("localforward", ForwardKind::Local),
+            ("remoteforward", ForwardKind::Remote),
+            ("dynamicforward", ForwardKind::Dynamic),
+        ] {
+            for spec in self.get_all(keyword) {
+                let mut parts = spec.split_whitespace();
+                let Some(local) = parts.next() else {
+                    continue;
+                };
+                port_forwards.push(PortForward {
+                    kind: kind.clone(),
+                    enabled: true,
+                    local: local.to_string(),
+                    remote: parts.next().unwrap_or_default().to_string(),
+                });
+            }
+        }
+
+        // ProxyJump 引用的主机按别名对应 mssh 中的配置
+        let proxy = self
+            .get("proxyjump")
+            .filter(|jump| !jump.eq_ignore_ascii_case("none"))
+            .map(|jump| ProxyConfig {
+                proxy_type: ProxyType::Jump,
+                jump_hosts: jump.split(',').map(|h| h.trim().to_string()).collect(),
+                ..ProxyConfig::default()
+            });
+
+        SSHConfig {
+            alias: self.alias.clone(),
+            address: self.get("hostname").unwrap_or(&self.alias).to_string(),
+            port: self.get("port").and_then(|p| p.parse::<u16>().ok()),
+            user: self.get("user").map(|s| s.to_string()),
+            key: self.get_all("identityfile").first().cloned(),
+            group: None,
+            tags: Vec::new(),
+            port_forwards,
+            tunnel_only: false,
+            proxy,
+            use_global_proxy: false,
+        }
+    }
+}
+
+/// 获取默认的 OpenSSH 用户配置文件路径
+///
+/// # 返回
+/// 返回 `~/.ssh/config`，无法获取用户主目录时返回 None
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("config"))
+}
+
+impl OpenSshConfig {
+    /// 读取并解析配置文件，`Include` 的相对路径以 `~/.ssh` 为基准
+    ///
+    /// # 参数
+    /// - `path`: 配置文件路径
+    ///
+    /// # 返回
+    /// 返回 Result，成功为解析结果，主配置文件无法读取时为 Err
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let ssh_dir = dirs::home_dir()
+            .map(|home| home.join(".ssh"))
+            .or_else(|| path.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        Ok(Self::parse(&content, &ssh_dir))
+    }
+
+    /// 解析配置文件内容
+    ///
+    /// # 参数
+    /// - `content`: 配置文件内容
+    /// - `ssh_dir`: `Include` 相对路径的基准目录
+    ///
+    /// # 返回
+    /// 返回解析结果
+    pub fn parse(content: &str, ssh_dir: &Path) -> Self {
+        let mut config = Self {
+            blocks: Vec::new(),
+            aliases: Vec::new(),
+            included_files: Vec::new(),
+            ssh_dir: ssh_dir.to_path_buf(),
+        };
+        config.parse_content(content, &[], 0);
+        config
+    }
+
+    /// 解析一段配置内容，新建的配置块都会带上父级条件
+    ///
+    /// # 参数
+    /// - `content`: 配置内容
+    /// - `parent`: 包含该内容的 `Include` 所在块的条件
+    /// - `depth`: 当前 Include 嵌套深度
+    fn parse_content(&mut self, content: &str, parent: &[Condition], depth: usize) {
+        self.blocks.push(Block {
+            conditions: parent.to_vec(),
+            options: Vec::new(),
+        });
+
+        for line in content.lines() {
+            let Some((keyword, args)) = split_line(line) else {
+                continue;
+            };
+
+            match keyword.as_str() {
+                "host" => {
+                    for pattern in &args {
+                        let is_concrete = !pattern.starts_with('!')
+                            && !pattern.contains(['*', '?'])
+                            && !self.aliases.contains(pattern);
+                        if is_concrete {
+                            self.aliases.push(pattern.clone());
+                        }
+                    }
+                    let mut conditions = parent.to_vec();
+                    conditions.push(Condition::Host(args));
+                    self.blocks.push(Block {
+                        conditions,
+                        options: Vec::new(),
+                    });
+                }
+                "match" => {
+                    let mut conditions = parent.to_vec();
+                    conditions.push(Condition::Match(parse_match_criteria(&args)));
+                    self.blocks.push(Block {
+                        conditions,
+                        options: Vec::new(),
+                    });
+                }
+                "include" => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        continue;
+                    }
+                    let current = self
+                        .blocks
+                        .last()
+                        .map(|b| b.conditions.clone())
+                        .unwrap_or_default();
+                    for file in self.expand_include(&args) {
+                        if let Ok(included) = fs::read_to_string(&file) {
+                            self.included_files.push(file);
+                            self.parse_content(&included, &current, depth + 1);
+                        }
+                    }
+                    // Include 结束后恢复所在块的条件
+                    self.blocks.push(Block {
+                        conditions: current,
+                        options: Vec::new(),
+                    });
+                }
+                _ => {
+                    if let Some(block) = self.blocks.last_mut() {
+                        block.options.push((keyword, args));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 展开 `Include` 参数中的路径和通配符
+    ///
+    /// # 参数
+    /// - `args`: Include 的参数列表
+    ///
+    /// # 返回
+    /// 返回按字母顺序排列的文件列表
+    fn expand_include(&self, args: &[String]) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for arg in args {
+            let expanded = shellexpand::tilde(arg).to_string();
+            let pattern = if Path::new(&expanded).is_absolute() {
+                PathBuf::from(expanded)
+            } else {
+                self.ssh_dir.join(expanded)
+            };
+
+            if let Ok(paths) = glob::glob(&pattern.to_string_lossy()) {
+                files.extend(paths.filter_map(Result::ok).filter(|p| p.is_file()));
+            }
+        }
+        files
+    }
+
+    /// 获取配置中出现的具体主机别名（不含通配符和取反模式）
+    ///
+    /// # 返回
+    /// 返回按出现顺序排列的别名列表
+    pub fn host_aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    /// 检查某个文件是否被 `Include` 引入
+    ///
+    /// # 参数
+    /// - `path`: 文件路径
+    ///
+    /// # 返回
+    /// 返回 true 表示该文件已被引入
+    pub fn includes(&self, path: &Path) -> bool {
+        let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.included_files
+            .iter()
+            .any(|file| fs::canonicalize(file).unwrap_or_else(|_| file.clone()) == target)
+    }
+
+    /// 计算某个主机最终生效的配置
+    ///
+    /// 按 OpenSSH 的规则从上到下匹配配置块，同一配置项以第一次获得的值为准，
+    /// 因此 `Host *` 等通配块中的默认值只补充尚未设置的配置项。
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回该主机的生效配置
+    pub fn resolve(&self, alias: &str) -> ResolvedHost {
+        let mut resolved = ResolvedHost {
+            alias: alias.to_string(),
+            options: HashMap::new(),
+        };
+
+        for block in &self.blocks {
+            let matched = block
+                .conditions
+                .iter()
+                .all(|condition| condition_matches(condition, &resolved));
+            if !matched {
+                continue;
+            }
+
+            for (keyword, args) in &block.options {
+                if args.is_empty() {
+                    continue;
+                }
+                if COMMAND_KEYWORDS.contains(&keyword.as_str()) {
+                    // 命令中的标记由 ssh 在连接时展开
+                    resolved
+                        .options
+                        .entry(keyword.clone())
+                        .or_insert_with(|| args.clone());
+                } else if MULTI_VALUE_KEYWORDS.contains(&keyword.as_str()) {
+                    let value = expand_tokens(&args.join(" "), &resolved);
+                    resolved
+                        .options
+                        .entry(keyword.clone())
+                        .or_default()
+                        .push(value);
+                } else if !resolved.options.contains_key(keyword) {
+                    let values = args.iter().map(|a| expand_tokens(a, &resolved)).collect();
+                    resolved.options.insert(keyword.clone(), values);
+                }
+            }
+        }
+
+        resolved
+    }
+
+    /// 将所有具体主机转换为 mssh 的 SSH 配置
+    ///
+    /// # 返回
+    /// 返回按出现顺序排列的 SSH 配置列表
+    pub fn to_ssh_configs(&self) -> Vec<SSHConfig> {
+        self.host_aliases()
+            .iter()
+            .map(|alias| self.resolve(alias).to_ssh_config())
+            .collect()
+    }
+}
+
+/// 将导入配置中的 ProxyJump 目标对应到 mssh 的主机别名
+///
+/// 从 `start` 开始逐个处理 `configs` 中的配置：目标已是现有别名时保持不变；
+/// 是候选配置的别名时把该候选一并加入；与现有主机的 `user@host:port` 相同时改为该主机的别名；
+/// 否则按 `[user@]host[:port]` 解析为新的跳板机配置追加到列表末尾。
+///
+/// # 参数
+/// - `configs`: 已有配置加上待导入配置，新增的跳板机追加到末尾
+/// - `start`: 待导入配置在列表中的起始位置
+/// - `candidates`: 可一并导入的候选配置
+pub fn add_jump_hosts(configs: &mut Vec<SSHConfig>, start: usize, candidates: &[SSHConfig]) {
+    let mut index = start;
+    while index < configs.len() {
+        let targets = configs[index].jump_hosts().to_vec();
+        let mut jump_hosts = Vec::with_capacity(targets.len());
+        for target in targets {
+            if configs.iter().any(|c| c.alias == target) {
+                jump_hosts.push(target);
+            } else if let Some(candidate) = candidates.iter().find(|c| c.alias == target) {
+                configs.push(candidate.clone());
+                jump_hosts.push(target);
+            } else if let Some(existing) = configs.iter().find(|c| c.jump_target() == target) {
+                jump_hosts.push(existing.alias.clone());
+            } else if let Some(jump_host) = jump_host_from_target(&target) {
+                jump_hosts.push(jump_host.alias.clone());
+                configs.push(jump_host);
+            } else {
+                jump_hosts.push(target);
+            }
+        }
+        if let Some(proxy) = configs[index].proxy.as_mut() {
+            proxy.jump_hosts = jump_hosts;
+        }
+        index += 1;
+    }
+}
+
+/// 将 ProxyJump 的 `[ssh://][user@]host[:port]` 目标解析为跳板机配置
+///
+/// # 参数
+/// - `target`: ProxyJump 目标
+///
+/// # 返回
+/// 返回以目标本身为别名的配置，目标无法解析时返回 None
+fn jump_host_from_target(target: &str) -> Option<SSHConfig> {
+    let alias = target.strip_prefix("ssh://").unwrap_or(target);
+    let (user, host_port) = match alias.rsplit_once('@') {
+        Some((user, rest)) => (Some(user.to_string()).filter(|u| !u.is_empty()), rest),
+        None => (None, alias),
+    };
+    let (address, port) = if let Some(rest) = host_port.strip_prefix('[') {
+        let (address, rest) = rest.split_once(']')?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => Some(port.parse::<u16>().ok()?),
+            None if rest.is_empty() => None,
+            None => return None,
+        };
+        (address, port)
+    } else {
+        match host_port.split_once(':') {
+            Some((address, port)) => (address, Some(port.parse::<u16>().ok()?)),
+            None => (host_port, None),
+        }
+    };
+    if address.is_empty() || address.contains(['/', ' ']) {
+        return None;
+    }
+
+    Some(SSHConfig {
+        alias: alias.to_string(),
+        address: address.to_string(),
+        port,
+        user,
+        key: None,
+        group: None,
+        tags: Vec::new(),
+        port_forwards: Vec::new(),
+        tunnel_only: false,
+        proxy: None,
+        use_global_proxy: false,
+    })
+}
+
+/// 获取默认的导出文件路径
+///
+/// # 返回
+/// 返回 `~/.ssh/config.d/mssh.conf`，无法获取用户主目录时返回 None
+pub fn default_export_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("config.d").join("mssh.conf"))
+}
+
+/// 将 SSH 配置渲染为 OpenSSH 的 Host 配置块
+///
+/// # 参数
+/// - `config`: SSH 配置
+/// - `global_config`: 全局配置，用于解析全局代理
+/// - `config_path`: mssh 配置文件路径，写入代理命令
+///
+/// # 返回
+/// 返回 Host 配置块文本
+pub fn render_host_block(
+    config: &SSHConfig,
+    global_config: &GlobalConfig,
+    config_path: &Path,
+) -> String {
+    let mut lines = vec![format!("Host {}", quote_arg(&config.alias))];
+    lines.push(format!("    HostName {}", quote_arg(&config.address)));
+
+    if let Some(port) = config.port {
+        lines.push(format!("    Port {port}"));
+    }
+    if let Some(user) = &config.user {
+        lines.push(format!("    User {}", quote_arg(user)));
+    }
+    if let Some(key) = &config.key {
+        lines.push(format!("    IdentityFile {}", quote_arg(key)));
+    }
+    for pf in config.enabled_forwards() {
+        lines.push(format!("    {}", forward_directive(pf)));
+    }
+    if !config.jump_hosts().is_empty() {
+        lines.push(format!("    ProxyJump {}", config.jump_hosts().join(",")));
+    } else if let Some(proxy_cmd) = config
+        .effective_proxy(global_config)
+        .and_then(|proxy| proxy.get_ssh_proxy_command(config_path))
+    {
+        lines.push(format!("    ProxyCommand {proxy_cmd}"));
+    }
+
+    lines.join("\n")
+}
+
+/// 将端口转发渲染为 OpenSSH 的配置项
+///
+/// # 参数
+/// - `pf`: 端口转发
+///
+/// # 返回
+/// 返回 `LocalForward`、`RemoteForward` 或 `DynamicForward` 配置项
+pub fn forward_directive(pf: &PortForward) -> String {
+    match pf.kind {
+        ForwardKind::Local => format!("LocalForward {} {}", pf.local, pf.remote),
+        ForwardKind::Remote => format!("RemoteForward {} {}", pf.local, pf.remote),
+        ForwardKind::Dynamic => format!("DynamicForward {}", pf.local),
+    }
+}
+
+/// 将所有 SSH 配置渲染为完整的 OpenSSH 配置片段
+///
+/// # 参数
+/// - `configs`: SSH 配置列表
+/// - `global_config`: 全局配置
+/// - `config_path`: mssh 配置文件路径
+///
+/// # 返回
+/// 返回带有生成标记的配置片段文本
+pub fn render_export(
+    configs: &[SSHConfig],
+    global_config: &GlobalConfig,
+    config_path: &Path,
+) -> String {
+    let mut content = format!(
+        "{GENERATED_MARKER}，请勿手动编辑，重新导出时会被覆盖\n\
+         # 在 ~/.ssh/config 开头添加 `Include config.d/*.conf` 以启用这些主机\n"
+    );
+    for config in configs {
+        content.push('\n');
+        content.push_str(&render_host_block(config, global_config, config_path));
+        content.push('\n');
+    }
+    content
+}
+
+/// 导出 SSH 配置到 mssh 管理的 OpenSSH 配置片段文件
+///
+/// 目标文件已存在但不是由 mssh 生成时拒绝覆盖。
+///
+/// # 参数
+/// - `path`: 导出文件路径
+/// - `configs`: SSH 配置列表
+/// - `global_config`: 全局配置
+/// - `config_path`: mssh 配置文件路径
+///
+/// # 返回
+/// 返回 Result，成功为导出的主机数量，失败为 Err
+pub fn export_to_file(
+    path: &Path,
+    configs: &[SSHConfig],
+    global_config: &GlobalConfig,
+    config_path: &Path,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if let Ok(existing) = fs::read_to_string(path) {
+        if !existing.starts_with(GENERATED_MARKER) {
+            return Err(format!("{} 不是由 mssh 生成的文件，拒绝覆盖", path.display()).into());
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    ConfigManager::write_atomic(path, &render_export(configs, global_config, config_path))?;
+    Ok(configs.len())
+}
+
+/// 为包含空白字符的参数加上双引号
+///
+/// # 参数
+/// - `arg`: 参数
+///
+/// # 返回
+/// 返回可以安全写入 ssh_config 的参数
+fn quote_arg(arg: &str) -> String {
+    if arg.contains(char::is_whitespace) {
+        format!("\"{arg}\"")
+    } else {
+        arg.to_string()
+    }
+}
+
+/// 拆分一行配置为关键字和参数
+///
+/// 关键字不区分大小写，可用空白或 `=` 与参数分隔；参数支持双引号包裹，
+/// 以 `#` 开头的参数及其后的内容视为注释。`ProxyCommand` 等命令配置项的
+/// 参数为原样保留的行剩余部分。
+///
+/// # 参数
+/// - `line`: 配置行
+///
+/// # 返回
+/// 返回 (小写关键字, 参数列表)，空行和注释行返回 None
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let keyword_end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let keyword = line[..keyword_end].to_lowercase();
+
+    let mut rest = line[keyword_end..].trim_start();
+    if let Some(stripped) = rest.strip_prefix('=') {
+        rest = stripped.trim_start();
+    }
+    if COMMAND_KEYWORDS.contains(&keyword.as_str()) {
+        let args = if rest.is_empty() {
+            Vec::new()
+        } else {
+            vec![rest.to_string()]
+        };
+        return Some((keyword, args));
+    }
+
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+
+    for c in rest.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            '#' if !in_quotes && !has_token => break,
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+
+    Some((keyword, args))
+}
+
+/// 解析 `Match` 行的条件
+///
+/// # 参数
+/// - `args`: Match 的参数列表
+///
+/// # 返回
+/// 返回 (是否取反, 小写条件名, 参数) 列表
+fn parse_match_criteria(args: &[String]) -> Vec<(bool, String, Option<String>)> {
+    let mut criteria = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let (negated, name) = match arg.strip_prefix('!') {
+            Some(name) => (true, name.to_lowercase()),
+            None => (false, arg.to_lowercase()),
+        };
+        let value = match name.as_str() {
+            "all" | "canonical" | "final" => None,
+            _ => iter.next().cloned(),
+        };
+        criteria.push((negated, name, value));
+    }
+
+    criteria
+}
+
+/// 判断配置块条件是否对当前主机成立
+///
+/// # 参数
+/// - `condition`: 配置块条件
+/// - `resolved`: 当前已解析的主机配置
+///
+/// # 返回
+/// 返回 true 表示条件成立
+fn condition_matches(condition: &Condition, resolved: &ResolvedHost) -> bool {
+    match condition {
+        Condition::Host(patterns) => pattern_list_matches(patterns, &resolved.alias),
+        Condition::Match(criteria) => criteria.iter().all(|(negated, name, value)| {
+            let patterns: Vec<String> = value
+                .as_deref()
+                .map(|v| v.split(',').map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+            let result = match name.as_str() {
+                "all" | "final" => true,
+                "host" => {
+                    let host = resolved.get("hostname").unwrap_or(&resolved.alias);
+                    pattern_list_matches(&patterns, host)
+                }
+                "originalhost" => pattern_list_matches(&patterns, &resolved.alias),
+                "user" => resolved
+                    .get("user")
+                    .is_some_and(|user| pattern_list_matches(&patterns, user)),
+                "localuser" => std::env::var("USER")
+                    .or_else(|_| std::env::var("USERNAME"))
+                    .is_ok_and(|user| pattern_list_matches(&patterns, &user)),
+                // exec、canonical 等条件在导入时无法可靠求值，视为不满足
+                _ => false,
+            };
+            result != *negated
+        }),
+    }
+}
+
+/// 判断逗号或空格分隔的模式列表是否匹配
+///
+/// 任一取反模式匹配时结果为不匹配，否则任一普通模式匹配即为匹配。
+///
+/// # 参数
+/// - `patterns`: 模式列表
+/// - `value`: 要匹配的值
+///
+/// # 返回
+/// 返回 true 表示匹配
+fn pattern_list_matches(patterns: &[String], value: &str) -> bool {
+    let value = value.to_lowercase();
+    let mut matched = false;
+
+    for pattern in patterns.iter().flat_map(|p| p.split(',')) {
+        let pattern = pattern.to_lowercase();
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if wildcard_match(negated, &value) {
+                return false;
+            }
+        } else if wildcard_match(&pattern, &value) {
+            matched = true;
+        }
+    }
+
+    matched
+}
+
+/// 通配符匹配，支持 `*` 和 `?`
+///
+/// # 参数
+/// - `pattern`: 模式
+/// - `value`: 要匹配的值
+///
+/// # 返回
+/// 返回 true 表示匹配
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = star {
+            p = star_p + 1;
+            v = star_v + 1;
+            star = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 展开配置值中的 `%h`、`%n`、`%r`、`%d`、`%u` 和 `%%` 标记
+///
+/// # 参数
+/// - `value`: 原始值
+/// - `resolved`: 当前已解析的主机配置
+///
+/// # 返回
+/// 返回展开后的值，未知标记保持原样
+fn expand_tokens(value: &str, resolved: &ResolvedHost) -> String {
+    if !value.contains('%') {
+        return value.to_string();
+    }
+
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => result.push('%'),
+            Some('h') => result.push_str(resolved.get("hostname").unwrap_or(&resolved.alias)),
+            Some('n') => result.push_str(&resolved.alias),
+            Some('r') => result.push_str(resolved.get("user").unwrap_or("%r")),
+            Some('d') => result.push('~'),
+            Some('u') => result.push_str(
+                &std::env::var("USER")
+                    .or_else(|_| std::env::var("USERNAME"))
+                    .unwrap_or_default(),
+            ),
+            Some(other) => {
+                result.push('%');
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 解析 `tests/fixtures/ssh_config` 中的配置文件，`Include` 以该目录为基准
+    fn fixture(name: &str) -> OpenSshConfig {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ssh_config");
+        let content = fs::read_to_string(dir.join(name)).unwrap();
+        OpenSshConfig::parse(&content, &dir)
+    }
+
+    #[test]
+    fn collects_concrete_aliases_from_multi_pattern_hosts() {
+        let config = fixture("basic.conf");
+        assert_eq!(
+            config.host_aliases(),
+            [
+                "web",
+                "db",
+                "db-replica",
+                "quoted host",
+                "bastion",
+                "proxied",
+                "no-proxy"
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_equals_syntax_and_trailing_comments() {
+        let web = fixture("basic.conf").resolve("web");
+        assert_eq!(web.get("hostname"), Some("web.example.com"));
+        assert_eq!(web.get("port"), Some("2222"));
+        assert_eq!(web.get("user"), Some("deploy"));
+    }
+
+    #[test]
+    fn first_value_wins_and_host_star_only_fills_defaults() {
+        let config = fixture("basic.conf");
+
+        let web = config.resolve("web");
+        assert_eq!(web.get("port"), Some("2222"));
+        assert_eq!(web.get_all("localforward"), ["8080 localhost:80"]);
+
+        let replica = config.resolve("db-replica");
+        assert_eq!(replica.get("hostname"), Some("db.internal"));
+        assert_eq!(replica.get("user"), Some("default-user"));
+        assert_eq!(replica.get("port"), Some("22"));
+    }
+
+    #[test]
+    fn negated_pattern_excludes_host_from_defaults() {
+        let bastion = fixture("basic.conf").resolve("bastion");
+        assert_eq!(bastion.get("hostname"), Some("bastion.example.com"));
+        assert_eq!(bastion.get("user"), None);
+        assert!(bastion.get_all("localforward").is_empty());
+    }
+
+    #[test]
+    fn quoted_arguments_keep_whitespace() {
+        let config = fixture("basic.conf");
+        assert_eq!(
+            config.resolve("db").get_all("identityfile"),
+            ["~/.ssh/id with space", "~/.ssh/id_rsa"]
+        );
+        assert_eq!(
+            config.resolve("quoted host").get("hostname"),
+            Some("quoted.example.com")
+        );
+    }
+
+    #[test]
+    fn include_expands_globs_in_order() {
+        let config = fixture("include.conf");
+        assert_eq!(config.host_aliases(), ["app", "cache", "main"]);
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ssh_config");
+        assert!(config.includes(&dir.join("conf.d/10-app.conf")));
+        assert!(!config.includes(&dir.join("conf.d/README")));
+
+        assert_eq!(config.resolve("app").get("user"), Some("app-user"));
+        assert_eq!(config.resolve("cache").get("user"), Some("fallback"));
+    }
+
+    #[test]
+    fn include_inside_host_block_applies_to_that_host() {
+        let config = fixture("include.conf");
+        let main = config.resolve("main");
+        assert_eq!(main.get("port"), Some("2200"));
+        assert_eq!(main.get("user"), Some("fallback"));
+        assert_eq!(config.resolve("app").get("port"), None);
+    }
+
+    #[test]
+    fn match_host_uses_resolved_hostname_and_user() {
+        let api = fixture("match.conf").resolve("prod-api");
+        assert_eq!(api.get("user"), Some("admin"));
+        assert_eq!(api.get("port"), Some("2022"));
+        assert!(api.get_all("identityfile").is_empty());
+        assert_eq!(api.get("serveraliveinterval"), Some("30"));
+    }
+
+    #[test]
+    fn match_originalhost_negation_and_exec() {
+        let config = fixture("match.conf");
+
+        let staging = config.resolve("staging");
+        assert_eq!(staging.get("user"), Some("stage"));
+        assert_eq!(staging.get("port"), None);
+
+        let external = config.resolve("external");
+        assert_eq!(external.get_all("identityfile"), ["~/.ssh/external"]);
+        // exec 条件在导入时不求值
+        assert_eq!(external.get("user"), None);
+    }
+
+    #[test]
+    fn command_keywords_keep_the_raw_rest_of_line() {
+        let proxied = fixture("basic.conf").resolve("proxied");
+        assert_eq!(
+            proxied.get("proxycommand"),
+            Some(r#"nc -X 5 -x "proxy.local:1080" %h %p"#)
+        );
+        assert_eq!(
+            proxied.get("localcommand"),
+            Some(r#"echo "connected to %n""#)
+        );
+    }
+
+    #[test]
+    fn converts_resolved_host_to_ssh_config() {
+        let config = fixture("basic.conf").resolve("web").to_ssh_config();
+        assert_eq!(config.alias, "web");
+        assert_eq!(config.address, "web.example.com");
+        assert_eq!(config.port, Some(2222));
+        assert_eq!(config.user.as_deref(), Some("deploy"));
+        assert_eq!(config.port_forwards.len(), 1);
+        assert_eq!(config.port_forwards[0].spec(), "8080:localhost:80");
+    }
+
+    #[test]
+    fn imports_jump_hosts_from_candidates_and_raw_targets() {
+        let candidates = fixture("jump.conf").to_ssh_configs();
+        let mut configs = vec![candidates[0].clone()];
+        add_jump_hosts(&mut configs, 0, &candidates);
+
+        let aliases: Vec<&str> = configs.iter().map(|c| c.alias.as_str()).collect();
+        assert_eq!(aliases, ["app", "gateway", "ops@10.0.0.1:2222"]);
+        assert_eq!(configs[0].jump_hosts(), ["gateway", "ops@10.0.0.1:2222"]);
+        assert_eq!(configs[2].address, "10.0.0.1");
+        assert_eq!(configs[2].user.as_deref(), Some("ops"));
+        assert_eq!(configs[2].port, Some(2222));
+    }
+
+    #[test]
+    fn maps_jump_targets_to_existing_hosts() {
+        let candidates = fixture("jump.conf").to_ssh_configs();
+        let mut gateway = candidates[1].clone();
+        gateway.alias = "gw".to_string();
+        let mut configs = vec![gateway, candidates[2].clone()];
+        add_jump_hosts(&mut configs, 1, &candidates);
+
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[1].jump_hosts(), ["gw"]);
+    }
+
+    #[test]
+    fn parses_jump_targets() {
+        let host = jump_host_from_target("ssh://root@[::1]:22").unwrap();
+        assert_eq!(host.alias, "root@[::1]:22");
+        assert_eq!(host.address, "::1");
+        assert_eq!(host.port, Some(22));
+        assert!(jump_host_from_target("host:port").is_none());
+    }
+}
@@ -0,0 +1,475 @@
+use crate::config::SSHConfig;
+use crate::proxy::GlobalConfig;
+use crate::ssh_config::{forward_directive, OpenSshConfig, ResolvedHost};
+use std::path::Path;
+
+/// 参与比较的字段
+const SYNC_FIELDS: [&str; 7] = [
+    "HostName",
+    "Port",
+    "User",
+    "IdentityFile",
+    "Forward",
+    "ProxyCommand",
+    "ProxyJump",
+];
+
+/// 主机在两侧的差异类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncStatus {
+    /// 仅存在于 ~/.ssh/config
+    Added,
+    /// 仅存在于 mssh
+    Removed,
+    /// 两侧都存在但字段不同
+    Changed,
+}
+
+/// 差异的处理方式
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncResolution {
+    /// 以 mssh 为准，写入 mssh 管理的配置片段
+    UseMssh,
+    /// 以 ~/.ssh/config 为准，更新 mssh 配置
+    UseSsh,
+    /// 暂不处理
+    Skip,
+}
+
+/// 单个字段的差异
+#[derive(Debug, Clone)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub mssh: String,
+    pub ssh: String,
+}
+
+impl FieldDiff {
+    /// 检查两侧的值是否不同
+    ///
+    /// # 返回
+    /// 返回 true 表示字段有差异
+    pub fn is_changed(&self) -> bool {
+        self.mssh != self.ssh
+    }
+}
+
+/// 一个主机的同步差异
+#[derive(Debug, Clone)]
+pub struct SyncEntry {
+    pub alias: String,
+    pub status: SyncStatus,
+    pub fields: Vec<FieldDiff>,
+    pub resolution: SyncResolution,
+    /// ~/.ssh/config 一侧转换得到的配置
+    pub ssh_config: Option<SSHConfig>,
+    /// ~/.ssh/config 一侧的 ProxyCommand
+    pub ssh_proxy_command: Option<String>,
+}
+
+impl SyncEntry {
+    /// 循环切换处理方式
+    ///
+    /// 仅存在于 ~/.ssh/config 的主机无法以 mssh 为准，只在导入和跳过之间切换。
+    pub fn cycle_resolution(&mut self, forward: bool) {
+        let options: &[SyncResolution] = match self.status {
+            SyncStatus::Added => &[SyncResolution::UseSsh, SyncResolution::Skip],
+            _ => &[
+                SyncResolution::UseMssh,
+                SyncResolution::UseSsh,
+                SyncResolution::Skip,
+            ],
+        };
+        let current = options
+            .iter()
+            .position(|r| *r == self.resolution)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % options.len()
+        } else {
+            (current + options.len() - 1) % options.len()
+        };
+        self.resolution = options[next].clone();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncManager {
+    pub entries: Vec<SyncEntry>,
+    pub selected_index: usize,
+}
+
+impl SyncManager {
+    /// 创建新的同步管理器
+    ///
+    /// # 返回
+    /// 返回初始化的同步管理器
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    /// 比较 mssh 配置与 ~/.ssh/config，生成差异列表
+    ///
+    /// # 参数
+    /// - `configs`: mssh 的 SSH 配置列表
+    /// - `global_config`: 全局配置
+    /// - `config_path`: mssh 配置文件路径，用于生成代理命令
+    /// - `ssh_config`: 解析后的 ~/.ssh/config
+    ///
+    /// # 返回
+    /// 返回有差异的主机列表，两侧完全一致的主机不包含在内
+    pub fn compute(
+        configs: &[SSHConfig],
+        global_config: &GlobalConfig,
+        config_path: &Path,
+        ssh_config: &OpenSshConfig,
+    ) -> Vec<SyncEntry> {
+        let mut entries = Vec::new();
+
+        for config in configs {
+            let mssh_values = mssh_field_values(config, global_config, config_path);
+            let exists_in_ssh = ssh_config.host_aliases().contains(&config.alias);
+
+            if !exists_in_ssh {
+                entries.push(SyncEntry {
+                    alias: config.alias.clone(),
+                    status: SyncStatus::Removed,
+                    fields: build_fields(&mssh_values, &vec![String::new(); SYNC_FIELDS.len()]),
+                    resolution: SyncResolution::UseMssh,
+                    ssh_config: None,
+                    ssh_proxy_command: None,
+                });
+                continue;
+            }
+
+            let resolved = ssh_config.resolve(&config.alias);
+            let fields = build_fields(&mssh_values, &ssh_field_values(&resolved));
+            if fields.iter().any(FieldDiff::is_changed) {
+                entries.push(SyncEntry {
+                    alias: config.alias.clone(),
+                    status: SyncStatus::Changed,
+                    fields,
+                    resolution: SyncResolution::UseMssh,
+                    ssh_config: Some(resolved.to_ssh_config()),
+                    ssh_proxy_command: proxy_command(&resolved).map(str::to_string),
+                });
+            }
+        }
+
+        for alias in ssh_config.host_aliases() {
+            if configs.iter().any(|c| &c.alias == alias) {
+                continue;
+            }
+            let resolved = ssh_config.resolve(alias);
+            entries.push(SyncEntry {
+                alias: alias.clone(),
+                status: SyncStatus::Added,
+                fields: build_fields(
+                    &vec![String::new(); SYNC_FIELDS.len()],
+                    &ssh_field_values(&resolved),
+                ),
+                resolution: SyncResolution::UseSsh,
+                ssh_config: Some(resolved.to_ssh_config()),
+                ssh_proxy_command: proxy_command(&resolved).map(str::to_string),
+            });
+        }
+
+        entries
+    }
+
+    /// 设置差异列表
+    ///
+    /// # 参数
+    /// - `entries`: 差异列表
+    pub fn set_entries(&mut self, entries: Vec<SyncEntry>) {
+        self.entries = entries;
+        self.selected_index = 0;
+    }
+
+    /// 获取当前选中的差异
+    ///
+    /// # 返回
+    /// 返回当前选中的差异，如果没有则为 None
+    pub fn selected_entry(&self) -> Option<&SyncEntry> {
+        self.entries.get(self.selected_index)
+    }
+
+    /// 切换当前差异的处理方式
+    ///
+    /// # 参数
+    /// - `forward`: true 为向后切换，false 为向前切换
+    pub fn cycle_current(&mut self, forward: bool) {
+        if let Some(entry) = self.entries.get_mut(self.selected_index) {
+            entry.cycle_resolution(forward);
+        }
+    }
+
+    /// 清空同步管理器
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.selected_index = 0;
+    }
+
+    /// 移动到下一个项目
+    pub fn next_item(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.entries.len();
+        }
+    }
+
+    /// 移动到上一个项目
+    pub fn previous_item(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected_index = if self.selected_index == 0 {
+                self.entries.len() - 1
+            } else {
+                self.selected_index - 1
+            };
+        }
+    }
+}
+
+/// 生成字段差异列表
+///
+/// # 参数
+/// - `mssh`: mssh 一侧的字段值
+/// - `ssh`: ~/.ssh/config 一侧的字段值
+///
+/// # 返回
+/// 返回按固定顺序排列的字段差异
+fn build_fields(mssh: &[String], ssh: &[String]) -> Vec<FieldDiff> {
+    SYNC_FIELDS
+        .iter()
+        .zip(mssh.iter().zip(ssh.iter()))
+        .map(|(field, (mssh, ssh))| FieldDiff {
+            field,
+            mssh: mssh.clone(),
+            ssh: ssh.clone(),
+        })
+        .collect()
+}
+
+/// 计算 mssh 配置在 OpenSSH 中对应的字段值
+///
+/// # 参数
+/// - `config`: SSH 配置
+/// - `global_config`: 全局配置
+/// - `config_path`: mssh 配置文件路径
+///
+/// # 返回
+/// 返回与 `SYNC_FIELDS` 顺序一致的字段值
+fn mssh_field_values(
+    config: &SSHConfig,
+    global_config: &GlobalConfig,
+    config_path: &Path,
+) -> Vec<String> {
+    vec![
+        config.address.clone(),
+        config.port.unwrap_or(22).to_string(),
+        config.user.clone().unwrap_or_default(),
+        config
+            .key
+            .as_deref()
+            .map(normalize_path)
+            .unwrap_or_default(),
+        sorted_join(config.enabled_forwards().map(forward_directive).collect()),
+        config
+            .effective_proxy(global_config)
+            .and_then(|proxy| proxy.get_ssh_proxy_command(config_path))
+            .unwrap_or_default(),
+        config.jump_hosts().join(","),
+    ]
+}
+
+/// 计算 ~/.ssh/config 中主机的字段值
+///
+/// # 参数
+/// - `resolved`: 主机的生效配置
+///
+/// # 返回
+/// 返回与 `SYNC_FIELDS` 顺序一致的字段值
+fn ssh_field_values(resolved: &ResolvedHost) -> Vec<String> {
+    vec![
+        resolved
+            .get("hostname")
+            .unwrap_or(&resolved.alias)
+            .to_string(),
+        resolved.get("port").unwrap_or("22").to_string(),
+        resolved.get("user").unwrap_or_default().to_string(),
+        resolved
+            .get_all("identityfile")
+            .first()
+            .map(|key| normalize_path(key))
+            .unwrap_or_default(),
+        sorted_join(
+            [
+                ("localforward", "LocalForward"),
+                ("remoteforward", "RemoteForward"),
+                ("dynamicforward", "DynamicForward"),
+            ]
+            .iter()
+            .flat_map(|(keyword, name)| {
+                resolved.get_all(keyword).iter().map(move |spec| {
+                    let spec = spec.split_whitespace().collect::<Vec<_>>().join(" ");
+                    format!("{name} {spec}")
+                })
+            })
+            .collect(),
+        ),
+        proxy_command(resolved).unwrap_or_default().to_string(),
+        resolved
+            .get("proxyjump")
+            .filter(|jump| !jump.eq_ignore_ascii_case("none"))
+            .unwrap_or_default()
+            .to_string(),
+    ]
+}
+
+/// 获取 ~/.ssh/config 中主机完整的 ProxyCommand
+///
+/// # 参数
+/// - `resolved`: 主机的生效配置
+///
+/// # 返回
+/// 返回原样保留的命令，未设置或为 `none` 时为 None
+fn proxy_command(resolved: &ResolvedHost) -> Option<&str> {
+    resolved
+        .get("proxycommand")
+        .filter(|command| !command.eq_ignore_ascii_case("none"))
+}
+
+/// 排序后合并多值字段，忽略配置项的先后顺序
+///
+/// # 参数
+/// - `values`: 字段值列表
+///
+/// # 返回
+/// 返回以 `; ` 分隔的字段值
+fn sorted_join(mut values: Vec<String>) -> String {
+    values.sort();
+    values.join("; ")
+}
+
+/// 展开路径中的 `~`，避免 `~/.ssh/id` 与绝对路径被误判为不同
+///
+/// # 参数
+/// - `path`: 路径
+///
+/// # 返回
+/// 返回展开后的路径
+fn normalize_path(path: &str) -> String {
+    shellexpand::tilde(path).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::{ProxyConfig, ProxyType};
+    use crate::ssh_config::render_export;
+
+    fn host(alias: &str, proxy: Option<ProxyConfig>) -> SSHConfig {
+        SSHConfig {
+            alias: alias.to_string(),
+            address: format!("{alias}.example.com"),
+            port: Some(2222),
+            user: Some("deploy".to_string()),
+            key: None,
+            group: None,
+            tags: Vec::new(),
+            port_forwards: Vec::new(),
+            tunnel_only: false,
+            proxy,
+            use_global_proxy: false,
+        }
+    }
+
+    fn socks5(username: Option<&str>) -> ProxyConfig {
+        ProxyConfig {
+            proxy_type: ProxyType::Socks5,
+            host: "proxy.local".to_string(),
+            port: Some(1080),
+            username: username.map(str::to_string),
+            ..ProxyConfig::default()
+        }
+    }
+
+    #[test]
+    fn exported_proxy_command_is_unchanged_after_round_trip() {
+        let configs = vec![
+            host("plain", None),
+            host("proxied", Some(socks5(None))),
+            host("authed", Some(socks5(Some("alice")))),
+        ];
+        let global_config = GlobalConfig::default();
+        let exported = render_export(&configs, &global_config, Path::new("config.toml"));
+        let ssh_config = OpenSshConfig::parse(&exported, Path::new("."));
+
+        let entries = SyncManager::compute(
+            &configs,
+            &global_config,
+            Path::new("config.toml"),
+            &ssh_config,
+        );
+        assert!(entries.is_empty(), "{entries:?}");
+    }
+
+    #[test]
+    fn compares_the_whole_proxy_command() {
+        let configs = vec![host("proxied", Some(socks5(None)))];
+        let global_config = GlobalConfig::default();
+        let ssh_config = OpenSshConfig::parse(
+            "Host proxied\n\
+             \x20   HostName proxied.example.com\n\
+             \x20   Port 2222\n\
+             \x20   User deploy\n\
+             \x20   ProxyCommand nc -X 5 -x proxy.local:1080 %h %p\n",
+            Path::new("."),
+        );
+
+        let entries = SyncManager::compute(
+            &configs,
+            &global_config,
+            Path::new("config.toml"),
+            &ssh_config,
+        );
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, SyncStatus::Changed);
+        let changed: Vec<_> = entries[0]
+            .fields
+            .iter()
+            .filter(|field| field.is_changed())
+            .collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].field, "ProxyCommand");
+        assert_eq!(changed[0].ssh, "nc -X 5 -x proxy.local:1080 %h %p");
+        assert_eq!(
+            entries[0].ssh_proxy_command.as_deref(),
+            Some("nc -X 5 -x proxy.local:1080 %h %p")
+        );
+    }
+
+    #[test]
+    fn proxy_command_none_means_no_proxy() {
+        let configs = vec![host("direct", None)];
+        let global_config = GlobalConfig::default();
+        let ssh_config = OpenSshConfig::parse(
+            "Host direct\n\
+             \x20   HostName direct.example.com\n\
+             \x20   Port 2222\n\
+             \x20   User deploy\n\
+             \x20   ProxyCommand none\n",
+            Path::new("."),
+        );
+
+        assert!(SyncManager::compute(
+            &configs,
+            &global_config,
+            Path::new("config.toml"),
+            &ssh_config,
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+//! 单元测试共用的辅助工具
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 测试结束时自动删除的临时目录
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// 在系统临时目录下创建新的空目录
+    ///
+    /// # 参数
+    /// - `name`: 目录名的一部分，便于出错时辨认是哪个测试
+    ///
+    /// # 返回
+    /// 返回临时目录
+    pub fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "mssh-test-{}-{}-{name}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// 获取目录路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 获取目录下的文件路径
+    ///
+    /// # 参数
+    /// - `name`: 相对路径
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
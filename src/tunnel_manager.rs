@@ -0,0 +1,491 @@
+use crate::config::{ConfigManager, SSHConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 启动后等待 ssh 报错的时间，端口占用、认证失败等错误通常在此期间内出现
+const STARTUP_CHECK: Duration = Duration::from_millis(1000);
+/// 界面中刷新隧道状态的间隔，检查其他进程启动的隧道需要运行外部命令
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 隧道状态
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum TunnelStatus {
+    /// 正在运行
+    #[default]
+    Running,
+    /// 进程已退出，附带退出码
+    Exited(Option<i32>),
+}
+
+/// 一条在后台运行的隧道
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tunnel {
+    pub alias: String,
+    pub pid: u32,
+    /// 启动时的端口转发摘要
+    pub forwards: Vec<String>,
+    /// 启动时间（Unix 时间戳，秒）
+    pub started_at: u64,
+    /// 启动时的 ssh 命令行，用于确认进程号没有被其他进程复用
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(skip)]
+    pub status: TunnelStatus,
+}
+
+impl Tunnel {
+    /// 检查隧道是否仍在运行
+    ///
+    /// # 返回
+    /// 返回 true 表示正在运行
+    pub fn is_running(&self) -> bool {
+        self.status == TunnelStatus::Running
+    }
+
+    /// 获取已运行时长的显示文本
+    ///
+    /// # 返回
+    /// 返回形如 `2 小时 5 分钟` 的文本
+    pub fn uptime(&self) -> String {
+        format_duration(unix_now().saturating_sub(self.started_at))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TunnelState {
+    #[serde(default)]
+    tunnels: Vec<Tunnel>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TunnelManager {
+    pub tunnels: Vec<Tunnel>,
+    /// 本进程启动的 ssh 子进程，用于回收退出状态
+    children: Arc<Mutex<HashMap<u32, Child>>>,
+    state_path: Option<PathBuf>,
+    /// 上次刷新的时间
+    last_refresh: Option<Instant>,
+}
+
+impl TunnelManager {
+    /// 创建隧道管理器，并载入其他 mssh 进程启动的隧道
+    ///
+    /// 已经退出的隧道记录会被清理。
+    ///
+    /// # 返回
+    /// 返回初始化的隧道管理器
+    pub fn new() -> Self {
+        let state_path = ConfigManager::get_config_dir().map(|dir| dir.join("tunnels.toml"));
+        let tunnels = state_path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str::<TunnelState>(&content).ok())
+            .map(|state| state.tunnels)
+            .unwrap_or_default();
+
+        let mut manager = Self {
+            tunnels,
+            children: Arc::new(Mutex::new(HashMap::new())),
+            state_path,
+            last_refresh: None,
+        };
+        let count = manager.tunnels.len();
+        manager.refresh();
+        manager.tunnels.retain(Tunnel::is_running);
+        if manager.tunnels.len() != count {
+            let _ = manager.save();
+        }
+        manager
+    }
+
+    /// 获取指定主机的隧道
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回隧道引用，没有则为 None
+    pub fn get(&self, alias: &str) -> Option<&Tunnel> {
+        self.tunnels.iter().find(|t| t.alias == alias)
+    }
+
+    /// 检查指定主机的隧道是否正在运行
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回 true 表示正在运行
+    pub fn is_running(&self, alias: &str) -> bool {
+        self.get(alias).is_some_and(Tunnel::is_running)
+    }
+
+    /// 启动隧道
+    ///
+    /// ssh 的错误输出写入日志文件，启动后短暂等待以便及时发现端口占用、认证失败等错误。
+    ///
+    /// # 参数
+    /// - `config`: SSH 配置
+    /// - `cmd`: 隧道的 ssh 命令，参见 `SSHManager::tunnel_command`
+    ///
+    /// # 返回
+    /// 返回 Result，成功为新隧道的引用，失败为 Err
+    pub fn start(
+        &mut self,
+        config: &SSHConfig,
+        mut cmd: Command,
+    ) -> Result<&Tunnel, Box<dyn std::error::Error>> {
+        if self.is_running(&config.alias) {
+            return Err(format!("隧道已在运行: {}", config.alias).into());
+        }
+
+        let log_path = self.log_path(&config.alias);
+        let stderr = match &log_path {
+            Some(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                Stdio::from(File::create(path)?)
+            }
+            None => Stdio::null(),
+        };
+
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(stderr);
+        // 放入独立的进程组，终端里的 Ctrl+C 不会影响隧道
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+
+        let command = command_line(&cmd);
+        let mut child = cmd.spawn().map_err(|e| format!("无法启动 ssh: {e}"))?;
+
+        let deadline = std::time::Instant::now() + STARTUP_CHECK;
+        while std::time::Instant::now() < deadline {
+            if let Some(status) = child.try_wait()? {
+                let reason = log_path
+                    .as_deref()
+                    .and_then(last_log_line)
+                    .unwrap_or_else(|| format!("退出码: {:?}", status.code()));
+                return Err(format!("隧道启动失败: {reason}").into());
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        let tunnel = Tunnel {
+            alias: config.alias.clone(),
+            pid: child.id(),
+            forwards: config.enabled_forwards().map(|pf| pf.summary()).collect(),
+            started_at: unix_now(),
+            command,
+            status: TunnelStatus::Running,
+        };
+        self.children
+            .lock()
+            .map_err(|_| "隧道状态已损坏")?
+            .insert(tunnel.pid, child);
+        self.tunnels.retain(|t| t.alias != config.alias);
+        self.tunnels.push(tunnel);
+        self.save()?;
+
+        Ok(self.tunnels.last().expect("刚添加的隧道"))
+    }
+
+    /// 停止隧道并移除记录
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，没有该隧道或停止失败时为 Err
+    pub fn stop(&mut self, alias: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let tunnel = self
+            .get(alias)
+            .cloned()
+            .ok_or_else(|| format!("没有运行中的隧道: {alias}"))?;
+
+        if tunnel.is_running() {
+            let child = self
+                .children
+                .lock()
+                .map_err(|_| "隧道状态已损坏")?
+                .remove(&tunnel.pid);
+            match child {
+                Some(mut child) => {
+                    child.kill()?;
+                    child.wait()?;
+                }
+                None => kill_process(&tunnel)?,
+            }
+        }
+
+        self.tunnels.retain(|t| t.alias != alias);
+        self.save()?;
+        Ok(())
+    }
+
+    /// 检查是否需要重新刷新隧道状态
+    ///
+    /// # 返回
+    /// 返回 true 表示从未刷新过或距上次刷新已超过刷新间隔
+    pub fn is_due(&self) -> bool {
+        self.last_refresh
+            .map_or(true, |at| at.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    /// 刷新所有隧道的运行状态
+    pub fn refresh(&mut self) {
+        self.last_refresh = Some(Instant::now());
+        let Ok(mut children) = self.children.lock() else {
+            return;
+        };
+        for tunnel in self.tunnels.iter_mut().filter(|t| t.is_running()) {
+            let exited = match children.get_mut(&tunnel.pid) {
+                Some(child) => match child.try_wait() {
+                    Ok(Some(status)) => Some(status.code()),
+                    Ok(None) => None,
+                    Err(_) => Some(None),
+                },
+                None => (!is_tunnel_process(tunnel.pid, &tunnel.command)).then_some(None),
+            };
+            if let Some(code) = exited {
+                children.remove(&tunnel.pid);
+                tunnel.status = TunnelStatus::Exited(code);
+            }
+        }
+    }
+
+    /// 获取隧道的日志文件路径
+    ///
+    /// 别名中路径分隔符等不适合作为文件名的字符替换为 `_`，日志不会写到 tunnels 目录之外。
+    ///
+    /// # 参数
+    /// - `alias`: 主机别名
+    ///
+    /// # 返回
+    /// 返回日志文件路径，无法获取配置目录时为 None
+    pub fn log_path(&self, alias: &str) -> Option<PathBuf> {
+        let dir = self.state_path.as_deref()?.parent()?;
+        let name: String = alias
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '@') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Some(dir.join("tunnels").join(format!("{name}.log")))
+    }
+
+    /// 保存隧道记录，供其他 mssh 进程读取
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let state = TunnelState {
+            tunnels: self.tunnels.clone(),
+        };
+        fs::write(path, toml::to_string_pretty(&state)?)?;
+        Ok(())
+    }
+}
+
+/// 格式化持续时间
+///
+/// # 参数
+/// - `secs`: 秒数
+///
+/// # 返回
+/// 返回形如 `2 小时 5 分钟` 的文本
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes) = (secs / 3600, secs % 3600 / 60);
+    if hours > 0 {
+        format!("{hours} 小时 {minutes} 分钟")
+    } else if minutes > 0 {
+        format!("{minutes} 分钟")
+    } else {
+        format!("{secs} 秒")
+    }
+}
+
+/// 获取当前 Unix 时间戳
+///
+/// # 返回
+/// 返回秒数
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 读取日志的最后一行非空内容
+///
+/// # 参数
+/// - `path`: 日志文件路径
+///
+/// # 返回
+/// 返回最后一行，日志为空时为 None
+fn last_log_line(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// 获取命令的命令行参数，第一项为程序名
+///
+/// # 参数
+/// - `cmd`: 命令
+///
+/// # 返回
+/// 返回命令行参数列表
+fn command_line(cmd: &Command) -> Vec<String> {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// 检查不属于本进程的隧道是否仍在运行
+///
+/// 进程号可能已被系统分配给其他进程，因此同时比较进程的命令行与启动隧道时的命令行；
+/// Windows 上只能比较程序名。
+///
+/// # 参数
+/// - `pid`: 进程号
+/// - `command`: 启动隧道时的命令行
+///
+/// # 返回
+/// 返回 true 表示进程存在且是该隧道的 ssh 进程
+fn is_tunnel_process(pid: u32, command: &[String]) -> bool {
+    let Some(program) = command.first() else {
+        return false;
+    };
+    if cfg!(windows) {
+        let image = Path::new(program)
+            .file_stem()
+            .map(|stem| format!("{}.exe", stem.to_string_lossy()).to_lowercase());
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/FO", "CSV", "/NH"])
+            .output()
+            .map(|output| {
+                let output = String::from_utf8_lossy(&output.stdout).to_lowercase();
+                image.is_some_and(|image| output.contains(&format!("\"{image}\"")))
+                    && output.contains(&format!("\"{pid}\""))
+            })
+            .unwrap_or(false)
+    } else if cfg!(target_os = "linux") {
+        fs::read(format!("/proc/{pid}/cmdline")).is_ok_and(|cmdline| {
+            cmdline
+                .split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .eq(command.iter().map(|arg| arg.as_bytes()))
+        })
+    } else {
+        // ps 以空格拼接参数，按空白分隔后比较
+        Command::new("ps")
+            .args(["-o", "command=", "-p", &pid.to_string()])
+            .output()
+            .is_ok_and(|output| {
+                output.status.success()
+                    && String::from_utf8_lossy(&output.stdout)
+                        .split_whitespace()
+                        .eq(command.iter().flat_map(|arg| arg.split_whitespace()))
+            })
+    }
+}
+
+/// 结束不属于本进程的隧道
+///
+/// 进程已退出或进程号已被其他进程复用时不发送信号。
+///
+/// # 参数
+/// - `tunnel`: 隧道
+///
+/// # 返回
+/// 返回 Result，成功为 Ok(())，失败为 Err
+fn kill_process(tunnel: &Tunnel) -> Result<(), Box<dyn std::error::Error>> {
+    if !is_tunnel_process(tunnel.pid, &tunnel.command) {
+        return Ok(());
+    }
+    let pid = tunnel.pid;
+    let status = if cfg!(windows) {
+        Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .stdout(Stdio::null())
+            .status()?
+    } else {
+        Command::new("kill").arg(pid.to_string()).status()?
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("无法结束隧道进程 {pid}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_process_by_command_line() {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        let command = command_line(&cmd);
+        let mut child = cmd.spawn().unwrap();
+        // 刚启动的进程可能还没有完成 exec
+        thread::sleep(Duration::from_millis(200));
+
+        assert!(is_tunnel_process(child.id(), &command));
+        assert!(!is_tunnel_process(
+            child.id(),
+            &["sleep".to_string(), "31".to_string()]
+        ));
+        assert!(!is_tunnel_process(child.id(), &[]));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!is_tunnel_process(child.id(), &command));
+    }
+
+    #[test]
+    fn log_path_stays_in_tunnel_directory() {
+        let manager = TunnelManager {
+            tunnels: Vec::new(),
+            children: Arc::new(Mutex::new(HashMap::new())),
+            state_path: Some(PathBuf::from("/tmp/mssh/tunnels.toml")),
+            last_refresh: None,
+        };
+        let dir = Path::new("/tmp/mssh/tunnels");
+        assert_eq!(
+            manager.log_path("../../etc/x").unwrap(),
+            dir.join(".._.._etc_x.log")
+        );
+        assert_eq!(
+            manager.log_path("ops@10.0.0.1:22").unwrap(),
+            dir.join("ops@10.0.0.1_22.log")
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use crate::app::App;
+use ratatui::{prelude::*, widgets::*};
+
+/// 渲染配置文件错误界面
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+pub fn render_config_error(f: &mut Frame, area: Rect, app: &mut App) {
+    let error_text = app.config_error.as_deref().unwrap_or("未知错误");
+
+    let lines = vec![
+        Line::from(Span::styled(
+            "配置文件加载失败，为避免数据丢失，mssh 不会覆盖该文件。",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(error_text, Style::default().fg(Color::Red))),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[E] ", Style::default().fg(Color::Yellow)),
+            Span::raw("在编辑器中打开 ($EDITOR)"),
+        ]),
+        Line::from(vec![
+            Span::styled("[R] ", Style::default().fg(Color::Yellow)),
+            Span::raw("以只读模式启动"),
+        ]),
+        Line::from(vec![
+            Span::styled("[Q] ", Style::default().fg(Color::Yellow)),
+            Span::raw("退出"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("配置文件错误")
+                .border_style(Style::default().fg(Color::LightRed)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, area);
+}
@@ -1,4 +1,5 @@
 use crate::app::App;
+use crate::ui::render_list;
 use ratatui::{prelude::*, widgets::*};
 
 /// 渲染对话框
@@ -45,6 +46,279 @@ pub fn render_dialog(
     f.render_widget(dialog, popup_area);
 }
 
+/// 渲染移动到分组对话框，列表保持显示在对话框下方
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+pub fn render_move_to_group(f: &mut Frame, area: Rect, app: &mut App) {
+    render_list(f, area, app);
+
+    let popup_area = centered_rect(50, 30, area);
+    let alias = app
+        .get_selected_config()
+        .map(|c| c.alias.clone())
+        .unwrap_or_default();
+    let input = &app.navigation.group_manager.input;
+    let groups = app.config_manager.groups();
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled("分组: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(input.as_str()),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            if groups.is_empty() {
+                "已有分组: 无".to_string()
+            } else {
+                format!("已有分组: {}", groups.join(", "))
+            },
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "用 / 分隔多级分组，留空表示移出分组",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("移动 {alias} 到分组"))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(dialog, popup_area);
+    // 边框占一列，"分组: " 占六列
+    f.set_cursor(
+        popup_area.x + 1 + 6 + input.chars().count() as u16,
+        popup_area.y + 1,
+    );
+}
+
+/// 渲染配置文件冲突提示
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+pub fn render_config_conflict(f: &mut Frame, area: Rect) {
+    let popup_area = centered_rect(60, 40, area);
+    let text = vec![
+        Line::from("配置文件已在外部修改，而当前有尚未保存的修改。"),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "R",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(": 放弃未保存的修改，重新加载配置"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "K",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(": 保留修改继续编辑，保存时写入重新加载后的配置"),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "编辑中的主机若在外部也被修改，保存时以表单内容为准",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("配置冲突")
+                .border_style(Style::default().fg(Color::Red)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(dialog, popup_area);
+}
+
+/// 渲染修复私钥权限的提示
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+pub fn render_key_permission_prompt(f: &mut Frame, area: Rect, app: &App) {
+    let Some(prompt) = &app.key_permission_prompt else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    let key_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let text = vec![
+        Line::from(format!(
+            "私钥 {} 的权限为 0{:o}，其他用户可以读取，ssh 会拒绝使用。",
+            prompt.key, prompt.mode
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Y", key_style),
+            Span::raw(": 将权限改为 0600 后保存"),
+        ]),
+        Line::from(vec![
+            Span::styled("N", key_style),
+            Span::raw(": 不修改权限，直接保存"),
+        ]),
+        Line::from(vec![
+            Span::styled("Esc", key_style),
+            Span::raw(": 返回编辑"),
+        ]),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("私钥权限")
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(dialog, popup_area);
+}
+
+/// 渲染密码库主密码提示，输入内容以 `*` 显示
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+pub fn render_vault_prompt(f: &mut Frame, area: Rect, app: &App) {
+    let Some(prompt) = &app.vault_prompt else {
+        return;
+    };
+    let popup_area = centered_rect(60, 30, area);
+    let vault = &app.ssh_manager.vault;
+    let (title, hint) = if vault.exists() {
+        (
+            "解锁密码库",
+            "输入主密码解锁密码库，解锁后本次运行期间无需再次输入",
+        )
+    } else if prompt.first.is_none() {
+        ("创建密码库", "设置主密码，代理密码将加密保存在密码库中")
+    } else {
+        ("创建密码库", "请再次输入主密码")
+    };
+    let masked = "*".repeat(prompt.input.chars().count());
+
+    let mut text = vec![
+        Line::from(hint),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("主密码: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(masked.as_str()),
+        ]),
+        Line::from(""),
+    ];
+    let plaintext = app.config_manager.plaintext_password_count();
+    if plaintext > 0 {
+        text.push(Line::from(Span::styled(
+            format!("配置文件中有 {plaintext} 个明文代理密码，解锁后将移入密码库"),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    text.push(Line::from(Span::styled(
+        format!("密码库: {}", vault.path().display()),
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let dialog = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(dialog, popup_area);
+    // 边框占一行一列，"主密码: " 占八列
+    f.set_cursor(popup_area.x + 1 + 8 + masked.len() as u16, popup_area.y + 3);
+}
+
+/// 渲染连接前添加密钥到 ssh-agent 的提示
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+pub fn render_agent_prompt(f: &mut Frame, area: Rect, app: &App) {
+    let Some(prompt) = &app.agent_prompt else {
+        return;
+    };
+    let popup_area = centered_rect(60, 30, area);
+    let masked = "*".repeat(prompt.passphrase.chars().count());
+    let label_style = |editing: bool| {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        if editing {
+            style.fg(Color::Yellow)
+        } else {
+            style
+        }
+    };
+
+    // 输入行放在最前面，提示文字换行时不影响光标位置
+    let mut text = vec![Line::from(vec![
+        Span::styled("有效期(分钟): ", label_style(!prompt.editing_passphrase)),
+        Span::raw(prompt.minutes.as_str()),
+    ])];
+    if prompt.needs_passphrase {
+        text.push(Line::from(vec![
+            Span::styled("口令: ", label_style(prompt.editing_passphrase)),
+            Span::raw(masked.as_str()),
+        ]));
+    } else if prompt.stored_passphrase {
+        text.push(Line::from(vec![
+            Span::styled("口令: ", label_style(false)),
+            Span::raw("使用密码库中保存的口令"),
+        ]));
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(format!(
+        "密钥 {} 尚未加载到 ssh-agent，添加后在有效期内连接无需再输入口令",
+        prompt.key
+    )));
+
+    let dialog = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("添加到 ssh-agent")
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(dialog, popup_area);
+    // 边框占一行一列，"有效期(分钟): " 占十四列，"口令: " 占六列
+    let (x, y) = if prompt.editing_passphrase {
+        (6 + masked.len(), 1)
+    } else {
+        (14 + prompt.minutes.len(), 0)
+    };
+    f.set_cursor(popup_area.x + 1 + x as u16, popup_area.y + 1 + y);
+}
+
 /// 生成居中的矩形区域
 ///
 /// # 参数
@@ -54,7 +328,7 @@ pub fn render_dialog(
 ///
 /// # 返回
 /// 返回计算后的矩形区域
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+pub(super) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
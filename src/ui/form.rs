@@ -1,5 +1,7 @@
 use crate::app::App;
+use crate::config::ForwardKind;
 use crate::navigation_manager::AppMode;
+use crate::secret::SecretRef;
 use ratatui::{prelude::*, widgets::*};
 
 /// 渲染表单界面
@@ -64,9 +66,9 @@ fn render_scrollable_form_impl(f: &mut Frame, area: Rect, app: &mut App, visible
     } else {
         // SSH配置有三个段落
         vec![
-            (0, "基本信息", 5), // 别名、地址、端口、用户、密钥
-            (5, "端口转发", 3), // 启用、本地端口、远程端口
-            (8, "代理配置", 5), // 代理设置、代理主机、代理端口、代理用户名、代理密码
+            (0, "基本信息", 7),  // 别名、地址、端口、用户、密钥、分组、标签
+            (7, "端口转发", 5),  // 转发列表、转发类型、监听地址、目标地址、仅隧道
+            (12, "代理配置", 6), // 代理设置、代理主机、代理端口、代理用户名、代理密码、跳板机
         ]
     };
 
@@ -164,6 +166,8 @@ fn render_basic_info_section(f: &mut Frame, area: Rect, app: &mut App) {
             Constraint::Length(3), // 端口
             Constraint::Length(3), // 用户
             Constraint::Length(3), // 密钥
+            Constraint::Length(3), // 分组
+            Constraint::Length(3), // 标签
             Constraint::Min(0),
         ])
         .split(basic_inner);
@@ -173,6 +177,15 @@ fn render_basic_info_section(f: &mut Frame, area: Rect, app: &mut App) {
     render_form_field(f, basic_chunks[2], "端口", "port", app, 2);
     render_form_field(f, basic_chunks[3], "用户", "user", app, 3);
     render_form_field(f, basic_chunks[4], "密钥", "key", app, 4);
+    render_form_field(f, basic_chunks[5], "分组 (如 prod/db)", "group", app, 5);
+    render_form_field(
+        f,
+        basic_chunks[6],
+        "标签 (逗号分隔，如 env:prod, k8s-node)",
+        "tags",
+        app,
+        6,
+    );
 }
 
 /// 渲染端口转发区域
@@ -182,12 +195,6 @@ fn render_basic_info_section(f: &mut Frame, area: Rect, app: &mut App) {
 /// - `area`: 区域
 /// - `app`: 应用状态
 fn render_port_forward_section(f: &mut Frame, area: Rect, app: &mut App) {
-    let pf_enabled = app
-        .form_data()
-        .get("pf_enabled")
-        .map(|v| v.to_lowercase() == "true")
-        .unwrap_or(false);
-
     let pf_block = Block::default()
         .borders(Borders::NONE)
         .title(Span::styled(
@@ -202,32 +209,140 @@ fn render_port_forward_section(f: &mut Frame, area: Rect, app: &mut App) {
     let pf_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // 端口转发复选框
-            Constraint::Length(3), // 本地端口
-            Constraint::Length(3), // 远程端口
+            Constraint::Length(3), // 端口转发列表
+            Constraint::Length(3), // 转发类型
+            Constraint::Length(3), // 监听地址
+            Constraint::Length(3), // 目标地址
+            Constraint::Length(3), // 仅隧道
             Constraint::Min(0),
         ])
         .split(pf_inner);
 
-    render_checkbox_field(f, pf_chunks[0], "端口转发(空格启用)", "pf_enabled", app, 5);
+    render_forward_list_field(f, pf_chunks[0], app, 7);
+    render_forward_kind_field(f, pf_chunks[1], app, 8);
+    render_form_field(f, pf_chunks[2], "监听地址([IP:]PORT)", "pf_local", app, 9);
+    let is_dynamic = app.form_manager.form_data.forward_kind() == ForwardKind::Dynamic;
     render_form_field_with_enabled(
         f,
-        pf_chunks[1],
-        "本地端口(IP:PORT)",
-        "pf_local",
-        app,
-        6,
-        pf_enabled,
-    );
-    render_form_field_with_enabled(
-        f,
-        pf_chunks[2],
-        "远程端口(IP:PORT)",
+        pf_chunks[3],
+        "目标地址(HOST:PORT)",
         "pf_remote",
         app,
-        7,
-        pf_enabled,
+        10,
+        !is_dynamic,
+    );
+    render_tunnel_only_field(f, pf_chunks[4], app, 11);
+}
+
+/// 渲染仅隧道字段
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 区域
+/// - `app`: 应用状态
+/// - `field_index`: 字段索引
+fn render_tunnel_only_field(f: &mut Frame, area: Rect, app: &mut App, field_index: usize) {
+    let is_focused = app.current_field() == field_index;
+    let text = if app.form_manager.form_data.is_tunnel_only() {
+        "[x] 连接时只在后台运行端口转发，不打开 shell"
+    } else {
+        "[ ] 连接时打开 shell"
+    };
+
+    let field = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("仅隧道 (空格切换)")
+            .border_style(if is_focused {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            }),
+    );
+
+    f.render_widget(field, area);
+}
+
+/// 渲染端口转发列表字段
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 区域
+/// - `app`: 应用状态
+/// - `field_index`: 字段索引
+fn render_forward_list_field(f: &mut Frame, area: Rect, app: &mut App, field_index: usize) {
+    let is_focused = app.current_field() == field_index;
+    let form_data = &app.form_manager.form_data;
+
+    let mut spans = Vec::new();
+    for (i, pf) in form_data.port_forwards.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" | "));
+        }
+        let checkbox = if pf.enabled { "[✓]" } else { "[ ]" };
+        let style = if is_focused && i == form_data.forward_index {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else if pf.enabled {
+            Style::default()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        spans.push(Span::styled(format!("{checkbox} {}", pf.summary()), style));
+    }
+    if spans.is_empty() {
+        spans.push(Span::styled(
+            "无，填写下方字段后在此按 a 添加",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let title = if is_focused {
+        "端口转发列表 (←→ 选择, a 添加, e 编辑, Del 删除, 空格启用/禁用)".to_string()
+    } else {
+        format!("端口转发列表 ({})", form_data.port_forwards.len())
+    };
+
+    let list = Paragraph::new(Line::from(spans)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(if is_focused {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            }),
+    );
+
+    f.render_widget(list, area);
+}
+
+/// 渲染转发类型字段
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 区域
+/// - `app`: 应用状态
+/// - `field_index`: 字段索引
+fn render_forward_kind_field(f: &mut Frame, area: Rect, app: &mut App, field_index: usize) {
+    let is_focused = app.current_field() == field_index;
+    let option_text = match app.form_manager.form_data.forward_kind() {
+        ForwardKind::Local => "本地转发 (-L)",
+        ForwardKind::Remote => "远程转发 (-R)",
+        ForwardKind::Dynamic => "动态转发 (-D, SOCKS)",
+    };
+
+    let kind_field = Paragraph::new(option_text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("转发类型 (空格切换)")
+            .border_style(if is_focused {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            }),
     );
+
+    f.render_widget(kind_field, area);
 }
 
 /// 渲染代理配置区域
@@ -256,7 +371,19 @@ fn render_proxy_section(f: &mut Frame, area: Rect, app: &mut App) {
     let proxy_inner = proxy_block.inner(area);
     f.render_widget(proxy_block, area);
 
-    if !use_global_proxy && proxy_enabled {
+    if !use_global_proxy && proxy_enabled && is_jump_proxy(app) {
+        let proxy_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // 代理选项
+                Constraint::Length(3), // 跳板机链
+                Constraint::Min(0),
+            ])
+            .split(proxy_inner);
+
+        render_proxy_option_field(f, proxy_chunks[0], app, 12);
+        render_jump_hosts_field(f, proxy_chunks[1], app, 17);
+    } else if !use_global_proxy && proxy_enabled {
         // 需要显示详细的代理配置字段
         let proxy_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -270,11 +397,11 @@ fn render_proxy_section(f: &mut Frame, area: Rect, app: &mut App) {
             ])
             .split(proxy_inner);
 
-        render_proxy_option_field(f, proxy_chunks[0], app, 8);
-        render_form_field(f, proxy_chunks[1], "代理主机", "proxy_host", app, 9);
-        render_form_field(f, proxy_chunks[2], "代理端口", "proxy_port", app, 10);
-        render_form_field(f, proxy_chunks[3], "代理用户", "proxy_username", app, 11);
-        render_form_field(f, proxy_chunks[4], "代理密码", "proxy_password", app, 12);
+        render_proxy_option_field(f, proxy_chunks[0], app, 12);
+        render_form_field(f, proxy_chunks[1], "代理主机", "proxy_host", app, 13);
+        render_form_field(f, proxy_chunks[2], "代理端口", "proxy_port", app, 14);
+        render_form_field(f, proxy_chunks[3], "代理用户", "proxy_username", app, 15);
+        render_form_field(f, proxy_chunks[4], "代理密码", "proxy_password", app, 16);
     } else {
         // 只显示代理选项
         let proxy_chunks = Layout::default()
@@ -285,7 +412,7 @@ fn render_proxy_section(f: &mut Frame, area: Rect, app: &mut App) {
             ])
             .split(proxy_inner);
 
-        render_proxy_option_field(f, proxy_chunks[0], app, 8);
+        render_proxy_option_field(f, proxy_chunks[0], app, 12);
 
         // 在剩余空间显示提示信息
         let hint_text = if use_global_proxy {
@@ -324,8 +451,8 @@ fn render_form_field(
     let value = app.form_data().get(field_name).cloned().unwrap_or_default();
     // 改进密码掩码显示，聚焦时显示最后一个字符
     let display_value = if field_name.contains("password") {
-        if is_focused && !value.is_empty() {
-            // 显示前面的字符为星号，最后一个字符显示明文
+        if is_focused && !value.is_empty() || SecretRef::parse(&value).is_reference() {
+            // 显示前面的字符为星号，最后一个字符显示明文；引用不含密码本身，直接显示
             value
         } else {
             // 非聚焦或空字符串时全部显示为星号
@@ -404,45 +531,6 @@ fn render_form_field_with_enabled(
     }
 }
 
-/// 渲染复选框字段（如启用端口转发）
-///
-/// # 参数
-/// - `f`: 绘制 Frame
-/// - `area`: 区域
-/// - `label`: 字段标签
-/// - `field_name`: 字段名
-/// - `app`: 应用状态
-/// - `field_index`: 字段索引
-fn render_checkbox_field(
-    f: &mut Frame,
-    area: Rect,
-    label: &str,
-    field_name: &str,
-    app: &mut App,
-    field_index: usize,
-) {
-    let is_focused = app.current_field() == field_index;
-    let checked = app
-        .form_data()
-        .get(field_name)
-        .map(|v| v.to_lowercase() == "true")
-        .unwrap_or(false);
-
-    let checkbox_text = if checked { "[✓]" } else { "[ ]" };
-    let display_text = format!("{checkbox_text} {label}");
-
-    let checkbox =
-        Paragraph::new(display_text).block(Block::default().borders(Borders::ALL).border_style(
-            if is_focused {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            },
-        ));
-
-    f.render_widget(checkbox, area);
-}
-
 /// 渲染代理选项字段（如全局代理、SOCKS5、HTTP等）
 ///
 /// # 参数
@@ -476,6 +564,7 @@ fn render_proxy_option_field(f: &mut Frame, area: Rect, app: &mut App, field_ind
         match proxy_type {
             "Socks5" => "SOCKS5代理",
             "Http" => "HTTP代理",
+            "Jump" => "跳板机",
             _ => "不使用代理",
         }
     };
@@ -494,6 +583,60 @@ fn render_proxy_option_field(f: &mut Frame, area: Rect, app: &mut App, field_ind
     f.render_widget(proxy_field, area);
 }
 
+/// 检查表单是否选择了跳板机
+///
+/// # 参数
+/// - `app`: 应用状态
+///
+/// # 返回
+/// 返回 true 表示代理类型为跳板机
+fn is_jump_proxy(app: &App) -> bool {
+    app.form_data().get("proxy_type").map(String::as_str) == Some("Jump")
+}
+
+/// 渲染跳板机链字段
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 区域
+/// - `app`: 应用状态
+/// - `field_index`: 字段索引
+fn render_jump_hosts_field(f: &mut Frame, area: Rect, app: &mut App, field_index: usize) {
+    let is_focused = app.current_field() == field_index;
+    let hosts = app.form_manager.form_data.jump_hosts();
+
+    let mut spans = vec![if hosts.is_empty() {
+        Span::styled("未选择", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::raw(hosts.join(" → "))
+    }];
+    if let Some(candidate) = app.form_data().get("jump_candidate") {
+        spans.push(Span::styled(
+            format!("  + {candidate}"),
+            Style::default().fg(Color::Green),
+        ));
+    }
+
+    let title = if is_focused {
+        "跳板机链 (←→ 选择主机, 空格添加, Backspace 删除)"
+    } else {
+        "跳板机链"
+    };
+
+    let field = Paragraph::new(Line::from(spans)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(if is_focused {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            }),
+    );
+
+    f.render_widget(field, area);
+}
+
 /// 渲染完整表单（无需滚动）
 ///
 /// # 参数
@@ -505,8 +648,8 @@ fn render_full_form(f: &mut Frame, area: Rect, app: &mut App) {
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(17), // 基本信息区域 (5个字段 + 2个空行)
-            Constraint::Length(12), // 端口转发区域 (3个字段)
+            Constraint::Length(23), // 基本信息区域 (7个字段 + 2个空行)
+            Constraint::Length(17), // 端口转发区域 (5个字段)
             Constraint::Min(25),    // 代理配置区域(动态字段)
             Constraint::Min(0),
         ])
@@ -541,16 +684,15 @@ fn render_field_by_type(
     let label = get_field_label(&field);
 
     match field {
-        crate::forms::FormField::PortForwardEnabled => {
-            render_checkbox_field(f, area, &label, field_name, app, field_index);
+        crate::forms::FormField::PortForwards => {
+            render_forward_list_field(f, area, app, field_index);
         }
-        crate::forms::FormField::PortForwardLocal | crate::forms::FormField::PortForwardRemote => {
-            // 端口转发字段需要根据是否启用来决定是否可用
-            let pf_enabled = app
-                .form_data()
-                .get("pf_enabled")
-                .map(|v| v.to_lowercase() == "true")
-                .unwrap_or(false);
+        crate::forms::FormField::PortForwardKind => {
+            render_forward_kind_field(f, area, app, field_index);
+        }
+        crate::forms::FormField::PortForwardRemote => {
+            // 动态转发没有目标地址
+            let is_dynamic = app.form_manager.form_data.forward_kind() == ForwardKind::Dynamic;
             render_form_field_with_enabled(
                 f,
                 area,
@@ -558,9 +700,12 @@ fn render_field_by_type(
                 field_name,
                 app,
                 field_index,
-                pf_enabled,
+                !is_dynamic,
             );
         }
+        crate::forms::FormField::TunnelOnly => {
+            render_tunnel_only_field(f, area, app, field_index);
+        }
         crate::forms::FormField::UseGlobalProxy => {
             render_proxy_option_field(f, area, app, field_index);
         }
@@ -580,10 +725,21 @@ fn render_field_by_type(
                 .map(|v| v.to_lowercase() == "true")
                 .unwrap_or(false);
 
-            if !use_global_proxy && proxy_enabled {
+            if !use_global_proxy && proxy_enabled && !is_jump_proxy(app) {
                 render_form_field(f, area, &label, field_name, app, field_index);
             }
         }
+        crate::forms::FormField::JumpHosts => {
+            let use_global_proxy = app
+                .form_data()
+                .get("use_global_proxy")
+                .map(|v| v.to_lowercase() == "true")
+                .unwrap_or(true);
+
+            if !use_global_proxy && is_jump_proxy(app) {
+                render_jump_hosts_field(f, area, app, field_index);
+            }
+        }
         _ => {
             render_form_field(f, area, &label, field_name, app, field_index);
         }
@@ -604,14 +760,19 @@ fn get_field_label(field: &crate::forms::FormField) -> String {
         crate::forms::FormField::Port => "端口".to_string(),
         crate::forms::FormField::User => "用户".to_string(),
         crate::forms::FormField::Key => "密钥".to_string(),
-        crate::forms::FormField::PortForwardEnabled => "端口转发".to_string(),
-        crate::forms::FormField::PortForwardLocal => "本地端口".to_string(),
-        crate::forms::FormField::PortForwardRemote => "远程端口".to_string(),
+        crate::forms::FormField::Group => "分组 (如 prod/db)".to_string(),
+        crate::forms::FormField::Tags => "标签 (逗号分隔，如 env:prod, k8s-node)".to_string(),
+        crate::forms::FormField::PortForwards => "端口转发".to_string(),
+        crate::forms::FormField::PortForwardKind => "转发类型".to_string(),
+        crate::forms::FormField::PortForwardLocal => "监听地址".to_string(),
+        crate::forms::FormField::PortForwardRemote => "目标地址".to_string(),
+        crate::forms::FormField::TunnelOnly => "仅隧道".to_string(),
         crate::forms::FormField::UseGlobalProxy => "代理设置".to_string(),
         crate::forms::FormField::ProxyHost => "代理主机".to_string(),
         crate::forms::FormField::ProxyPort => "代理端口".to_string(),
         crate::forms::FormField::ProxyUsername => "代理用户名".to_string(),
         crate::forms::FormField::ProxyPassword => "代理密码".to_string(),
+        crate::forms::FormField::JumpHosts => "跳板机".to_string(),
         crate::forms::FormField::GlobalProxyType => "代理类型".to_string(),
         crate::forms::FormField::GlobalProxyHost => "代理主机".to_string(),
         crate::forms::FormField::GlobalProxyPort => "代理端口".to_string(),
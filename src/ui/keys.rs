@@ -0,0 +1,265 @@
+use super::dialog::centered_rect;
+use crate::app::App;
+use crate::key_manager::{default_key_dir, display_path, KeyDialog, SshKey};
+use ratatui::{prelude::*, widgets::*};
+
+/// 渲染密钥管理界面
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+pub fn render_keys(f: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let key_manager = &app.navigation.key_manager;
+    let dir = default_key_dir()
+        .map(|dir| display_path(&dir))
+        .unwrap_or_else(|| "~/.ssh".to_string());
+    let info = Paragraph::new(format!("{dir} 中共有 {} 个密钥", key_manager.keys.len()))
+        .block(Block::default().borders(Borders::ALL).title("密钥管理"))
+        .alignment(Alignment::Center);
+    f.render_widget(info, chunks[0]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(chunks[1]);
+
+    let items: Vec<ListItem> = key_manager
+        .keys
+        .iter()
+        .map(|key| {
+            let mut spans = vec![
+                Span::raw(key.name()),
+                Span::styled(
+                    format!(" {}", key_type(key)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ];
+            if !key.has_private {
+                spans.push(Span::styled(" [缺少私钥]", Style::default().fg(Color::Red)));
+            }
+            if !key.hosts.is_empty() {
+                spans.push(Span::styled(
+                    format!(" ({} 个主机)", key.hosts.len()),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("密钥"))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default();
+    state.select(Some(key_manager.selected_index));
+    f.render_stateful_widget(list, body[0], &mut state);
+
+    let details = match key_manager.selected_key() {
+        Some(key) => key_details(key),
+        None => vec![
+            Line::from("没有找到密钥"),
+            Line::from(""),
+            Line::from(Span::styled(
+                "按 Ctrl+N 生成新的 ed25519 密钥",
+                Style::default().fg(Color::DarkGray),
+            )),
+        ],
+    };
+    let paragraph = Paragraph::new(details)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("密钥详情")
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, body[1]);
+
+    match &key_manager.dialog {
+        Some(KeyDialog::Generate {
+            name,
+            comment,
+            editing_comment,
+        }) => render_generate_dialog(f, area, name, comment, *editing_comment),
+        Some(KeyDialog::Deploy { selected }) => render_deploy_dialog(f, area, app, *selected),
+        None => {}
+    }
+}
+
+/// 生成密钥类型和长度的描述
+///
+/// # 参数
+/// - `key`: 密钥
+///
+/// # 返回
+/// 返回如 `ED25519 256` 的描述
+fn key_type(key: &SshKey) -> String {
+    match key.bits {
+        Some(bits) => format!("{} {bits}", key.algorithm),
+        None => key.algorithm.to_string(),
+    }
+}
+
+/// 生成密钥详情
+///
+/// # 参数
+/// - `key`: 密钥
+///
+/// # 返回
+/// 返回详情的各行
+fn key_details(key: &SshKey) -> Vec<Line<'static>> {
+    let label =
+        |text: &'static str| Span::styled(text, Style::default().add_modifier(Modifier::BOLD));
+    let protection = match (key.has_private, key.encrypted) {
+        (false, _) => Span::styled("缺少私钥，只能部署公钥", Style::default().fg(Color::Red)),
+        (true, Some(true)) => Span::styled("有口令", Style::default().fg(Color::Green)),
+        (true, Some(false)) => Span::styled("无口令", Style::default().fg(Color::Yellow)),
+        (true, None) => Span::styled("未知", Style::default().fg(Color::DarkGray)),
+    };
+    let hosts = if key.hosts.is_empty() {
+        Span::styled("无", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::raw(key.hosts.join(", "))
+    };
+
+    vec![
+        Line::from(vec![label("路径: "), Span::raw(key.display_path())]),
+        Line::from(vec![label("类型: "), Span::raw(key_type(key))]),
+        Line::from(vec![label("指纹: "), Span::raw(key.fingerprint.clone())]),
+        Line::from(vec![label("注释: "), Span::raw(key.comment.clone())]),
+        Line::from(vec![label("私钥: "), protection]),
+        Line::from(vec![label("使用的主机: "), hosts]),
+        Line::from(""),
+        Line::from(label("公钥:")),
+        Line::from(Span::styled(
+            key.public_key.clone(),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ]
+}
+
+/// 渲染生成密钥弹窗
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `name`: 文件名
+/// - `comment`: 注释
+/// - `editing_comment`: 正在输入注释
+fn render_generate_dialog(
+    f: &mut Frame,
+    area: Rect,
+    name: &str,
+    comment: &str,
+    editing_comment: bool,
+) {
+    let popup_area = centered_rect(60, 40, area);
+    let field_style = |active: bool| {
+        if active {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        }
+    };
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled("文件名: ", field_style(!editing_comment)),
+            Span::raw(name),
+        ]),
+        Line::from(vec![
+            Span::styled("注释: ", field_style(editing_comment)),
+            Span::raw(comment),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "口令由 ssh-keygen 在终端中询问，注释留空时使用 ssh-keygen 的默认值",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("生成 ed25519 密钥")
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(dialog, popup_area);
+    // 边框占一列，"文件名: " 占八列，"注释: " 占六列
+    let (offset, row, input) = if editing_comment {
+        (6, 1, comment)
+    } else {
+        (8, 0, name)
+    };
+    f.set_cursor(
+        popup_area.x + 1 + offset + input.chars().count() as u16,
+        popup_area.y + 1 + row,
+    );
+}
+
+/// 渲染选择部署主机的弹窗
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+/// - `selected`: 选中的主机在配置列表中的下标
+fn render_deploy_dialog(f: &mut Frame, area: Rect, app: &App, selected: usize) {
+    let popup_area = centered_rect(50, 60, area);
+    let key = app.navigation.key_manager.selected_key();
+
+    let items: Vec<ListItem> = app
+        .config_manager
+        .configs
+        .iter()
+        .map(|config| {
+            let mut spans = vec![
+                Span::raw(config.alias.clone()),
+                Span::styled(
+                    format!(" {}", config.address),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ];
+            if key.is_some_and(|key| key.hosts.contains(&config.alias)) {
+                spans.push(Span::styled(
+                    " [使用该密钥]",
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let title = format!(
+        "将 {} 部署到主机",
+        key.map(SshKey::name).unwrap_or_default()
+    );
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_widget(Clear, popup_area);
+    f.render_stateful_widget(list, popup_area, &mut state);
+}
@@ -1,4 +1,10 @@
+use crate::agent::KeyStatus;
 use crate::app::App;
+use crate::group_manager::{group_name, ListRow};
+use crate::health_manager::HostStatus;
+use crate::history_manager::format_time;
+use crate::navigation_manager::AppMode;
+use crate::tunnel_manager::TunnelStatus;
 use crate::ui::render_scrollbar;
 use ratatui::{prelude::*, widgets::*};
 
@@ -25,7 +31,21 @@ pub fn render_list(f: &mut Frame, area: Rect, app: &mut App) {
 /// - `area`: 绘制区域
 /// - `app`: 应用状态
 fn render_config_list(f: &mut Frame, area: Rect, app: &mut App) {
+    let searching = matches!(*app.mode(), AppMode::Search);
+    let area = if searching || app.navigation.search_manager.is_filtering() {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(area);
+        render_search_bar(f, chunks[1], app, searching);
+        chunks[0]
+    } else {
+        area
+    };
+
+    let rows = app.list_rows();
     let configs = &app.config_manager.configs;
+    let filtering = app.navigation.search_manager.is_filtering();
 
     // 计算可视区域大小（减去边框和标题的高度）
     let visible_height = area.height.saturating_sub(2); // 减去上下边框
@@ -33,13 +53,42 @@ fn render_config_list(f: &mut Frame, area: Rect, app: &mut App) {
 
     // 更新滚动位置
     app.navigation
-        .update_scroll_position(configs.len(), visible_items);
+        .update_scroll_position(rows.len(), visible_items);
 
-    // 创建所有项目列表
-    let items: Vec<ListItem> = configs
+    // 创建所有项目列表，编号保持为配置列表中的编号，便于快速连接
+    let items: Vec<ListItem> = rows
         .iter()
-        .enumerate()
-        .map(|(i, config)| {
+        .map(|row| {
+            let (m, depth) = match row {
+                ListRow::Group {
+                    path,
+                    depth,
+                    count,
+                    collapsed,
+                } => {
+                    return ListItem::new(Line::from(Span::styled(
+                        format!(
+                            "{}{} {} ({count})",
+                            "  ".repeat(*depth),
+                            if *collapsed { "▶" } else { "▼" },
+                            group_name(path)
+                        ),
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                }
+                ListRow::Recent { count, collapsed } => {
+                    return ListItem::new(Line::from(Span::styled(
+                        format!("{} 最近连接 ({count})", if *collapsed { "▶" } else { "▼" }),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                }
+                ListRow::Host { matched, depth, .. } => (matched, *depth),
+            };
+            let (i, config) = (m.index, &configs[m.index]);
             let proxy_info = if config.use_global_proxy {
                 if app.config_manager.global_config.proxy.is_enabled() {
                     " [全局代理]".to_string()
@@ -47,13 +96,15 @@ fn render_config_list(f: &mut Frame, area: Rect, app: &mut App) {
                     String::new()
                 }
             } else if let Some(proxy) = &config.proxy {
-                if proxy.is_enabled() {
+                if proxy.proxy_type == crate::proxy::ProxyType::Jump && proxy.is_enabled() {
+                    format!(" [跳板机: {}]", proxy.jump_hosts.join(" → "))
+                } else if proxy.is_enabled() {
                     format!(
                         " [{}代理]",
                         match proxy.proxy_type {
                             crate::proxy::ProxyType::Socks5 => "SOCKS5",
                             crate::proxy::ProxyType::Http => "HTTP",
-                            crate::proxy::ProxyType::None => "",
+                            crate::proxy::ProxyType::None | crate::proxy::ProxyType::Jump => "",
                         }
                     )
                 } else {
@@ -63,28 +114,65 @@ fn render_config_list(f: &mut Frame, area: Rect, app: &mut App) {
                 String::new()
             };
 
-            let pf_info = if let Some(pf) = &config.port_forward {
-                if pf.enabled {
-                    format!(" [端口转发: {}->{}]", pf.local, pf.remote)
-                } else {
-                    String::new()
-                }
-            } else {
-                String::new()
+            let forwards: Vec<_> = config.enabled_forwards().collect();
+            let pf_info = match forwards.as_slice() {
+                [] => String::new(),
+                [pf] => format!(" [端口转发: {}]", pf.summary()),
+                _ => format!(" [端口转发: {} 条]", forwards.len()),
             };
 
-            let content = format!(
-                "{}. {} ({}@{}:{}){}{}",
-                i + 1,
-                config.alias,
-                config.user.as_deref().unwrap_or("root"),
-                config.address,
+            let status = app.health_manager.status(&config.alias);
+            let mut spans = vec![Span::raw("  ".repeat(depth))];
+            if let Some(status) = &status {
+                spans.push(status_dot(status));
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::raw(format!("{}. ", i + 1)));
+            spans.extend(highlight(&config.alias, &m.alias));
+            spans.push(Span::raw(" ("));
+            match &config.user {
+                Some(user) => spans.extend(highlight(user, &m.user)),
+                None => spans.push(Span::raw("root")),
+            }
+            spans.push(Span::raw("@"));
+            spans.extend(highlight(&config.address, &m.address));
+            spans.push(Span::raw(format!(
+                ":{}){}{}",
                 config.port.unwrap_or(22),
                 proxy_info,
                 pf_info
-            );
+            )));
 
-            ListItem::new(content)
+            let tunnel_info = match app.tunnel_manager.get(&config.alias) {
+                Some(tunnel) if tunnel.is_running() => {
+                    Span::styled(" [隧道运行中]", Style::default().fg(Color::Green))
+                }
+                Some(_) => Span::styled(" [隧道已退出]", Style::default().fg(Color::Red)),
+                None if config.tunnel_only => {
+                    Span::styled(" [仅隧道]", Style::default().fg(Color::DarkGray))
+                }
+                None => Span::raw(""),
+            };
+
+            spans.push(tunnel_info);
+            if let Some(HostStatus::Up(rtt)) = status {
+                spans.push(Span::styled(
+                    format!(" {}ms", rtt.as_millis()),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            // 搜索结果不分组显示，附上所在分组和命中的标签
+            if let (true, Some(group)) = (filtering, &config.group) {
+                spans.push(Span::styled(
+                    format!(" [{group}]"),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            for &tag in &m.tags {
+                spans.push(Span::raw(" "));
+                spans.push(tag_chip(&config.tags[tag]));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -98,12 +186,25 @@ fn render_config_list(f: &mut Frame, area: Rect, app: &mut App) {
         .take(visible_items)
         .collect();
 
+    let mut title = if app.config_manager.is_read_only() {
+        "SSH 配置列表 [只读]".to_string()
+    } else {
+        "SSH 配置列表".to_string()
+    };
+    if filtering {
+        title.push_str(&format!(" ({}/{})", rows.len(), configs.len()));
+    }
+    let sort_mode = app.config_manager.global_config.sort_mode;
+    if !sort_mode.is_manual() {
+        title.push_str(&format!(" [排序: {}]", sort_mode.label()));
+    }
+
     // 创建列表，使用滚动功能
     let list = List::new(visible_items_list)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("SSH 配置列表")
+                .title(title)
                 .border_style(Style::default().fg(Color::Cyan)),
         )
         .style(Style::default())
@@ -125,11 +226,130 @@ fn render_config_list(f: &mut Frame, area: Rect, app: &mut App) {
     f.render_stateful_widget(list, area, &mut state);
 
     // 渲染滚动条（如果内容超出可视区域）
-    if configs.len() > visible_items {
+    if rows.len() > visible_items {
         render_scrollbar(f, area, &app.navigation.scroll_manager);
     }
 }
 
+/// 渲染搜索栏
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+/// - `editing`: 是否正在输入
+fn render_search_bar(f: &mut Frame, area: Rect, app: &App, editing: bool) {
+    let query = &app.navigation.search_manager.query;
+    let border_color = if editing { Color::Yellow } else { Color::Cyan };
+    let search = Paragraph::new(Line::from(vec![
+        Span::styled("/", Style::default().fg(Color::Yellow)),
+        Span::raw(query.as_str()),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(if editing {
+                "搜索"
+            } else {
+                "搜索 (/ 修改, Esc 清除)"
+            })
+            .border_style(Style::default().fg(border_color)),
+    );
+    f.render_widget(search, area);
+
+    if editing {
+        // 边框和 "/" 各占一列
+        f.set_cursor(area.x + query.chars().count() as u16 + 2, area.y + 1);
+    }
+}
+
+/// 将文本按命中位置拆分为高亮和普通片段
+///
+/// # 参数
+/// - `text`: 文本
+/// - `positions`: 命中的字符位置
+///
+/// # 返回
+/// 返回拆分后的片段
+fn highlight<'a>(text: &'a str, positions: &[usize]) -> Vec<Span<'a>> {
+    if positions.is_empty() {
+        return vec![Span::raw(text)];
+    }
+
+    let style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_hit = false;
+    for (i, c) in text.chars().enumerate() {
+        let hit = positions.binary_search(&i).is_ok();
+        if hit != current_hit && !current.is_empty() {
+            let part = std::mem::take(&mut current);
+            spans.push(if current_hit {
+                Span::styled(part, style)
+            } else {
+                Span::raw(part)
+            });
+        }
+        current_hit = hit;
+        current.push(c);
+    }
+    spans.push(if current_hit {
+        Span::styled(current, style)
+    } else {
+        Span::raw(current)
+    });
+    spans
+}
+
+/// 标签的颜色，按 `key:value` 中的 key 选取，相同 key 的标签颜色相同
+const TAG_COLORS: &[Color] = &[
+    Color::LightBlue,
+    Color::LightGreen,
+    Color::LightMagenta,
+    Color::LightYellow,
+    Color::LightCyan,
+    Color::LightRed,
+];
+
+/// 生成标签的彩色标记
+///
+/// # 参数
+/// - `tag`: 标签
+///
+/// # 返回
+/// 返回带背景色的片段
+fn tag_chip(tag: &str) -> Span<'static> {
+    let key = tag.split_once(':').map_or(tag, |(key, _)| key);
+    let hash = key.bytes().fold(0usize, |hash, b| {
+        hash.wrapping_mul(31).wrapping_add(b as usize)
+    });
+    Span::styled(
+        format!(" {tag} "),
+        Style::default()
+            .fg(Color::Black)
+            .bg(TAG_COLORS[hash % TAG_COLORS.len()]),
+    )
+}
+
+/// 生成表示主机可达状态的圆点
+///
+/// # 参数
+/// - `status`: 检测结果
+///
+/// # 返回
+/// 返回带颜色的圆点
+fn status_dot(status: &HostStatus) -> Span<'static> {
+    let (dot, color) = match status {
+        HostStatus::Checking => ("◌", Color::Yellow),
+        HostStatus::Up(_) => ("●", Color::Green),
+        HostStatus::Down(_) => ("●", Color::Red),
+        HostStatus::Skipped => ("○", Color::DarkGray),
+    };
+    Span::styled(dot, Style::default().fg(color))
+}
+
 /// 渲染配置详情
 ///
 /// # 参数
@@ -137,7 +357,65 @@ fn render_config_list(f: &mut Frame, area: Rect, app: &mut App) {
 /// - `area`: 绘制区域
 /// - `app`: 应用状态
 fn render_config_details(f: &mut Frame, area: Rect, app: &App) {
-    let details = if let Some(config) = app.get_selected_config() {
+    let selected_row = app.selected_row();
+    let details = if let Some(ListRow::Recent { .. }) = selected_row {
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "最近连接",
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+        for index in app.recent_hosts() {
+            let config = &app.config_manager.configs[index];
+            if let Some(entry) = app.history_manager.last_entry(&config.alias) {
+                lines.push(Line::from(format!(
+                    "  {}. {} - {}",
+                    index + 1,
+                    config.alias,
+                    format_time(entry.started_at)
+                )));
+                lines.push(Line::from(Span::styled(
+                    format!("     {}", entry.summary()),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Enter: 展开/折叠 | mssh history: 查看全部记录",
+            Style::default().fg(Color::DarkGray),
+        )));
+        lines
+    } else if let Some(ListRow::Group { path, count, .. }) = selected_row {
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("分组: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(path.clone()),
+            ]),
+            Line::from(vec![
+                Span::styled("主机数: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(count.to_string()),
+            ]),
+            Line::from(""),
+        ];
+        for (i, config) in app.config_manager.configs.iter().enumerate() {
+            if config.in_group(&path) {
+                lines.push(Line::from(format!(
+                    "  {}. {} ({})",
+                    i + 1,
+                    config.alias,
+                    config.address
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Enter: 展开/折叠 | Ctrl+B: 连接全部",
+            Style::default().fg(Color::DarkGray),
+        )));
+        lines
+    } else if let Some(config) = app.get_selected_config() {
         let mut lines = vec![
             Line::from(vec![
                 Span::styled("别名: ", Style::default().add_modifier(Modifier::BOLD)),
@@ -157,11 +435,76 @@ fn render_config_details(f: &mut Frame, area: Rect, app: &App) {
             ]),
         ];
 
+        if let Some(group) = &config.group {
+            lines.push(Line::from(vec![
+                Span::styled("分组: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(group),
+            ]));
+        }
+
+        if !config.tags.is_empty() {
+            let mut spans = vec![Span::styled(
+                "标签: ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )];
+            for tag in &config.tags {
+                spans.push(tag_chip(tag));
+                spans.push(Span::raw(" "));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        let status = match app.health_manager.status(&config.alias) {
+            Some(HostStatus::Checking) => {
+                Span::styled("检测中…", Style::default().fg(Color::Yellow))
+            }
+            Some(HostStatus::Up(rtt)) => Span::styled(
+                format!("在线，延迟 {} ms", rtt.as_millis()),
+                Style::default().fg(Color::Green),
+            ),
+            Some(HostStatus::Down(error)) => {
+                Span::styled(format!("离线 ({error})"), Style::default().fg(Color::Red))
+            }
+            Some(HostStatus::Skipped) => {
+                Span::styled("经跳板机连接，未检测", Style::default().fg(Color::DarkGray))
+            }
+            None => Span::styled("未检测 (Ctrl+R 检测)", Style::default().fg(Color::DarkGray)),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("状态: ", Style::default().add_modifier(Modifier::BOLD)),
+            status,
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("上次连接: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(match app.history_manager.last_entry(&config.alias) {
+                Some(entry) => format!("{} ({})", format_time(entry.started_at), entry.summary()),
+                None => "从未连接".to_string(),
+            }),
+        ]));
+
         // 总是显示密钥状态
         if let Some(key) = &config.key {
+            let agent = match app.agent_manager.key_status(key) {
+                KeyStatus::Loaded => {
+                    Span::styled(" (已加载到 ssh-agent)", Style::default().fg(Color::Green))
+                }
+                KeyStatus::NotLoaded => Span::styled(
+                    " (未加载到 ssh-agent，连接时可添加)",
+                    Style::default().fg(Color::Yellow),
+                ),
+                KeyStatus::NoAgent => Span::styled(
+                    " (未检测到 ssh-agent)",
+                    Style::default().fg(Color::DarkGray),
+                ),
+                KeyStatus::Unknown(error) => Span::styled(
+                    format!(" (ssh-agent: {error})"),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            };
             lines.push(Line::from(vec![
                 Span::styled("密钥: ", Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(key),
+                agent,
             ]));
         } else {
             // 如果没有设置密钥，显示无
@@ -173,19 +516,18 @@ fn render_config_details(f: &mut Frame, area: Rect, app: &App) {
 
         // 总是显示端口转发状态
         lines.push(Line::from(""));
-        if let Some(pf) = &config.port_forward {
-            lines.push(Line::from(vec![
-                Span::styled("端口转发: ", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(if pf.enabled { "启用" } else { "禁用" }),
-            ]));
-            if pf.enabled {
-                lines.push(Line::from(vec![
-                    Span::styled("  本地: ", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(&pf.local),
-                ]));
+        if !config.port_forwards.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                "端口转发: ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )]));
+            for pf in &config.port_forwards {
                 lines.push(Line::from(vec![
-                    Span::styled("  远程: ", Style::default().add_modifier(Modifier::BOLD)),
-                    Span::raw(&pf.remote),
+                    Span::raw(format!("  {} ", pf.summary())),
+                    Span::styled(
+                        if pf.enabled { "" } else { "(禁用)" },
+                        Style::default().fg(Color::DarkGray),
+                    ),
                 ]));
             }
         } else {
@@ -196,6 +538,34 @@ fn render_config_details(f: &mut Frame, area: Rect, app: &App) {
             ]));
         }
 
+        if let Some(tunnel) = app.tunnel_manager.get(&config.alias) {
+            let status = match &tunnel.status {
+                TunnelStatus::Running => Span::styled(
+                    format!("运行中 (PID {}, 已运行 {})", tunnel.pid, tunnel.uptime()),
+                    Style::default().fg(Color::Green),
+                ),
+                TunnelStatus::Exited(code) => Span::styled(
+                    match code {
+                        Some(code) => format!("已退出 (退出码 {code})"),
+                        None => "已退出".to_string(),
+                    },
+                    Style::default().fg(Color::Red),
+                ),
+            };
+            lines.push(Line::from(vec![
+                Span::styled("隧道: ", Style::default().add_modifier(Modifier::BOLD)),
+                status,
+            ]));
+            for forward in &tunnel.forwards {
+                lines.push(Line::from(format!("  {forward}")));
+            }
+        } else if config.tunnel_only {
+            lines.push(Line::from(vec![
+                Span::styled("隧道: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("未启动 (仅隧道)"),
+            ]));
+        }
+
         lines.push(Line::from(""));
         if config.use_global_proxy {
             lines.push(Line::from(vec![
@@ -213,16 +583,21 @@ fn render_config_details(f: &mut Frame, area: Rect, app: &App) {
                     Span::raw(format!(
                         "{}:{}",
                         global_proxy.host,
-                        global_proxy.port.unwrap_or(match global_proxy.proxy_type {
-                            crate::proxy::ProxyType::Socks5 => 1080,
-                            crate::proxy::ProxyType::Http => 8080,
-                            crate::proxy::ProxyType::None => 0,
-                        })
+                        global_proxy.effective_port().unwrap_or(0)
                     )),
                 ]));
             }
         } else if let Some(proxy) = &config.proxy {
-            if proxy.is_enabled() {
+            if proxy.proxy_type == crate::proxy::ProxyType::Jump && proxy.is_enabled() {
+                lines.push(Line::from(vec![
+                    Span::styled("代理: ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw("跳板机"),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("  链路: ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(proxy.jump_hosts.join(" → ")),
+                ]));
+            } else if proxy.is_enabled() {
                 lines.push(Line::from(vec![
                     Span::styled("代理: ", Style::default().add_modifier(Modifier::BOLD)),
                     Span::raw("自定义代理"),
@@ -236,11 +611,7 @@ fn render_config_details(f: &mut Frame, area: Rect, app: &App) {
                     Span::raw(format!(
                         "{}:{}",
                         proxy.host,
-                        proxy.port.unwrap_or(match proxy.proxy_type {
-                            crate::proxy::ProxyType::Socks5 => 1080,
-                            crate::proxy::ProxyType::Http => 8080,
-                            crate::proxy::ProxyType::None => 0,
-                        })
+                        proxy.effective_port().unwrap_or(0)
                     )),
                 ]));
             } else {
@@ -1,20 +1,30 @@
+mod config_error;
 mod dialog;
 mod form;
 mod import;
+mod keys;
 mod list;
 mod proxy;
 mod scrollbar;
+mod sync;
 
 use crate::app::App;
+use crate::key_manager::KeyDialog;
 use crate::navigation_manager::AppMode;
 use ratatui::{prelude::*, widgets::*};
 
-pub use dialog::render_dialog;
+pub use config_error::render_config_error;
+pub use dialog::{
+    render_agent_prompt, render_config_conflict, render_dialog, render_key_permission_prompt,
+    render_move_to_group, render_vault_prompt,
+};
 pub use form::render_form;
 pub use import::render_import;
+pub use keys::render_keys;
 pub use list::render_list;
 pub use proxy::render_proxy_config;
 pub use scrollbar::{render_scrollbar, ScrollManager};
+pub use sync::render_sync;
 
 /// 渲染主用户界面
 ///
@@ -31,9 +41,34 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         ])
         .split(f.size());
 
-    match *app.mode() {
-        AppMode::List => render_list(f, chunks[0], app),
-        AppMode::AddForm | AppMode::EditForm => render_form(f, chunks[0], app),
+    // 配置冲突、主密码、添加密钥和私钥权限的提示显示在被打断的界面之上
+    let mode = app
+        .config_conflict
+        .as_ref()
+        .map(|conflict| &conflict.previous_mode)
+        .or(app
+            .vault_prompt
+            .as_ref()
+            .map(|prompt| &prompt.previous_mode))
+        .or(app
+            .agent_prompt
+            .as_ref()
+            .map(|prompt| &prompt.previous_mode))
+        .or(app
+            .key_permission_prompt
+            .as_ref()
+            .map(|prompt| &prompt.previous_mode))
+        .unwrap_or(app.mode())
+        .clone();
+    match mode {
+        AppMode::List
+        | AppMode::Search
+        | AppMode::ConfigConflict
+        | AppMode::VaultUnlock
+        | AppMode::AgentAdd => render_list(f, chunks[0], app),
+        AppMode::AddForm | AppMode::EditForm | AppMode::KeyPermission => {
+            render_form(f, chunks[0], app)
+        }
         AppMode::DeleteDialog => render_dialog(
             f,
             chunks[0],
@@ -44,6 +79,17 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         ),
         AppMode::SelectImport => render_import(f, chunks[0], app),
         AppMode::ProxyConfig => render_proxy_config(f, chunks[0], app),
+        AppMode::ConfigError => render_config_error(f, chunks[0], app),
+        AppMode::SyncDiff => render_sync(f, chunks[0], app),
+        AppMode::MoveToGroup => render_move_to_group(f, chunks[0], app),
+        AppMode::Keys => render_keys(f, chunks[0], app),
+    }
+    match *app.mode() {
+        AppMode::ConfigConflict => render_config_conflict(f, chunks[0]),
+        AppMode::VaultUnlock => render_vault_prompt(f, chunks[0], app),
+        AppMode::AgentAdd => render_agent_prompt(f, chunks[0], app),
+        AppMode::KeyPermission => render_key_permission_prompt(f, chunks[0], app),
+        _ => {}
     }
 
     render_message_bar(f, app);
@@ -60,13 +106,31 @@ fn render_help_bar(f: &mut Frame, area: Rect, app: &App) {
     let help_text = match *app.mode() {
         AppMode::List => vec![
             Span::raw("Enter: 连接 | "),
+            Span::raw("/: 搜索 | "),
+            Span::raw("←→: 折叠/展开 | "),
+            Span::raw("Ctrl+G: 移动分组 | "),
+            Span::raw("Ctrl+B: 连接分组 | "),
+            Span::raw("Shift+↑↓: 调整顺序 | "),
+            Span::raw("Ctrl+W: 排序 | "),
+            Span::raw("Ctrl+R: 检测主机 | "),
             Span::raw("Ctrl+N: 新增 | "),
             Span::raw("Ctrl+E: 编辑 | "),
             Span::raw("Ctrl+D: 删除 | "),
             Span::raw("Ctrl+L: 导入 | "),
+            Span::raw("Ctrl+X: 导出 | "),
+            Span::raw("Ctrl+S: 同步 | "),
+            Span::raw("Ctrl+T: 隧道 | "),
             Span::raw("Ctrl+P: 全局代理 | "),
+            Span::raw("Ctrl+K: 密钥管理 | "),
+            Span::raw("Ctrl+V: 解锁密码库 | "),
             Span::raw("Ctrl+Q: 退出"),
         ],
+        AppMode::AddForm | AppMode::EditForm if app.current_field() == 4 => vec![
+            Span::raw("Enter: 保存 | "),
+            Span::raw("Tab: 补全路径 | "),
+            Span::raw("↑↓: 切换字段 | "),
+            Span::raw("Esc: 取消"),
+        ],
         AppMode::AddForm | AppMode::EditForm => vec![
             Span::raw("Enter: 保存 | "),
             Span::raw("Tab/↑↓: 切换字段 | "),
@@ -84,6 +148,68 @@ fn render_help_bar(f: &mut Frame, area: Rect, app: &App) {
             Span::raw("Tab/↑↓: 切换字段 | "),
             Span::raw("Esc: 取消"),
         ],
+        AppMode::Search => vec![
+            Span::raw("输入: 搜索 | "),
+            Span::raw("↑↓: 选择 | "),
+            Span::raw("Enter: 确定 | "),
+            Span::raw("Esc: 清除搜索"),
+        ],
+        AppMode::MoveToGroup => vec![
+            Span::raw("输入: 分组 | "),
+            Span::raw("Tab: 切换已有分组 | "),
+            Span::raw("Enter: 移动 | "),
+            Span::raw("Esc: 取消"),
+        ],
+        AppMode::SyncDiff => vec![
+            Span::raw("↑↓: 选择 | "),
+            Span::raw("Space/←→: 切换处理方式 | "),
+            Span::raw("Enter: 应用 | "),
+            Span::raw("Esc: 取消"),
+        ],
+        AppMode::VaultUnlock => vec![
+            Span::raw("输入: 主密码 | "),
+            Span::raw("Enter: 确定 | "),
+            Span::raw("Esc: 跳过"),
+        ],
+        AppMode::AgentAdd => vec![
+            Span::raw("Enter: 添加并连接 | "),
+            Span::raw("Tab: 切换输入 | "),
+            Span::raw("Ctrl+S: 直接连接 | "),
+            Span::raw("Esc: 取消"),
+        ],
+        AppMode::Keys => match app.navigation.key_manager.dialog {
+            Some(KeyDialog::Generate { .. }) => vec![
+                Span::raw("Enter: 生成 | "),
+                Span::raw("Tab: 切换输入 | "),
+                Span::raw("Esc: 取消"),
+            ],
+            Some(KeyDialog::Deploy { .. }) => vec![
+                Span::raw("↑↓: 选择主机 | "),
+                Span::raw("Enter: 部署 | "),
+                Span::raw("Esc: 取消"),
+            ],
+            None => vec![
+                Span::raw("↑↓: 选择 | "),
+                Span::raw("Enter: 部署到主机 | "),
+                Span::raw("Ctrl+N: 生成密钥 | "),
+                Span::raw("Ctrl+R: 重新扫描 | "),
+                Span::raw("Esc: 返回"),
+            ],
+        },
+        AppMode::KeyPermission => vec![
+            Span::raw("Y/Enter: 修复并保存 | "),
+            Span::raw("N: 直接保存 | "),
+            Span::raw("Esc: 返回编辑"),
+        ],
+        AppMode::ConfigConflict => vec![
+            Span::raw("R: 放弃修改并重新加载 | "),
+            Span::raw("K/Esc: 保留修改"),
+        ],
+        AppMode::ConfigError => vec![
+            Span::raw("E: 编辑 | "),
+            Span::raw("R: 只读启动 | "),
+            Span::raw("Q/Esc: 退出"),
+        ],
     };
 
     let help = Paragraph::new(Line::from(help_text))
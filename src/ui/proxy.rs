@@ -1,4 +1,5 @@
 use crate::app::App;
+use crate::secret::SecretRef;
 use ratatui::{prelude::*, widgets::*};
 
 /// 渲染代理配置界面
@@ -55,8 +56,8 @@ fn render_proxy_field(
 
     let value = app.form_data().get(field_name).cloned().unwrap_or_default();
     let display_value = if field_name.contains("password") {
-        if is_focused && !value.is_empty() {
-            // 显示前面的字符为星号，最后一个字符显示明文
+        if is_focused && !value.is_empty() || SecretRef::parse(&value).is_reference() {
+            // 显示前面的字符为星号，最后一个字符显示明文；引用不含密码本身，直接显示
             value
         } else {
             // 非聚焦或空字符串时全部显示为星号
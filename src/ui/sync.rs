@@ -0,0 +1,115 @@
+use crate::app::App;
+use crate::sync_manager::{SyncResolution, SyncStatus};
+use ratatui::{prelude::*, widgets::*};
+
+/// 渲染同步差异界面
+///
+/// # 参数
+/// - `f`: 绘制 Frame
+/// - `area`: 绘制区域
+/// - `app`: 应用状态
+pub fn render_sync(f: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let sync_manager = &app.navigation.sync_manager;
+    let count_of = |status: SyncStatus| {
+        sync_manager
+            .entries
+            .iter()
+            .filter(|e| e.status == status)
+            .count()
+    };
+    let info_text = format!(
+        "mssh 与 ~/.ssh/config 存在 {} 处差异 (新增: {}, 变更: {}, 仅在 mssh: {})",
+        sync_manager.entries.len(),
+        count_of(SyncStatus::Added),
+        count_of(SyncStatus::Changed),
+        count_of(SyncStatus::Removed),
+    );
+    let info = Paragraph::new(info_text)
+        .block(Block::default().borders(Borders::ALL).title("双向同步"))
+        .alignment(Alignment::Center);
+    f.render_widget(info, chunks[0]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(chunks[1]);
+
+    let items: Vec<ListItem> = sync_manager
+        .entries
+        .iter()
+        .map(|entry| {
+            let (status_text, status_color) = match entry.status {
+                SyncStatus::Added => ("[新增]", Color::Green),
+                SyncStatus::Changed => ("[变更]", Color::Yellow),
+                SyncStatus::Removed => ("[仅 mssh]", Color::Magenta),
+            };
+            let resolution_text = match (&entry.status, &entry.resolution) {
+                (SyncStatus::Added, SyncResolution::UseSsh) => "导入到 mssh",
+                (SyncStatus::Removed, SyncResolution::UseSsh) => "从 mssh 删除",
+                (_, SyncResolution::UseSsh) => "以 ~/.ssh/config 为准",
+                (_, SyncResolution::UseMssh) => "以 mssh 为准",
+                (_, SyncResolution::Skip) => "跳过",
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(status_text, Style::default().fg(status_color)),
+                Span::raw(format!(" {} ", entry.alias)),
+                Span::styled(
+                    format!("→ {resolution_text}"),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("主机"))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default();
+    state.select(Some(sync_manager.selected_index));
+    f.render_stateful_widget(list, body[0], &mut state);
+
+    let rows: Vec<Row> = sync_manager
+        .selected_entry()
+        .map(|entry| {
+            entry
+                .fields
+                .iter()
+                .map(|diff| {
+                    let style = if diff.is_changed() {
+                        Style::default().fg(Color::Yellow)
+                    } else {
+                        Style::default()
+                    };
+                    Row::new(vec![
+                        diff.field.to_string(),
+                        diff.mssh.clone(),
+                        diff.ssh.clone(),
+                    ])
+                    .style(style)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let table = Table::new(rows)
+        .header(
+            Row::new(vec!["字段", "mssh", "~/.ssh/config"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .widths(&[
+            Constraint::Length(14),
+            Constraint::Percentage(43),
+            Constraint::Percentage(43),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("字段差异"));
+
+    f.render_widget(table, body[1]);
+}
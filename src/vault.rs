@@ -0,0 +1,786 @@
+use crate::config::ConfigManager;
+use crate::secret::SecretRef;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// 配置中引用密码库条目的前缀，如 `vault:proxy/global`
+pub const VAULT_PREFIX: &str = "vault:";
+
+/// 非交互环境中提供主密码的环境变量
+pub const MASTER_PASSWORD_ENV: &str = "MSSH_VAULT_PASSWORD";
+
+/// 密码库文件格式的版本
+const VAULT_VERSION: u32 = 1;
+/// 密文的附加认证数据，防止把其他文件的密文当作密码库解密
+const ASSOCIATED_DATA: &[u8] = b"mssh-vault-v1";
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+/// Argon2id 的内存开销（KiB）、迭代次数和并行度
+const MEMORY_KIB: u32 = 19 * 1024;
+const ITERATIONS: u32 = 2;
+const PARALLELISM: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum VaultError {
+    #[error("密码库尚未创建")]
+    NotFound,
+    #[error("密码库 {} 已存在", .0.display())]
+    AlreadyExists(PathBuf),
+    #[error("密码库未解锁")]
+    Locked,
+    #[error("主密码错误，或密码库已损坏")]
+    WrongPassword,
+    #[error("密码库中没有条目: {0}")]
+    MissingSecret(String),
+    #[error("无法读取密码库 {}: {source}", .path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("无法写入密码库 {}: {source}", .path.display())]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("密码库 {} 格式无效: {message}", .path.display())]
+    Format { path: PathBuf, message: String },
+}
+
+/// 派生密钥使用的 Argon2id 参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    /// Base64 编码的盐
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+/// 密码库文件，条目以 JSON 序列化后整体加密
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    /// Base64 编码的 XChaCha20-Poly1305 随机数
+    nonce: String,
+    /// Base64 编码的密文
+    ciphertext: String,
+}
+
+/// 解锁后的密钥和条目
+#[derive(Clone)]
+struct Unlocked {
+    key: Zeroizing<[u8; KEY_LEN]>,
+    kdf: KdfParams,
+    secrets: BTreeMap<String, Zeroizing<String>>,
+}
+
+/// 加密保存代理密码等敏感信息的密码库
+///
+/// 密码库位于配置文件所在目录的 `vault.toml`，主密码经 Argon2id 派生出密钥，
+/// 条目以 XChaCha20-Poly1305 加密。配置文件中只保存 `vault:<条目>` 形式的引用。
+#[derive(Clone)]
+pub struct Vault {
+    path: PathBuf,
+    unlocked: Option<Unlocked>,
+}
+
+impl std::fmt::Debug for Vault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 不输出密钥和条目内容
+        f.debug_struct("Vault")
+            .field("path", &self.path)
+            .field("unlocked", &self.unlocked.is_some())
+            .finish()
+    }
+}
+
+impl Vault {
+    /// 创建与配置文件位于同一目录的密码库，此时尚未读取文件
+    ///
+    /// # 参数
+    /// - `config_path`: 配置文件路径
+    ///
+    /// # 返回
+    /// 返回未解锁的密码库
+    pub fn new(config_path: &Path) -> Self {
+        Self {
+            path: config_path.with_file_name("vault.toml"),
+            unlocked: None,
+        }
+    }
+
+    /// 获取密码库文件路径
+    ///
+    /// # 返回
+    /// 返回密码库文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 检查密码库文件是否存在
+    ///
+    /// # 返回
+    /// 返回 true 表示已创建密码库
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// 检查密码库是否已解锁
+    ///
+    /// # 返回
+    /// 返回 true 表示已解锁
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked.is_some()
+    }
+
+    /// 以主密码创建新的空密码库
+    ///
+    /// # 参数
+    /// - `password`: 主密码
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，密码库已存在或写入失败时为 Err
+    pub fn create(&mut self, password: &str) -> Result<(), VaultError> {
+        if self.exists() {
+            return Err(VaultError::AlreadyExists(self.path.clone()));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let kdf = KdfParams {
+            salt: STANDARD.encode(salt),
+            memory_kib: MEMORY_KIB,
+            iterations: ITERATIONS,
+            parallelism: PARALLELISM,
+        };
+        let key = self.derive_key(password, &kdf)?;
+        self.unlocked = Some(Unlocked {
+            key,
+            kdf,
+            secrets: BTreeMap::new(),
+        });
+        self.save()
+    }
+
+    /// 以主密码解锁密码库
+    ///
+    /// # 参数
+    /// - `password`: 主密码
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，密码错误或文件无法读取时为 Err
+    pub fn unlock(&mut self, password: &str) -> Result<(), VaultError> {
+        let file = self.read_file()?;
+        let key = self.derive_key(password, &file.kdf)?;
+        let secrets = self.decrypt(&file, &key)?;
+        self.unlocked = Some(Unlocked {
+            key,
+            kdf: file.kdf,
+            secrets,
+        });
+        Ok(())
+    }
+
+    /// 在终端中输入主密码并解锁，密码库不存在时创建
+    ///
+    /// 设置了 `MSSH_VAULT_PASSWORD` 环境变量时直接使用其中的主密码。
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    pub fn unlock_from_terminal(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.is_unlocked() {
+            return Ok(());
+        }
+        if let Ok(password) = std::env::var(MASTER_PASSWORD_ENV) {
+            let password = Zeroizing::new(password);
+            return if self.exists() {
+                Ok(self.unlock(&password)?)
+            } else {
+                Ok(self.create(&password)?)
+            };
+        }
+
+        let read_password = |prompt: &str| {
+            read_password(prompt)
+                .map_err(|e| format!("{e}，非交互环境可通过 {MASTER_PASSWORD_ENV} 提供主密码"))
+        };
+        if self.exists() {
+            let password = read_password("密码库主密码: ")?;
+            return Ok(self.unlock(&password)?);
+        }
+
+        eprintln!("将在 {} 创建密码库", self.path.display());
+        let password = read_password("设置主密码: ")?;
+        if password.is_empty() {
+            return Err("主密码不能为空".into());
+        }
+        let confirm = read_password("再次输入主密码: ")?;
+        if *password != *confirm {
+            return Err("两次输入的主密码不一致".into());
+        }
+        Ok(self.create(&password)?)
+    }
+
+    /// 获取条目的内容
+    ///
+    /// # 参数
+    /// - `id`: 条目名称
+    ///
+    /// # 返回
+    /// 返回 Result，成功为条目内容，未解锁或条目不存在时为 Err
+    pub fn get(&self, id: &str) -> Result<&str, VaultError> {
+        let unlocked = self.unlocked.as_ref().ok_or(VaultError::Locked)?;
+        unlocked
+            .secrets
+            .get(id)
+            .map(|secret| secret.as_str())
+            .ok_or_else(|| VaultError::MissingSecret(id.to_string()))
+    }
+
+    /// 获取所有条目名称
+    ///
+    /// # 返回
+    /// 返回按名称排序的条目，未解锁时为空
+    pub fn ids(&self) -> Vec<&str> {
+        self.unlocked
+            .iter()
+            .flat_map(|unlocked| unlocked.secrets.keys())
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// 添加或修改条目并保存
+    ///
+    /// 写入前重新读取文件，避免覆盖其他 mssh 进程写入的条目。
+    ///
+    /// # 参数
+    /// - `id`: 条目名称
+    /// - `secret`: 条目内容
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，失败为 Err
+    pub fn set(&mut self, id: &str, secret: &str) -> Result<(), VaultError> {
+        self.refresh()?;
+        if let Some(unlocked) = &mut self.unlocked {
+            unlocked
+                .secrets
+                .insert(id.to_string(), Zeroizing::new(secret.to_string()));
+        }
+        self.save()
+    }
+
+    /// 删除条目并保存
+    ///
+    /// # 参数
+    /// - `id`: 条目名称
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，条目不存在或写入失败时为 Err
+    pub fn remove(&mut self, id: &str) -> Result<(), VaultError> {
+        self.refresh()?;
+        let unlocked = self.unlocked.as_mut().ok_or(VaultError::Locked)?;
+        if unlocked.secrets.remove(id).is_none() {
+            return Err(VaultError::MissingSecret(id.to_string()));
+        }
+        self.save()
+    }
+
+    /// 将明文密码移入密码库，并替换为引用
+    ///
+    /// # 参数
+    /// - `password`: 配置中的密码，已是引用或为空时不做修改
+    /// - `id`: 保存到的条目名称
+    ///
+    /// # 返回
+    /// 返回 Result，成功为是否移入了密码，未解锁或写入失败时为 Err
+    pub fn seal(&mut self, password: &mut Option<String>, id: &str) -> Result<bool, VaultError> {
+        let Some(plaintext) = password.as_deref().filter(|p| is_plaintext(p)) else {
+            return Ok(false);
+        };
+        if !self.is_unlocked() {
+            return Err(VaultError::Locked);
+        }
+        self.set(id, plaintext)?;
+        *password = Some(reference(id));
+        Ok(true)
+    }
+
+    /// 以已解锁的密钥重新读取文件，获取其他进程写入的条目
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，未解锁、主密码已被修改或文件无法读取时为 Err
+    fn refresh(&mut self) -> Result<(), VaultError> {
+        let unlocked = self.unlocked.as_ref().ok_or(VaultError::Locked)?;
+        if !self.exists() {
+            return Ok(());
+        }
+        let file = self.read_file()?;
+        let secrets = self.decrypt(&file, &unlocked.key)?;
+        if let Some(unlocked) = &mut self.unlocked {
+            unlocked.secrets = secrets;
+        }
+        Ok(())
+    }
+
+    /// 读取并解析密码库文件
+    ///
+    /// # 返回
+    /// 返回 Result，成功为密码库文件内容，失败为 Err
+    fn read_file(&self) -> Result<VaultFile, VaultError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(VaultError::NotFound),
+            Err(source) => {
+                return Err(VaultError::Read {
+                    path: self.path.clone(),
+                    source,
+                })
+            }
+        };
+        let file: VaultFile = toml::from_str(&content).map_err(|e| self.format_error(e))?;
+        if file.version != VAULT_VERSION {
+            return Err(self.format_error(format!("不支持的版本 {}", file.version)));
+        }
+        Ok(file)
+    }
+
+    /// 由主密码派生密钥
+    ///
+    /// # 参数
+    /// - `password`: 主密码
+    /// - `kdf`: Argon2id 参数
+    ///
+    /// # 返回
+    /// 返回 Result，成功为密钥，参数无效时为 Err
+    fn derive_key(
+        &self,
+        password: &str,
+        kdf: &KdfParams,
+    ) -> Result<Zeroizing<[u8; KEY_LEN]>, VaultError> {
+        let salt = STANDARD
+            .decode(&kdf.salt)
+            .map_err(|e| self.format_error(e))?;
+        let params = Params::new(
+            kdf.memory_kib,
+            kdf.iterations,
+            kdf.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|e| self.format_error(e))?;
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, key.as_mut())
+            .map_err(|e| self.format_error(e))?;
+        Ok(key)
+    }
+
+    /// 解密密码库文件中的条目
+    ///
+    /// # 参数
+    /// - `file`: 密码库文件内容
+    /// - `key`: 密钥
+    ///
+    /// # 返回
+    /// 返回 Result，成功为条目，密钥错误或内容被篡改时为 Err
+    fn decrypt(
+        &self,
+        file: &VaultFile,
+        key: &[u8; KEY_LEN],
+    ) -> Result<BTreeMap<String, Zeroizing<String>>, VaultError> {
+        let nonce = STANDARD
+            .decode(&file.nonce)
+            .map_err(|e| self.format_error(e))?;
+        if nonce.len() != 24 {
+            return Err(self.format_error("随机数长度无效"));
+        }
+        let ciphertext = STANDARD
+            .decode(&file.ciphertext)
+            .map_err(|e| self.format_error(e))?;
+        let plaintext = Zeroizing::new(
+            XChaCha20Poly1305::new(key.into())
+                .decrypt(
+                    XNonce::from_slice(&nonce),
+                    Payload {
+                        msg: &ciphertext,
+                        aad: ASSOCIATED_DATA,
+                    },
+                )
+                .map_err(|_| VaultError::WrongPassword)?,
+        );
+        let secrets: BTreeMap<String, String> =
+            serde_json::from_slice(&plaintext).map_err(|e| self.format_error(e))?;
+        Ok(secrets
+            .into_iter()
+            .map(|(id, secret)| (id, Zeroizing::new(secret)))
+            .collect())
+    }
+
+    /// 以新的随机数加密条目并写入文件，文件权限为 0600
+    ///
+    /// # 返回
+    /// 返回 Result，成功为 Ok(())，未解锁或写入失败时为 Err
+    fn save(&self) -> Result<(), VaultError> {
+        let unlocked = self.unlocked.as_ref().ok_or(VaultError::Locked)?;
+        let secrets: BTreeMap<&str, &str> = unlocked
+            .secrets
+            .iter()
+            .map(|(id, secret)| (id.as_str(), secret.as_str()))
+            .collect();
+        let plaintext =
+            Zeroizing::new(serde_json::to_vec(&secrets).map_err(|e| self.format_error(e))?);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(unlocked.key.as_ref().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: ASSOCIATED_DATA,
+                },
+            )
+            .map_err(|_| self.format_error("加密失败"))?;
+
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            kdf: unlocked.kdf.clone(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        let content = toml::to_string_pretty(&file).map_err(|e| self.format_error(e))?;
+
+        let write_error = |source| VaultError::Write {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(write_error)?;
+            }
+        }
+        if !self.exists() {
+            // 先创建仅所有者可读写的空文件，原子写入时会保留该权限
+            create_private_file(&self.path).map_err(write_error)?;
+        }
+        ConfigManager::write_atomic(&self.path, &content).map_err(write_error)
+    }
+
+    /// 生成格式错误
+    ///
+    /// # 参数
+    /// - `message`: 错误信息
+    ///
+    /// # 返回
+    /// 返回 VaultError::Format
+    fn format_error(&self, message: impl std::fmt::Display) -> VaultError {
+        VaultError::Format {
+            path: self.path.clone(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// 获取引用指向的条目名称
+///
+/// # 参数
+/// - `value`: 配置中的密码
+///
+/// # 返回
+/// 返回条目名称，不是 `vault:` 引用时为 None
+pub fn secret_id(value: &str) -> Option<&str> {
+    value.strip_prefix(VAULT_PREFIX)
+}
+
+/// 生成指向条目的引用
+///
+/// # 参数
+/// - `id`: 条目名称
+///
+/// # 返回
+/// 返回形如 `vault:proxy/global` 的引用
+pub fn reference(id: &str) -> String {
+    format!("{VAULT_PREFIX}{id}")
+}
+
+/// 检查配置中的密码是否为明文
+///
+/// # 参数
+/// - `value`: 配置中的密码
+///
+/// # 返回
+/// 返回 true 表示非空且不是密码库或其他来源的引用
+pub fn is_plaintext(value: &str) -> bool {
+    !value.is_empty() && !SecretRef::parse(value).is_reference()
+}
+
+/// 全局代理密码使用的条目名称
+pub const GLOBAL_PROXY_SECRET_ID: &str = "proxy/global";
+
+/// 生成代理密码使用的条目名称
+///
+/// 主机的条目名称带随机后缀，主机改名后新建同名主机时不会覆盖原来的密码。
+///
+/// # 参数
+/// - `alias`: 使用自定义代理的主机别名，全局代理为 None
+///
+/// # 返回
+/// 返回 `proxy/global` 或形如 `proxy/<别名>-<8 位十六进制>` 的条目名称
+pub fn proxy_secret_id(alias: Option<&str>) -> String {
+    match alias {
+        Some(alias) => format!("proxy/{alias}-{:08x}", OsRng.next_u32()),
+        None => GLOBAL_PROXY_SECRET_ID.to_string(),
+    }
+}
+
+/// 生成私钥口令使用的条目名称
+///
+/// # 参数
+/// - `key`: 主机配置中的密钥路径
+///
+/// # 返回
+/// 返回形如 `key/<密钥路径>` 的条目名称
+pub fn key_secret_id(key: &str) -> String {
+    format!("key/{key}")
+}
+
+/// 获取主机代理密码引用的、由 mssh 生成的条目名称
+///
+/// # 参数
+/// - `password`: 主机配置中的代理密码
+///
+/// # 返回
+/// 返回 `proxy/` 下的条目名称，其他引用、明文或全局代理的条目为 None
+pub fn host_secret_id(password: Option<&str>) -> Option<&str> {
+    password
+        .and_then(secret_id)
+        .filter(|id| id.starts_with("proxy/") && *id != GLOBAL_PROXY_SECRET_ID)
+}
+
+/// 创建仅所有者可读写的空文件
+///
+/// # 参数
+/// - `path`: 文件路径
+///
+/// # 返回
+/// 返回 io::Result，成功为 Ok(())，失败为 Err
+fn create_private_file(path: &Path) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path).map(|_| ())
+}
+
+/// 在终端中读取不回显的密码
+///
+/// 提示输出到标准错误；按键从终端读取，标准输入被重定向时同样可用。
+///
+/// # 参数
+/// - `prompt`: 提示文字
+///
+/// # 返回
+/// 返回 Result，成功为输入的密码，按 Ctrl+C 或 Esc 取消时为 Err
+pub fn read_password(prompt: &str) -> io::Result<Zeroizing<String>> {
+    eprint!("{prompt}");
+    io::stderr().flush()?;
+
+    enable_raw_mode().map_err(|e| io::Error::new(e.kind(), format!("无法从终端读取: {e}")))?;
+    let result = (|| {
+        let mut password = Zeroizing::new(String::new());
+        loop {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match key.code {
+                KeyCode::Enter => return Ok(password),
+                KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Backspace => {
+                    password.pop();
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    password.push(c)
+                }
+                _ => {}
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::Interrupted, "已取消"))
+    })();
+    disable_raw_mode()?;
+    eprintln!();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    /// 在临时目录中创建已解锁的密码库，目录在返回的 TempDir 销毁时删除
+    fn vault(name: &str) -> (TempDir, Vault) {
+        let dir = TempDir::new(name);
+        let mut vault = Vault::new(&dir.join("config.toml"));
+        vault.create("master").unwrap();
+        (dir, vault)
+    }
+
+    /// 修改密码库文件的内容
+    fn tamper(vault: &Vault, change: impl FnOnce(&mut VaultFile)) {
+        let mut file = vault.read_file().unwrap();
+        change(&mut file);
+        fs::write(vault.path(), toml::to_string_pretty(&file).unwrap()).unwrap();
+    }
+
+    /// 翻转 Base64 编码内容的第一个字节
+    fn flip_first_byte(encoded: &mut String) {
+        let mut bytes = STANDARD.decode(&*encoded).unwrap();
+        bytes[0] ^= 0x01;
+        *encoded = STANDARD.encode(bytes);
+    }
+
+    #[test]
+    fn unlocks_what_was_saved() {
+        let (dir, mut vault) = vault("round-trip");
+        vault.set("proxy/global", "secret").unwrap();
+        assert!(!fs::read_to_string(vault.path()).unwrap().contains("secret"));
+
+        let mut reopened = Vault::new(&dir.join("config.toml"));
+        assert!(reopened.exists());
+        assert!(!reopened.is_unlocked());
+        assert!(matches!(
+            reopened.get("proxy/global"),
+            Err(VaultError::Locked)
+        ));
+        reopened.unlock("master").unwrap();
+        assert_eq!(reopened.get("proxy/global").unwrap(), "secret");
+        assert!(matches!(
+            reopened.create("master"),
+            Err(VaultError::AlreadyExists(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn vault_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, vault) = vault("mode");
+        let mode = fs::metadata(vault.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn rejects_wrong_master_password() {
+        let (dir, _vault) = vault("wrong-password");
+        let mut reopened = Vault::new(&dir.join("config.toml"));
+        assert!(matches!(
+            reopened.unlock("not-master"),
+            Err(VaultError::WrongPassword)
+        ));
+        assert!(!reopened.is_unlocked());
+    }
+
+    #[test]
+    fn rejects_tampered_ciphertext() {
+        let (dir, mut vault) = vault("tampered-ciphertext");
+        vault.set("proxy/global", "secret").unwrap();
+        tamper(&vault, |file| flip_first_byte(&mut file.ciphertext));
+
+        let mut reopened = Vault::new(&dir.join("config.toml"));
+        assert!(matches!(
+            reopened.unlock("master"),
+            Err(VaultError::WrongPassword)
+        ));
+        // 已解锁的密码库在修改前重新读取文件，同样拒绝被篡改的内容
+        assert!(matches!(
+            vault.set("proxy/other", "x"),
+            Err(VaultError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn rejects_tampered_nonce() {
+        let (dir, vault) = vault("tampered-nonce");
+        tamper(&vault, |file| flip_first_byte(&mut file.nonce));
+        let mut reopened = Vault::new(&dir.join("config.toml"));
+        assert!(matches!(
+            reopened.unlock("master"),
+            Err(VaultError::WrongPassword)
+        ));
+
+        tamper(&vault, |file| file.nonce = STANDARD.encode([0u8; 12]));
+        assert!(matches!(
+            reopened.unlock("master"),
+            Err(VaultError::Format { .. })
+        ));
+    }
+
+    #[test]
+    fn set_and_remove_persist() {
+        let (dir, mut vault) = vault("persist");
+        vault.set("proxy/global", "one").unwrap();
+        vault.set("key/~/.ssh/id_ed25519", "two").unwrap();
+        vault.set("proxy/global", "three").unwrap();
+        vault.remove("key/~/.ssh/id_ed25519").unwrap();
+        assert!(matches!(
+            vault.remove("key/~/.ssh/id_ed25519"),
+            Err(VaultError::MissingSecret(_))
+        ));
+
+        let mut reopened = Vault::new(&dir.join("config.toml"));
+        reopened.unlock("master").unwrap();
+        assert_eq!(reopened.ids(), vec!["proxy/global"]);
+        assert_eq!(reopened.get("proxy/global").unwrap(), "three");
+    }
+
+    #[test]
+    fn seals_only_plaintext() {
+        let (_dir, mut vault) = vault("seal");
+        let mut password = Some("secret".to_string());
+        assert!(vault.seal(&mut password, "proxy/global").unwrap());
+        assert_eq!(password.as_deref(), Some("vault:proxy/global"));
+        assert_eq!(vault.get("proxy/global").unwrap(), "secret");
+
+        let mut reference = Some("env:PROXY_PASSWORD".to_string());
+        assert!(!vault.seal(&mut reference, "proxy/other").unwrap());
+        assert_eq!(reference.as_deref(), Some("env:PROXY_PASSWORD"));
+        assert!(!vault.seal(&mut None, "proxy/other").unwrap());
+    }
+
+    #[test]
+    fn host_secrets_get_unique_ids() {
+        let first = proxy_secret_id(Some("web"));
+        let second = proxy_secret_id(Some("web"));
+        assert!(first.starts_with("proxy/web-"));
+        // 改名后新建的同名主机不会覆盖原来的条目
+        assert_ne!(first, second);
+        assert_eq!(proxy_secret_id(None), GLOBAL_PROXY_SECRET_ID);
+        assert_eq!(key_secret_id("~/.ssh/id_ed25519"), "key/~/.ssh/id_ed25519");
+
+        let reference = reference(&first);
+        assert_eq!(secret_id(&reference), Some(first.as_str()));
+        assert_eq!(host_secret_id(Some(&reference)), Some(first.as_str()));
+        assert_eq!(host_secret_id(Some("vault:proxy/global")), None);
+        assert_eq!(host_secret_id(Some("vault:key/id")), None);
+        assert_eq!(host_secret_id(Some("env:PROXY_PASSWORD")), None);
+        assert_eq!(host_secret_id(Some("plaintext")), None);
+    }
+}